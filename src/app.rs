@@ -1,10 +1,26 @@
 use eframe::egui;
-use rusqlite::{params, Connection, Result};
-use std::error::Error;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use crate::db::{Eucarinogammarus, import_csv, load_records};
-use crate::views::{view_tab, add_tab, edit_tab, delete_tab};
+use eucarinogammarus_db::audit::{self, AuditEntry};
+use eucarinogammarus_db::backup::{Snapshot, SnapshotInfo, SnapshotReason};
+use eucarinogammarus_db::bulk::{self, BulkChange, BulkMode};
+use eucarinogammarus_db::{Database, DbError};
+use eucarinogammarus_db::config::{self, Config};
+use eucarinogammarus_db::crypto;
+use eucarinogammarus_db::db::{Eucarinogammarus, FieldError, SortDirection, COLUMNS};
+use eucarinogammarus_db::duplicates::{self, DuplicateGroup};
+use eucarinogammarus_db::merge::PendingMerge;
+use eucarinogammarus_db::dwca::export::{DwcMapping, ExportOptions};
+use eucarinogammarus_db::dwca::import::{self, PendingImport};
+use eucarinogammarus_db::similarity::{self, DistanceMatrix};
+use eucarinogammarus_db::sync::SyncEntry;
+use eucarinogammarus_db::trash::TrashedRecord;
+use eucarinogammarus_db::undo::{Command, FieldWrite, UndoStack};
+use eucarinogammarus_db::vocabulary::{self, Term};
+use crate::views::{view_tab, add_tab, edit_tab, delete_tab, export_tab, import_tab, compare_tab, detail_window, trash_tab, bulk_tab, duplicates_tab, quality_tab, vocabulary_tab, file_dialog, startup_screen, error_log, backup_window, merge_window, sync_window, password_window, encryption_window, conflict_window};
 
 #[derive(Debug, PartialEq)]
 pub enum Tab {
@@ -12,17 +28,34 @@ pub enum Tab {
     Add,
     Edit,
     Delete,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub enum SortDirection {
-    Ascending,
-    Descending,
+    Export,
+    Import,
+    Compare,
+    Trash,
+    Bulk,
+    Duplicates,
+    Quality,
+    Vocabulary,
 }
 
 pub struct EucarinogammarusApp {
-    pub conn: Arc<Mutex<Connection>>,
+    pub db: Arc<Mutex<Database>>,
+    pub database_path: PathBuf,
+    // Ошибка открытия базы при запуске; пока она есть, вместо вкладок показывается экран запуска
+    pub startup: Option<StartupForm>,
+    pub config: Config,
+    pub file_form: FileForm,
+    pub backup_form: BackupForm,
+    pub merge_form: MergeForm,
+    pub sync_form: SyncForm,
+    pub password_form: PasswordForm,
+    pub encryption_form: EncryptionForm,
     pub records: Vec<Eucarinogammarus>,
+    // Версии загруженных записей: с ними сверяется сохранение правки
+    pub versions: HashMap<i32, i64>,
+    // PRAGMA data_version на момент загрузки записей и время последней проверки
+    pub data_version: i64,
+    pub last_poll: Instant,
     pub selected_tab: Tab,
     pub search_term: String,
     pub sort_column: String,
@@ -31,21 +64,280 @@ pub struct EucarinogammarusApp {
     pub edit_id: String,
     pub edit_column: String,
     pub edit_value: String,
+    // ID и версия записи, когда её выбрали для правки
+    pub edit_version: Option<(i32, i64)>,
+    // Правка, которую не удалось сохранить: запись успел изменить другой пользователь
+    pub conflict: Option<EditConflict>,
+    // Нарушения правил проверки в формах добавления и редактирования
+    pub form_errors: Vec<FieldError>,
+    pub quality_report: Option<Vec<FieldError>>,
     pub delete_id: String,
+    pub delete_confirm: Option<i32>,
+    pub trash: Vec<TrashedRecord>,
+    pub purge_confirm: Option<i32>,
+    pub bulk_form: BulkForm,
+    pub duplicate_form: DuplicateForm,
+    pub vocabulary_form: VocabularyForm,
+    // Словари столбцов для подсказок; сбрасываются после изменения данных
+    pub vocabularies: HashMap<String, Vec<Term>>,
+    pub export_form: ExportForm,
+    pub import_form: ImportForm,
+    pub compare_ids: Vec<i32>,
+    pub detail_id: Option<i32>,
+    pub similarity: Option<DistanceMatrix>,
+    pub history: Option<(i32, Vec<AuditEntry>)>,
+    pub undo_stack: UndoStack,
     pub status_message: String,
+    // Журнал ошибок за сеанс, от старых к новым
+    pub error_log: Vec<ErrorEntry>,
+    pub show_error_log: bool,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
+pub struct ErrorEntry {
+    pub time: Instant,
+    pub title: &'static str,
+    pub message: String,
+}
+
+// Значения новой записи в порядке COLUMNS
+#[derive(Debug, Clone)]
 pub struct NewRecord {
-    pub code: String,
-    pub genus: String,
-    pub species: String,
-    pub size_mm: String,
-    pub body: String,
+    pub values: Vec<String>,
+}
+
+impl Default for NewRecord {
+    fn default() -> Self {
+        Self {
+            values: vec![String::new(); COLUMNS.len()],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BulkForm {
+    pub column: String,
+    pub mode: BulkMode,
+    pub find: String,
+    pub value: String,
+    pub preview: Vec<BulkChange>,
+    pub preview_column: String,
+}
+
+impl Default for BulkForm {
+    fn default() -> Self {
+        Self {
+            column: String::new(),
+            mode: BulkMode::Set,
+            find: String::new(),
+            value: String::new(),
+            preview: Vec::new(),
+            preview_column: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DuplicateForm {
+    pub threshold: f64,
+    pub groups: Vec<DuplicateGroup>,
+    pub selected: Option<usize>,
+    pub keep_id: i32,
+    // Для каждого столбца из COLUMNS — ID записи, чьё значение сохраняется
+    pub choices: Vec<i32>,
+}
+
+impl Default for DuplicateForm {
+    fn default() -> Self {
+        Self {
+            threshold: 0.85,
+            groups: Vec::new(),
+            selected: None,
+            keep_id: 0,
+            choices: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct VocabularyForm {
+    pub column: String,
+    pub selected: BTreeSet<String>,
+    pub target: String,
+    pub new_term: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportForm {
+    pub file_path: String,
+    pub mapping_path: String,
+    pub options: ExportOptions,
+    pub issues: Vec<String>,
+    pub matrix_path: String,
+    pub newick_path: String,
+}
+
+impl Default for ExportForm {
+    fn default() -> Self {
+        Self {
+            file_path: "eucarinogammarus-dwca.zip".to_string(),
+            mapping_path: String::new(),
+            options: ExportOptions::default(),
+            issues: Vec::new(),
+            matrix_path: "distance-matrix.csv".to_string(),
+            newick_path: "upgma.nwk".to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StartupForm {
+    pub path: PathBuf,
+    pub error: DbError,
+    pub open_path: String,
+    pub new_path: String,
+    pub csv_path: String,
+    // Пароль, если база зашифрована
+    pub password: String,
+}
+
+impl StartupForm {
+    fn new(path: &Path, error: DbError) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            error,
+            open_path: String::new(),
+            new_path: if path.exists() { String::new() } else { path.display().to_string() },
+            csv_path: config::seed_csv_path(path).display().to_string(),
+            password: String::new(),
+        }
+    }
+}
+
+// Действие меню «Файл», для которого открыто окно выбора пути
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileAction {
+    Open,
+    New,
+    SaveAs,
+    // Выгрузка в текстовый каталог; path — каталог
+    ExportText,
+    // Новая база path из текстового каталога directory
+    FromText,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FileForm {
+    pub action: Option<FileAction>,
+    pub path: String,
+    pub directory: String,
+}
+
+// Окно сравнения с другим файлом базы
+#[derive(Debug, Default, Clone)]
+pub struct MergeForm {
+    pub open: bool,
+    pub path: String,
+    pub pending: Option<PendingMerge>,
+}
+
+// Запрос пароля для открываемой зашифрованной базы
+#[derive(Debug, Default, Clone)]
+pub struct PasswordForm {
+    pub path: Option<PathBuf>,
+    pub password: String,
+}
+
+// Окно включения, смены и снятия шифрования текущей базы
+#[derive(Debug, Default, Clone)]
+pub struct EncryptionForm {
+    pub open: bool,
+    pub password: String,
+    pub confirm: String,
+}
+
+// Окно синхронизации с другими копиями базы через файлы наборов изменений
+#[derive(Debug, Default, Clone)]
+pub struct SyncForm {
+    pub open: bool,
+    pub export_path: String,
+    pub import_path: String,
+    // Выгружать только изменения после последней выгрузки
+    pub incremental: bool,
+    pub log: Vec<SyncEntry>,
+}
+
+// Окно снимков базы: список, предпросмотр выбранного и подтверждение восстановления
+#[derive(Debug, Default, Clone)]
+pub struct BackupForm {
+    pub open: bool,
+    pub snapshots: Vec<Snapshot>,
+    pub selected: Option<usize>,
+    pub preview: Option<SnapshotInfo>,
+    pub confirm_restore: bool,
+}
+
+// Столкновение правки с изменением записи другим пользователем
+#[derive(Debug, Clone)]
+pub struct EditConflict {
+    pub id: i32,
+    pub name: String,
+    pub column: String,
+    // Значение из формы и значение, которое сейчас в базе
+    pub mine: String,
+    pub theirs: String,
+    // Версия записи в базе, поверх которой можно сохранить своё значение
+    pub version: i64,
+    // Последнее изменение записи по журналу: кто и когда
+    pub changed_by: Option<AuditEntry>,
+    // Операция, которую прервал конфликт (массовая правка, слияние, отмена);
+    // None — правка одного поля на вкладке редактирования
+    pub operation: Option<String>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ImportForm {
+    pub file_path: String,
+    pub pending: Option<PendingImport>,
+}
+
+// База, открытая при запуске, до создания окна
+pub struct StartupDatabase {
+    db: Database,
+    database_path: PathBuf,
+    config: Config,
+    // Экран запуска, если базу из настроек или --db открыть не удалось
+    startup: Option<StartupForm>,
+    settings_error: Option<DbError>,
+}
+
+impl StartupDatabase {
+    // db_path — файл из аргумента --db; без него открывается последняя база из настроек.
+    // Ошибка возвращается, только если не создаётся даже пустая база в памяти
+    pub fn open(db_path: Option<PathBuf>) -> Result<Self, DbError> {
+        // Подключение к базе данных: схема, журнал изменений и начальный импорт
+        let loaded = Config::load();
+        let mut config = loaded.as_ref().cloned().unwrap_or_default();
+        let database_path = config.database_path(db_path.as_deref());
+        let (db, startup) = match Database::open_seeded(&database_path, config::seed_csv_path(&database_path)) {
+            Ok(db) => (db, None),
+            // До выбора на экране запуска приложение работает с пустой базой в памяти
+            Err(error) => (Database::open_in_memory()?, Some(StartupForm::new(&database_path, error))),
+        };
+        
+        // Испорченный файл настроек не перезаписывается, чтобы его можно было исправить
+        let mut settings_error = None;
+        if startup.is_none() {
+            config.remember(&database_path);
+            settings_error = loaded.and_then(|_| config.save()).err();
+        }
+        
+        Ok(Self { db, database_path, config, startup, settings_error })
+    }
 }
 
 impl EucarinogammarusApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, opened: StartupDatabase) -> Self {
         // Настройка стиля
         let mut style = (*cc.egui_ctx.style()).clone();
         style.text_styles = [
@@ -57,60 +349,25 @@ impl EucarinogammarusApp {
         ].into();
         cc.egui_ctx.set_style(style);
         
-        // Подключение к базе данных
-        let conn = Connection::open("eucarinogammarus.db").expect("Не удалось подключиться к базе данных");
-        
-        // Создание таблицы, если она не существует
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS Eucarinogammarus (
-                id INTEGER PRIMARY KEY,
-                Код TEXT,
-                Род TEXT,
-                Вид TEXT,
-                Размеры_мм TEXT,
-                Тело TEXT,
-                Окраска TEXT,
-                Распространение TEXT,
-                Глубина_м TEXT,
-                Вооруж_тела TEXT,
-                Средний_ряд_I_VII TEXT,
-                Средн_ряд_VIII_X TEXT,
-                Сред_ряд_урозом TEXT,
-                Боковой_ряд TEXT,
-                Краевой_ряд TEXT,
-                Особен_воор TEXT,
-                Эпимир_пласт TEXT,
-                Верх_антенны TEXT,
-                Прид_жгутик TEXT,
-                Нижн_антенны TEXT,
-                Базип_III_V TEXT,
-                Уроподы_III TEXT,
-                Головн_сегм TEXT,
-                Глаза TEXT,
-                Тельсон TEXT
-            )",
-            [],
-        ).expect("Не удалось создать таблицу");
-        
-        // Импорт данных из CSV, если таблица пуста
-        let count: i64 = {
-            let mut stmt = conn.prepare("SELECT COUNT(*) FROM Eucarinogammarus").expect("Ошибка запроса");
-            stmt.query_row([], |row| row.get(0)).expect("Ошибка получения количества записей")
-        };
-        
-        if count == 0 {
-            import_csv(&conn, "Eucarinogammarus.csv").expect("Ошибка импорта данных");
-        }
-        
-        // Загрузка записей
-        let records = load_records(&conn).unwrap_or_default();
-        
         // Создание экземпляра приложения
-        let conn = Arc::new(Mutex::new(conn));
+        let StartupDatabase { db, database_path, config, startup, settings_error } = opened;
+        let db = Arc::new(Mutex::new(db));
         
-        Self {
-            conn,
-            records,
+        let mut app = Self {
+            db,
+            database_path,
+            startup,
+            config,
+            file_form: FileForm::default(),
+            backup_form: BackupForm::default(),
+            merge_form: MergeForm::default(),
+            sync_form: SyncForm::default(),
+            password_form: PasswordForm::default(),
+            encryption_form: EncryptionForm::default(),
+            records: Vec::new(),
+            versions: HashMap::new(),
+            data_version: 0,
+            last_poll: Instant::now(),
             selected_tab: Tab::View,
             search_term: String::new(),
             sort_column: "id".to_string(),
@@ -119,39 +376,339 @@ impl EucarinogammarusApp {
             edit_id: String::new(),
             edit_column: String::new(),
             edit_value: String::new(),
+            edit_version: None,
+            conflict: None,
+            form_errors: Vec::new(),
+            quality_report: None,
             delete_id: String::new(),
+            delete_confirm: None,
+            trash: Vec::new(),
+            purge_confirm: None,
+            bulk_form: BulkForm::default(),
+            duplicate_form: DuplicateForm::default(),
+            vocabulary_form: VocabularyForm::default(),
+            vocabularies: HashMap::new(),
+            export_form: ExportForm::default(),
+            import_form: ImportForm::default(),
+            compare_ids: Vec::new(),
+            detail_id: None,
+            similarity: None,
+            history: None,
+            undo_stack: UndoStack::default(),
             status_message: String::new(),
+            error_log: Vec::new(),
+            show_error_log: false,
+        };
+        
+        // Загрузка записей и снимок при запуске
+        app.refresh_records();
+        if let Some(e) = settings_error {
+            app.report_error(e);
+        }
+        if app.startup.is_none() {
+            if let Err(e) = app.auto_snapshot(SnapshotReason::Startup) {
+                app.report_error(e);
+            }
+        }
+        app
+    }
+    
+    // Ошибка попадает в журнал и в строку состояния
+    pub fn report_error(&mut self, error: DbError) {
+        self.status_message = format!("Ошибка: {}", error);
+        self.error_log.push(ErrorEntry {
+            time: Instant::now(),
+            title: error.title(),
+            message: error.to_string(),
+        });
+    }
+    
+    // Переход к другой базе: всё, что относится к прежней (отмена, выбор,
+    // кэши, незавершённые формы), сбрасывается
+    fn switch_database(&mut self, db: Database, path: &Path) -> Result<(), DbError> {
+        match self.db.lock() {
+            Ok(mut current) => *current = db,
+            Err(_) => return Err(unavailable()),
+        }
+        
+        self.database_path = path.to_path_buf();
+        self.startup = None;
+        self.backup_form = BackupForm::default();
+        self.merge_form = MergeForm::default();
+        self.sync_form = SyncForm::default();
+        self.password_form = PasswordForm::default();
+        self.encryption_form = EncryptionForm::default();
+        self.reset_session();
+        
+        self.config.remember(path);
+        self.config.save()?;
+        Ok(())
+    }
+    
+    // Сброс того, что относится к прежнему содержимому базы
+    fn reset_session(&mut self) {
+        self.undo_stack = UndoStack::default();
+        self.form_errors.clear();
+        self.edit_version = None;
+        self.conflict = None;
+        self.delete_confirm = None;
+        self.purge_confirm = None;
+        self.bulk_form = BulkForm::default();
+        self.duplicate_form = DuplicateForm::default();
+        self.vocabulary_form = VocabularyForm::default();
+        self.import_form = ImportForm::default();
+        self.compare_ids.clear();
+        self.detail_id = None;
+        self.refresh_records();
+    }
+    
+    pub fn open_database(&mut self, path: &Path) -> Result<(), DbError> {
+        if !path.is_file() {
+            return Err(DbError::Invalid(format!("Файл {} не найден", path.display())));
         }
+        // Для зашифрованной базы сначала спрашивается пароль
+        let db = match Database::open(path) {
+            Err(DbError::Encrypted(_)) if crypto::ENABLED => {
+                self.password_form = PasswordForm { path: Some(path.to_path_buf()), password: String::new() };
+                return Ok(());
+            }
+            result => result?,
+        };
+        self.switch_database(db, path)?;
+        self.status_message = format!("Открыта база {}", path.display());
+        Ok(())
+    }
+    
+    pub fn open_encrypted(&mut self, path: &Path, password: &str) -> Result<(), DbError> {
+        self.switch_database(Database::open_with_password(path, password)?, path)?;
+        self.status_message = format!("Открыта зашифрованная база {}", path.display());
+        Ok(())
     }
     
+    // Включение, смена (Some) или снятие (None) шифрования текущей базы
+    pub fn set_password(&mut self, password: Option<&str>) -> Result<(), DbError> {
+        let encrypted = match self.db.lock() {
+            Ok(mut db) => {
+                let was_encrypted = db.is_encrypted();
+                db.set_password(password)?;
+                was_encrypted
+            }
+            Err(_) => return Err(unavailable()),
+        };
+        self.encryption_form = EncryptionForm { open: true, ..Default::default() };
+        self.status_message = match (encrypted, password) {
+            (false, Some(_)) => "База зашифрована; снимки, сделанные раньше, остались незашифрованными".to_string(),
+            (true, Some(_)) => "Пароль изменён; снимки, сделанные раньше, открываются прежним паролем".to_string(),
+            (_, None) => "Шифрование снято".to_string(),
+        };
+        Ok(())
+    }
+    
+    pub fn new_database(&mut self, path: &Path) -> Result<(), DbError> {
+        if path.exists() {
+            return Err(DbError::Invalid(format!("Файл {} уже существует", path.display())));
+        }
+        self.switch_database(Database::open(path)?, path)?;
+        self.status_message = format!("Создана пустая база {}", path.display());
+        Ok(())
+    }
+    
+    // Новая база path, заполненная из csv_path; существующая база открывается как есть
+    pub fn seed_database(&mut self, path: &Path, csv_path: &Path) -> Result<(), DbError> {
+        self.switch_database(Database::open_seeded(path, csv_path)?, path)?;
+        self.status_message = format!("Открыта база {}", path.display());
+        Ok(())
+    }
+    
+    pub fn export_text(&mut self, dir: &Path) -> Result<(), DbError> {
+        let summary = match self.db.lock() {
+            Ok(db) => db.export_text(dir)?,
+            Err(_) => return Err(unavailable()),
+        };
+        self.status_message = format!(
+            "База выгружена в {}: записей {}, находок {}",
+            dir.display(),
+            summary.records,
+            summary.specimens
+        );
+        Ok(())
+    }
+    
+    // Новая база из текстового каталога; дальше работа идёт с ней
+    pub fn database_from_text(&mut self, path: &Path, dir: &Path) -> Result<(), DbError> {
+        let (db, summary) = Database::create_from_text(path, dir)?;
+        self.switch_database(db, path)?;
+        self.status_message = format!("База {} собрана из {}: записей {}", path.display(), dir.display(), summary.records);
+        Ok(())
+    }
+    
+    // Копия текущей базы в новый файл; дальше работа идёт с копией
+    pub fn save_database_as(&mut self, path: &Path) -> Result<(), DbError> {
+        // Копия зашифрованной базы зашифрована тем же паролем
+        let copy = match self.db.lock() {
+            Ok(db) => {
+                db.save_as(path)?;
+                db.open_copy(path)?
+            }
+            Err(_) => return Err(unavailable()),
+        };
+        self.switch_database(copy, path)?;
+        self.status_message = format!("База сохранена как {}", path.display());
+        Ok(())
+    }
+    
+    // Автоматический снимок, если он включён в настройках для этой причины
+    pub fn auto_snapshot(&mut self, reason: SnapshotReason) -> Result<(), DbError> {
+        let settings = &self.config.backup;
+        let enabled = match reason {
+            SnapshotReason::Startup => settings.on_startup,
+            SnapshotReason::Import => settings.before_import,
+            SnapshotReason::BulkEdit => settings.before_bulk_edit,
+            SnapshotReason::Manual | SnapshotReason::Restore => true,
+        };
+        if !enabled {
+            return Ok(());
+        }
+        
+        match self.db.lock() {
+            Ok(db) => db.snapshot(settings, reason)?,
+            Err(_) => return Err(unavailable()),
+        };
+        Ok(())
+    }
+    
+    pub fn create_snapshot(&mut self) -> Result<(), DbError> {
+        let snapshot = match self.db.lock() {
+            Ok(db) => db.snapshot(&self.config.backup, SnapshotReason::Manual)?,
+            Err(_) => return Err(unavailable()),
+        };
+        self.status_message = format!("Снимок сохранён: {}", snapshot.path.display());
+        self.load_snapshots()
+    }
+    
+    pub fn load_snapshots(&mut self) -> Result<(), DbError> {
+        let snapshots = match self.db.lock() {
+            Ok(db) => db.snapshots(&self.config.backup)?,
+            Err(_) => return Err(unavailable()),
+        };
+        self.backup_form.snapshots = snapshots;
+        self.backup_form.selected = None;
+        self.backup_form.preview = None;
+        self.backup_form.confirm_restore = false;
+        Ok(())
+    }
+    
+    // Выбор снимка и подсчёт записей в нём
+    pub fn preview_snapshot(&mut self, index: usize) -> Result<(), DbError> {
+        let path = match self.backup_form.snapshots.get(index) {
+            Some(snapshot) => snapshot.path.clone(),
+            None => return Ok(()),
+        };
+        self.backup_form.selected = Some(index);
+        self.backup_form.preview = None;
+        self.backup_form.confirm_restore = false;
+        let info = match self.db.lock() {
+            Ok(db) => db.inspect_snapshot(&path)?,
+            Err(_) => return Err(unavailable()),
+        };
+        self.backup_form.preview = Some(info);
+        Ok(())
+    }
+    
+    // Замена базы выбранным снимком; отмена и формы прежней базы сбрасываются
+    pub fn restore_snapshot(&mut self) -> Result<(), DbError> {
+        let snapshot = match self.backup_form.selected.and_then(|i| self.backup_form.snapshots.get(i)) {
+            Some(snapshot) => snapshot.clone(),
+            None => return Ok(()),
+        };
+        
+        let (before, info) = match self.db.lock() {
+            Ok(mut db) => db.restore_snapshot(&self.config.backup, &snapshot.path)?,
+            Err(_) => return Err(unavailable()),
+        };
+        
+        self.reset_session();
+        self.load_snapshots()?;
+        self.status_message = format!(
+            "База восстановлена из снимка от {} (записей {}); прежнее состояние сохранено в {}",
+            snapshot.created,
+            info.records,
+            before.path.display()
+        );
+        Ok(())
+    }
+    
+    // Выполнение действия из окна выбора файла
+    pub fn run_file_action(&mut self) -> Result<(), DbError> {
+        let action = match self.file_form.action {
+            Some(action) => action,
+            None => return Ok(()),
+        };
+        let path = PathBuf::from(self.file_form.path.trim());
+        if path.as_os_str().is_empty() {
+            return Err(DbError::Invalid("Не указан путь к файлу".to_string()));
+        }
+        
+        match action {
+            FileAction::Open => self.open_database(&path)?,
+            FileAction::New => self.new_database(&path)?,
+            FileAction::SaveAs => self.save_database_as(&path)?,
+            FileAction::ExportText => self.export_text(&path)?,
+            FileAction::FromText => {
+                let directory = PathBuf::from(self.file_form.directory.trim());
+                self.database_from_text(&path, &directory)?
+            }
+        }
+        self.file_form = FileForm::default();
+        Ok(())
+    }
+    
+    // Перечитывание записей и корзины; при ошибке остаются прежние данные
     pub fn refresh_records(&mut self) {
-        if let Ok(conn) = self.conn.lock() {
-            if let Ok(records) = load_records(&conn) {
+        // Счётчик читается до записей: изменение между ними даст лишнюю
+        // перезагрузку, но не пропущенную
+        let loaded = match self.db.lock() {
+            Ok(db) => db.data_version().and_then(|version| Ok((version, db.records()?, db.versions()?, db.trash()?))),
+            Err(_) => Err(unavailable()),
+        };
+        
+        match loaded {
+            Ok((data_version, records, versions, trash)) => {
+                self.data_version = data_version;
                 self.records = records;
+                self.versions = versions;
+                self.trash = trash;
+                self.similarity = None;
+                self.history = None;
+                self.quality_report = None;
+                self.vocabularies.clear();
+                // Предпросмотр массовой правки построен по прежним значениям
+                self.bulk_form.preview.clear();
             }
+            Err(e) => self.report_error(e),
         }
     }
     
-    pub fn add_record(&mut self) -> Result<(), Box<dyn Error>> {
+    pub fn add_record(&mut self) -> Result<(), DbError> {
         // Сначала получаем данные из полей
-        let code = self.new_record.code.clone();
-        let genus = self.new_record.genus.clone();
-        let species = self.new_record.species.clone();
-        let size_mm = self.new_record.size_mm.clone();
-        let body = self.new_record.body.clone();
+        let values: Vec<&str> = self.new_record.values.iter().map(String::as_str).collect();
         
         // Затем выполняем операцию с базой данных
-        if let Ok(conn) = self.conn.lock() {
-            conn.execute(
-                "INSERT INTO Eucarinogammarus (
-                    Код, Род, Вид, Размеры_мм, Тело
-                ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5
-                )",
-                params![code, genus, species, size_mm, body],
-            )?;
+        let db = self.db.lock().map_err(|_| unavailable())?;
+        let named: Vec<(&str, &str)> = COLUMNS.iter().copied().zip(values.iter().copied()).collect();
+        self.form_errors = db.validate(None, &named)?;
+        if !self.form_errors.is_empty() {
+            self.status_message = "Запись не добавлена: исправьте отмеченные поля".to_string();
+            return Ok(());
         }
         
+        let id = db.insert_values(&values)?;
+        drop(db);
+        self.undo_stack.push(Command::Insert {
+            id,
+            name: format!("{} {} (ID {})", values[1], values[2], id),
+        });
+        
         // Очистка полей после добавления
         self.new_record = NewRecord::default();
         
@@ -162,22 +719,14 @@ impl EucarinogammarusApp {
         Ok(())
     }
     
-    pub fn edit_record(&mut self) -> Result<(), Box<dyn Error>> {
+    pub fn edit_record(&mut self) -> Result<(), DbError> {
         // Проверка валидности столбца
-        let valid_columns = vec![
-            "Код", "Род", "Вид", "Размеры_мм", "Тело", "Окраска", "Распространение",
-            "Глубина_м", "Вооруж_тела", "Средний_ряд_I_VII", "Средн_ряд_VIII_X",
-            "Сред_ряд_урозом", "Боковой_ряд", "Краевой_ряд", "Особен_воор",
-            "Эпимир_пласт", "Верх_антенны", "Прид_жгутик", "Нижн_антенны",
-            "Базип_III_V", "Уроподы_III", "Головн_сегм", "Глаза", "Тельсон"
-        ];
-        
-        if !valid_columns.contains(&self.edit_column.as_str()) {
+        if !COLUMNS.contains(&self.edit_column.as_str()) {
             self.status_message = "Неверное имя столбца".to_string();
             return Ok(());
         }
         
-        let id = self.edit_id.parse::<i32>().unwrap_or(0);
+        let id = self.edit_id.trim().parse::<i32>().unwrap_or(0);
         if id <= 0 {
             self.status_message = "Неверный ID".to_string();
             return Ok(());
@@ -186,83 +735,758 @@ impl EucarinogammarusApp {
         // Сначала получаем данные из полей
         let column = self.edit_column.clone();
         let value = self.edit_value.clone();
+        let version = match self.edit_version {
+            Some((edited, version)) if edited == id => Some(version),
+            _ => self.versions.get(&id).copied(),
+        };
         
-        // Затем выполняем операцию с базой данных
-        if let Ok(conn) = self.conn.lock() {
-            let query = format!(
-                "UPDATE Eucarinogammarus SET {} = ?1 WHERE id = ?2",
-                column
-            );
-            
-            conn.execute(&query, params![value, id])?;
+        let db = self.db.lock().map_err(|_| unavailable())?;
+        self.form_errors = db.validate(Some(id), &[(column.as_str(), value.as_str())])?;
+        drop(db);
+        if !self.form_errors.is_empty() {
+            self.status_message = "Запись не обновлена: исправьте значение".to_string();
+            return Ok(());
         }
         
-        // Обновление статуса и записей
-        self.status_message = "Запись успешно обновлена".to_string();
+        self.save_edit(id, column, value, version)
+    }
+    
+    // Сохранение поля, если запись не менялась с версии version (None — без
+    // проверки). Если её успел изменить другой пользователь, правка не
+    // сохраняется, а открывается окно конфликта
+    fn save_edit(&mut self, id: i32, column: String, value: String, version: Option<i64>) -> Result<(), DbError> {
+        let saved = match self.db.lock() {
+            Ok(db) => {
+                // Прежнее значение берётся из базы, а не из списка на экране,
+                // чтобы отмена вернула именно его
+                let current = db.record(id)?.ok_or(DbError::NotFound(id))?;
+                let old = current.field(&column).unwrap_or("").to_string();
+                let result = match version {
+                    Some(version) => db.update_checked(id, &column, &value, version),
+                    None => db.update(id, &column, &value),
+                };
+                match result {
+                    Ok(record) => Ok((record, old)),
+                    Err(DbError::Conflict(_)) => {
+                        let current = db.record(id)?.ok_or(DbError::NotFound(id))?;
+                        Err(EditConflict {
+                            id,
+                            name: current.name(),
+                            theirs: current.field(&column).unwrap_or("").to_string(),
+                            column: column.clone(),
+                            mine: value.clone(),
+                            version: db.versions()?.get(&id).copied().unwrap_or_default(),
+                            changed_by: db.history(id)?.into_iter().next(),
+                            operation: None,
+                        })
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(_) => return Err(unavailable()),
+        };
+        
+        self.refresh_records();
+        match saved {
+            Ok((record, old)) => {
+                self.status_message = format!("Запись {} обновлена", record.name());
+                self.undo_stack.push(Command::Update { id, column, old, new: value });
+                self.edit_version = self.versions.get(&id).map(|version| (id, *version));
+                self.conflict = None;
+            }
+            Err(conflict) => {
+                self.status_message = format!("Запись {} изменена другим пользователем; правка не сохранена", conflict.name);
+                self.conflict = Some(conflict);
+            }
+        }
+        Ok(())
+    }
+    
+    // Выбор в окне конфликта: сохранить своё значение поверх изменения
+    // другого пользователя или оставить то, что уже в базе
+    pub fn resolve_conflict(&mut self, keep_mine: bool) -> Result<(), DbError> {
+        let conflict = match self.conflict.take() {
+            Some(conflict) => conflict,
+            None => return Ok(()),
+        };
+        // Прерванная операция уже откатилась; её можно повторить по новым данным
+        if conflict.operation.is_some() {
+            return Ok(());
+        }
+        if keep_mine {
+            return self.save_edit(conflict.id, conflict.column, conflict.mine, Some(conflict.version));
+        }
+        
+        self.edit_value = conflict.theirs;
+        self.edit_version = self.versions.get(&conflict.id).map(|version| (conflict.id, *version));
+        self.status_message = format!("Правка записи {} отменена: оставлено значение из базы", conflict.name);
+        Ok(())
+    }
+    
+    // Операция прервана изменением записи id другим пользователем:
+    // транзакция откатилась, данные перечитываются, а окно конфликта
+    // показывает поле, которое операция собиралась изменить
+    fn open_conflict(&mut self, operation: String, id: i32, writes: Vec<FieldWrite>) {
+        let conflict = match self.db.lock() {
+            Ok(db) => operation_conflict(&db, id, writes),
+            Err(_) => Err(unavailable()),
+        };
+        
+        self.refresh_records();
+        match conflict {
+            Ok(conflict) => {
+                self.status_message = format!(
+                    "Запись {} изменена другим пользователем; операция «{}» не выполнена",
+                    conflict.name, operation
+                );
+                self.conflict = Some(EditConflict { operation: Some(operation), ..conflict });
+            }
+            Err(e) => self.report_error(e),
+        }
+    }
+    
+    // Проверка раз в POLL_INTERVAL, не изменил ли базу другой пользователь
+    // или программа; если изменил, записи перечитываются
+    pub fn poll_changes(&mut self) {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = Instant::now();
+        
+        let version = match self.db.lock() {
+            Ok(db) => db.data_version(),
+            Err(_) => return,
+        };
+        match version {
+            Ok(version) if version != self.data_version => {
+                self.refresh_records();
+                self.status_message = "База изменена другим пользователем; данные обновлены".to_string();
+            }
+            // Занятая база проверяется в следующий раз
+            Ok(_) | Err(DbError::Locked(_)) => {}
+            Err(e) => self.report_error(e),
+        }
+    }
+    
+    // Предпросмотр массовой правки по текущему отбору записей
+    pub fn preview_bulk_edit(&mut self) -> Result<(), DbError> {
+        let preview = bulk::preview(
+            &self.filtered_records(),
+            &self.bulk_form.column,
+            self.bulk_form.mode,
+            &self.bulk_form.find,
+            &self.bulk_form.value,
+        )?;
+        
+        self.status_message = format!("Будет изменено записей: {}", preview.len());
+        self.bulk_form.preview = preview;
+        self.bulk_form.preview_column = self.bulk_form.column.clone();
+        Ok(())
+    }
+    
+    // Применяется ровно то, что было показано в предпросмотре
+    pub fn apply_bulk_edit(&mut self) -> Result<(), DbError> {
+        self.auto_snapshot(SnapshotReason::BulkEdit)?;
+        let changes = std::mem::take(&mut self.bulk_form.preview);
+        
+        let column = self.bulk_form.preview_column.clone();
+        let result = match self.db.lock() {
+            Ok(db) => bulk::apply(db.conn(), &column, &changes),
+            Err(_) => return Err(unavailable()),
+        };
+        let command = match result {
+            Err(DbError::Conflict(id)) => {
+                let writes = changes.iter().map(|c| FieldWrite::new(c.id, &column, &c.old, &c.new)).collect();
+                self.open_conflict(format!("массовая правка {}", column), id, writes);
+                return Ok(());
+            }
+            result => result?,
+        };
+        
+        self.status_message = format!("Изменено записей: {}", changes.len());
+        self.undo_stack.push(command);
+        self.refresh_records();
+        
+        Ok(())
+    }
+    
+    pub fn find_duplicates(&mut self) {
+        self.duplicate_form.groups = duplicates::find_duplicates(&self.records, self.duplicate_form.threshold);
+        self.duplicate_form.selected = None;
+        self.status_message = format!("Найдено групп дубликатов: {}", self.duplicate_form.groups.len());
+    }
+    
+    // Выбор группы: по умолчанию остаётся первая запись, а для каждого поля
+    // берётся первое непустое значение, начиная с неё
+    pub fn select_duplicate_group(&mut self, index: usize) {
+        let ids = match self.duplicate_form.groups.get(index) {
+            Some(group) => group.ids.clone(),
+            None => return,
+        };
+        let records: Vec<&Eucarinogammarus> = ids.iter()
+            .filter_map(|id| self.records.iter().find(|r| r.id == *id))
+            .collect();
+        let keep_id = ids[0];
+        
+        self.duplicate_form.choices = COLUMNS.iter()
+            .map(|c| {
+                records.iter()
+                    .find(|r| !r.field(c).unwrap_or("").trim().is_empty())
+                    .map(|r| r.id)
+                    .unwrap_or(keep_id)
+            })
+            .collect();
+        self.duplicate_form.keep_id = keep_id;
+        self.duplicate_form.selected = Some(index);
+    }
+    
+    pub fn merge_duplicates(&mut self) -> Result<(), DbError> {
+        let group = match self.duplicate_form.selected.and_then(|i| self.duplicate_form.groups.get(i)) {
+            Some(group) => group.clone(),
+            None => return Ok(()),
+        };
+        let records: Vec<&Eucarinogammarus> = group.ids.iter()
+            .filter_map(|id| self.records.iter().find(|r| r.id == *id))
+            .collect();
+        
+        let values: Vec<(String, String)> = COLUMNS.iter()
+            .zip(&self.duplicate_form.choices)
+            .map(|(column, source)| {
+                let value = records.iter()
+                    .find(|r| r.id == *source)
+                    .and_then(|r| r.field(column))
+                    .unwrap_or("");
+                (column.to_string(), value.to_string())
+            })
+            .collect();
+        
+        let keep_id = self.duplicate_form.keep_id;
+        let result = match self.db.lock() {
+            Ok(db) => duplicates::merge(db.conn(), keep_id, &values, &records, db.user_name()),
+            Err(_) => return Err(unavailable()),
+        };
+        let command = match result {
+            Err(DbError::Conflict(id)) => {
+                let keeper = records.iter().find(|r| r.id == keep_id);
+                let writes = values.iter()
+                    .map(|(column, value)| {
+                        let old = keeper.and_then(|r| r.field(column)).unwrap_or("");
+                        FieldWrite::new(keep_id, column, old, value)
+                    })
+                    .collect();
+                self.open_conflict(format!("объединение дубликатов в ID {}", keep_id), id, writes);
+                return Ok(());
+            }
+            result => result?,
+        };
+        
+        self.undo_stack.push(command);
+        self.status_message = format!("Записи объединены в {}", self.record_name(keep_id));
         self.refresh_records();
+        self.find_duplicates();
         
         Ok(())
     }
     
-    pub fn delete_record(&mut self) -> Result<(), Box<dyn Error>> {
-        let id = self.delete_id.parse::<i32>().unwrap_or(0);
+    pub fn undo(&mut self) {
+        let command = self.undo_stack.next_undo().cloned();
+        let result = match self.db.lock() {
+            Ok(db) => self.undo_stack.undo(db.conn(), db.user_name()),
+            Err(_) => Err(unavailable()),
+        };
+        
+        match (result, command) {
+            (Ok(Some(description)), _) => self.status_message = format!("Отменено: {}", description),
+            (Ok(None), _) => self.status_message = "Нечего отменять".to_string(),
+            (Err(DbError::Conflict(id)), Some(command)) => {
+                self.open_conflict(format!("отмена: {}", command.description()), id, command.field_writes(true));
+                self.status_message.push_str("; операция убрана из списка отмены");
+            }
+            (Err(e), _) => {
+                self.report_error(e);
+                self.status_message.push_str("; операция убрана из списка отмены");
+            }
+        }
+        self.refresh_records();
+    }
+    
+    pub fn redo(&mut self) {
+        let command = self.undo_stack.next_redo().cloned();
+        let result = match self.db.lock() {
+            Ok(db) => self.undo_stack.redo(db.conn(), db.user_name()),
+            Err(_) => Err(unavailable()),
+        };
+        
+        match (result, command) {
+            (Ok(Some(description)), _) => self.status_message = format!("Повторено: {}", description),
+            (Ok(None), _) => self.status_message = "Нечего повторять".to_string(),
+            (Err(DbError::Conflict(id)), Some(command)) => {
+                self.open_conflict(format!("повтор: {}", command.description()), id, command.field_writes(false));
+                self.status_message.push_str("; операция убрана из списка повтора");
+            }
+            (Err(e), _) => {
+                self.report_error(e);
+                self.status_message.push_str("; операция убрана из списка повтора");
+            }
+        }
+        self.refresh_records();
+    }
+    
+    // Проверка ID перед удалением: запись должна существовать,
+    // после чего показывается окно подтверждения
+    pub fn request_delete(&mut self) {
+        let id = self.delete_id.trim().parse::<i32>().unwrap_or(0);
         if id <= 0 {
             self.status_message = "Неверный ID".to_string();
+            return;
+        }
+        
+        if self.records.iter().any(|r| r.id == id) {
+            self.delete_confirm = Some(id);
+        } else {
+            self.status_message = format!("Запись с ID {} не найдена", id);
+        }
+    }
+    
+    // Перемещение подтверждённой записи в корзину
+    pub fn delete_record(&mut self) -> Result<(), DbError> {
+        let id = match self.delete_confirm.take() {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        
+        let name = self.db.lock().map_err(|_| unavailable())?.delete(id)?.name();
+        self.status_message = format!("Запись {} перемещена в корзину", name);
+        self.undo_stack.push(Command::Delete { id, name });
+        
+        // Обновление записей
+        self.delete_id.clear();
+        self.refresh_records();
+        
+        Ok(())
+    }
+    
+    pub fn restore_record(&mut self, id: i32) -> Result<(), DbError> {
+        let name = self.db.lock().map_err(|_| unavailable())?.restore(id)?.name();
+        self.status_message = format!("Запись {} восстановлена", name);
+        self.undo_stack.push(Command::Restore { id, name });
+        
+        self.refresh_records();
+        
+        Ok(())
+    }
+    
+    pub fn purge_record(&mut self) -> Result<(), DbError> {
+        let id = match self.purge_confirm.take() {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        
+        let record = self.db.lock().map_err(|_| unavailable())?.purge(id)?;
+        self.undo_stack.forget(id);
+        self.status_message = format!("Запись {} удалена навсегда", record.name());
+        
+        self.refresh_records();
+        
+        Ok(())
+    }
+    
+    // Род и вид записи для сообщений; ищется и среди удалённых
+    pub fn record_name(&self, id: i32) -> String {
+        self.records.iter()
+            .chain(self.trash.iter().map(|t| &t.record))
+            .find(|r| r.id == id)
+            .map(Eucarinogammarus::name)
+            .unwrap_or_else(|| format!("ID {}", id))
+    }
+    
+    pub fn export_dwca(&mut self) -> Result<(), DbError> {
+        self.export_form.issues.clear();
+        
+        // Сопоставление из файла или стандартное
+        let mapping = if self.export_form.mapping_path.trim().is_empty() {
+            DwcMapping::default()
+        } else {
+            DwcMapping::load(self.export_form.mapping_path.trim())?
+        };
+        
+        let result = match self.db.lock() {
+            Ok(db) => db.export_dwca(self.export_form.file_path.trim(), &mapping, &self.export_form.options),
+            Err(_) => return Err(unavailable()),
+        };
+        
+        match result {
+            Ok(summary) => {
+                self.status_message = format!(
+                    "Архив создан: таксонов {}, описаний {}, распространение {}",
+                    summary.taxa, summary.descriptions, summary.distributions
+                );
+                Ok(())
+            }
+            Err(e) => {
+                if let DbError::ExportRejected(validation) = &e {
+                    self.export_form.issues = validation.issues.iter().map(|i| i.to_string()).collect();
+                }
+                Err(e)
+            }
+        }
+    }
+    
+    // Матрица расстояний пересчитывается только после изменения записей
+    pub fn similarity(&mut self) -> &DistanceMatrix {
+        self.similarity.get_or_insert_with(|| similarity::distance_matrix(&self.records))
+    }
+    
+    // История изменений записи; загружается заново после изменения данных
+    pub fn history(&mut self, record_id: i32) -> Vec<AuditEntry> {
+        if let Some((id, entries)) = &self.history {
+            if *id == record_id {
+                return entries.clone();
+            }
+        }
+        
+        let entries = match self.db.lock() {
+            Ok(db) => db.history(record_id),
+            Err(_) => Err(unavailable()),
+        };
+        let entries = entries.unwrap_or_else(|e| {
+            self.report_error(e);
+            Vec::new()
+        });
+        self.history = Some((record_id, entries.clone()));
+        entries
+    }
+    
+    pub fn revert_change(&mut self, audit_id: i64) -> Result<(), DbError> {
+        let result = match self.db.lock() {
+            Ok(db) => audit::revert_change(db.conn(), audit_id),
+            Err(_) => return Err(unavailable()),
+        };
+        let entry = match result {
+            Err(DbError::Conflict(id)) => {
+                let writes = self.history(id).into_iter()
+                    .filter(|e| e.id == audit_id)
+                    .map(|e| FieldWrite::new(
+                        id,
+                        e.column_name.as_deref().unwrap_or(""),
+                        e.new_value.as_deref().unwrap_or(""),
+                        e.old_value.as_deref().unwrap_or(""),
+                    ))
+                    .collect();
+                self.open_conflict("откат изменения".to_string(), id, writes);
+                return Ok(());
+            }
+            result => result?,
+        };
+        
+        // Откат прошёл, только если в поле было значение из журнала
+        if let Some(column) = &entry.column_name {
+            self.undo_stack.push(Command::Update {
+                id: entry.record_id,
+                column: column.clone(),
+                old: entry.new_value.clone().unwrap_or_default(),
+                new: entry.old_value.clone().unwrap_or_default(),
+            });
+        }
+        
+        self.status_message = format!(
+            "Изменение откатено: {} записи {}",
+            entry.column_name.unwrap_or_default(),
+            entry.record_id
+        );
+        self.refresh_records();
+        
+        Ok(())
+    }
+    
+    pub fn export_similarity(&mut self) -> Result<(), DbError> {
+        similarity::export(
+            &self.records,
+            self.export_form.matrix_path.trim(),
+            self.export_form.newick_path.trim(),
+        )?;
+        self.status_message = "Матрица расстояний и дендрограмма сохранены".to_string();
+        Ok(())
+    }
+    
+    pub fn read_import(&mut self) -> Result<(), DbError> {
+        let table = import::read_dwc(self.import_form.file_path.trim())?;
+        let rows = table.rows.len();
+        let pending = import::match_taxa(table, &self.records);
+        
+        self.status_message = format!(
+            "Прочитано строк: {}, названий: {}, строк без рода: {}. Проверьте сопоставление",
+            rows,
+            pending.groups.len(),
+            pending.unnamed
+        );
+        self.import_form.pending = Some(pending);
+        
+        Ok(())
+    }
+    
+    pub fn apply_import(&mut self) -> Result<(), DbError> {
+        if self.import_form.pending.is_none() {
             return Ok(());
         }
+        self.auto_snapshot(SnapshotReason::Import)?;
+        let pending = match &self.import_form.pending {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
         
-        // Сначала получаем данные из полей
-        let id_value = id;
+        let summary = match self.db.lock() {
+            Ok(mut db) => db.apply_import(pending)?,
+            Err(_) => return Err(unavailable()),
+        };
         
-        // Затем выполняем операцию с базой данных
-        if let Ok(conn) = self.conn.lock() {
-            conn.execute("DELETE FROM Eucarinogammarus WHERE id = ?1", params![id_value])?;
+        // Отмена импорта убирает созданные виды в корзину; находки остаются
+        if !summary.taxon_ids.is_empty() {
+            let commands = summary.taxon_ids.iter()
+                .map(|id| Command::Insert { id: *id, name: format!("ID {}", id) })
+                .collect();
+            self.undo_stack.push(Command::Bulk {
+                description: format!("импорт {} новых видов", summary.taxon_ids.len()),
+                commands,
+            });
         }
         
-        // Обновление статуса и записей
-        self.status_message = "Запись успешно удалена".to_string();
+        self.import_form.pending = None;
+        self.status_message = format!(
+            "Импорт завершён: новых видов {}, находок {}, местонахождений {}, повторов пропущено {}, строк пропущено {}, без рода {}",
+            summary.taxa_created,
+            summary.specimens_created,
+            summary.localities_created,
+            summary.duplicates_skipped,
+            summary.rows_skipped,
+            summary.rows_unnamed
+        );
         self.refresh_records();
         
         Ok(())
     }
     
+    pub fn compare_database(&mut self) -> Result<(), DbError> {
+        let path = PathBuf::from(self.merge_form.path.trim());
+        let pending = match self.db.lock() {
+            Ok(db) => db.compare_with(&path)?,
+            Err(_) => return Err(unavailable()),
+        };
+        
+        self.status_message = format!(
+            "Отличающихся записей: {}, без изменений: {}",
+            pending.changes.len(),
+            pending.unchanged
+        );
+        self.merge_form.pending = Some(pending);
+        Ok(())
+    }
+    
+    // Перенос выбранных отличий; отменяется одним действием
+    pub fn apply_merge(&mut self) -> Result<(), DbError> {
+        if self.merge_form.pending.is_none() {
+            return Ok(());
+        }
+        self.auto_snapshot(SnapshotReason::Import)?;
+        let pending = match &self.merge_form.pending {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        
+        let result = match self.db.lock() {
+            Ok(db) => db.apply_merge(pending),
+            Err(_) => return Err(unavailable()),
+        };
+        let (summary, command) = match result {
+            Err(DbError::Conflict(id)) => {
+                let writes = pending.changes.iter()
+                    .filter_map(|c| Some((c.target.as_ref()?.id, c)))
+                    .flat_map(|(target, c)| {
+                        c.fields.iter()
+                            .filter(|f| f.take_source)
+                            .map(move |f| FieldWrite::new(target, f.column, &f.target, &f.source))
+                    })
+                    .collect();
+                self.merge_form.pending = None;
+                self.open_conflict("слияние".to_string(), id, writes);
+                return Ok(());
+            }
+            result => result?,
+        };
+        
+        self.undo_stack.push(command);
+        self.merge_form.pending = None;
+        self.status_message = format!(
+            "Слияние завершено: добавлено {}, изменено {}, в корзину {}",
+            summary.added, summary.updated, summary.removed
+        );
+        self.refresh_records();
+        Ok(())
+    }
+    
+    pub fn load_sync_log(&mut self) -> Result<(), DbError> {
+        self.sync_form.log = match self.db.lock() {
+            Ok(db) => db.sync_log()?,
+            Err(_) => return Err(unavailable()),
+        };
+        Ok(())
+    }
+    
+    pub fn export_changes(&mut self) -> Result<(), DbError> {
+        let path = PathBuf::from(self.sync_form.export_path.trim());
+        let changes = match self.db.lock() {
+            Ok(db) => {
+                let since = if self.sync_form.incremental { db.last_export()? } else { None };
+                db.export_changes(&path, since.as_deref())?
+            }
+            Err(_) => return Err(unavailable()),
+        };
+        
+        self.status_message = format!(
+            "Набор изменений сохранён в {}: записей {}, удалений {}",
+            path.display(),
+            changes.records.len(),
+            changes.purged.len()
+        );
+        self.load_sync_log()
+    }
+    
+    // Применение набора изменений другой копии. Записи могут быть заменены
+    // или удалены окончательно, поэтому история отмены очищается
+    pub fn import_changes(&mut self) -> Result<(), DbError> {
+        let path = PathBuf::from(self.sync_form.import_path.trim());
+        self.auto_snapshot(SnapshotReason::Import)?;
+        let summary = match self.db.lock() {
+            Ok(db) => db.import_changes(&path)?,
+            Err(_) => return Err(unavailable()),
+        };
+        
+        self.undo_stack = UndoStack::default();
+        let repeated = if summary.repeated { " (набор уже применялся)" } else { "" };
+        self.status_message = format!(
+            "Синхронизация: добавлено {}, обновлено {}, удалено {}, пропущено {}{}",
+            summary.inserted, summary.updated, summary.deleted, summary.skipped, repeated
+        );
+        self.refresh_records();
+        self.load_sync_log()
+    }
+    
+    // Загрузка словаря столбца в кэш, если его там ещё нет
+    pub fn load_vocabulary(&mut self, column: &str) {
+        if self.vocabularies.contains_key(column) {
+            return;
+        }
+        let terms = match self.db.lock() {
+            Ok(db) => vocabulary::load_terms(db.conn(), column),
+            Err(_) => Err(unavailable()),
+        };
+        let terms = terms.unwrap_or_else(|e| {
+            self.report_error(e);
+            Vec::new()
+        });
+        self.vocabularies.insert(column.to_string(), terms);
+    }
+    
+    pub fn add_vocabulary_term(&mut self) -> Result<(), DbError> {
+        let column = self.vocabulary_form.column.clone();
+        let db = self.db.lock().map_err(|_| unavailable())?;
+        vocabulary::add_term(db.conn(), &column, &self.vocabulary_form.new_term)?;
+        self.status_message = format!("Термин добавлен в словарь {}", column);
+        self.vocabulary_form.new_term.clear();
+        self.vocabularies.remove(&column);
+        Ok(())
+    }
+    
+    pub fn remove_vocabulary_term(&mut self, term: &str) -> Result<(), DbError> {
+        let column = self.vocabulary_form.column.clone();
+        let db = self.db.lock().map_err(|_| unavailable())?;
+        vocabulary::remove_term(db.conn(), &column, term)?;
+        self.status_message = format!("Термин исключён из словаря {}", column);
+        self.vocabularies.remove(&column);
+        Ok(())
+    }
+    
+    // Замена выбранных вариантов итоговым термином; отменяется как массовая правка
+    pub fn merge_vocabulary_variants(&mut self) -> Result<(), DbError> {
+        let form = &self.vocabulary_form;
+        if form.selected.is_empty() {
+            self.status_message = "Не выбраны варианты для объединения".to_string();
+            return Ok(());
+        }
+        
+        let variants: Vec<String> = form.selected.iter().cloned().collect();
+        let result = match self.db.lock() {
+            Ok(db) => vocabulary::merge_variants(db.conn(), &self.records, &form.column, &variants, &form.target),
+            Err(_) => return Err(unavailable()),
+        };
+        let command = match result {
+            Err(DbError::Conflict(id)) => {
+                let (column, target) = (form.column.clone(), form.target.trim().to_string());
+                let writes = self.records.iter()
+                    .filter_map(|r| Some(FieldWrite::new(r.id, &column, r.field(&column)?, &target)))
+                    .collect();
+                self.open_conflict(format!("объединение вариантов {}", column), id, writes);
+                return Ok(());
+            }
+            result => result?,
+        };
+        
+        self.status_message = format!("Варианты объединены: {}", command.description());
+        self.undo_stack.push(command);
+        self.vocabulary_form.selected.clear();
+        self.refresh_records();
+        
+        Ok(())
+    }
+    
+    // Проверка всех записей по правилам из db::VALIDATION_RULES
+    pub fn check_quality(&mut self) -> Result<(), DbError> {
+        let report = self.db.lock().map_err(|_| unavailable())?.validate_all()?;
+        self.status_message = format!("Найдено нарушений: {}", report.len());
+        self.quality_report = Some(report);
+        Ok(())
+    }
+    
     pub fn filtered_records(&self) -> Vec<&Eucarinogammarus> {
         self.records.iter()
-            .filter(|r| {
-                let search = self.search_term.to_lowercase();
-                self.search_term.is_empty() ||
-                r.code.to_lowercase().contains(&search) ||
-                r.genus.to_lowercase().contains(&search) ||
-                r.species.to_lowercase().contains(&search) ||
-                r.size_mm.to_lowercase().contains(&search) ||
-                r.body.to_lowercase().contains(&search) ||
-                r.coloration.to_lowercase().contains(&search) ||
-                r.distribution.to_lowercase().contains(&search) ||
-                r.depth_m.to_lowercase().contains(&search) ||
-                r.body_armament.to_lowercase().contains(&search) ||
-                r.median_row_i_vii.to_lowercase().contains(&search) ||
-                r.median_row_viii_x.to_lowercase().contains(&search) ||
-                r.median_row_urozom.to_lowercase().contains(&search) ||
-                r.lateral_row.to_lowercase().contains(&search) ||
-                r.marginal_row.to_lowercase().contains(&search) ||
-                r.special_armament.to_lowercase().contains(&search) ||
-                r.epimeral_plate.to_lowercase().contains(&search) ||
-                r.upper_antennae.to_lowercase().contains(&search) ||
-                r.accessory_flagellum.to_lowercase().contains(&search) ||
-                r.lower_antennae.to_lowercase().contains(&search) ||
-                r.basipodite_iii_v.to_lowercase().contains(&search) ||
-                r.uropods_iii.to_lowercase().contains(&search) ||
-                r.head_segment.to_lowercase().contains(&search) ||
-                r.eyes.to_lowercase().contains(&search) ||
-                r.telson.to_lowercase().contains(&search)
-            })
+            .filter(|r| r.matches(&self.search_term))
             .collect()
     }
 }
 
 impl eframe::App for EucarinogammarusApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.startup.is_some() {
+            egui::CentralPanel::default().show(ctx, |ui| startup_screen::render(ui, self));
+            password_window::show(ctx, self);
+            return;
+        }
+        
+        self.poll_changes();
+        ctx.request_repaint_after(POLL_INTERVAL);
+        
+        // Ctrl+Z / Ctrl+Y; пока фокус в поле ввода, сочетания обрабатывает само поле
+        if ctx.memory(|m| m.focus().is_none()) {
+            let (undo, redo) = ctx.input(|i| {
+                let command = i.modifiers.command;
+                (
+                    command && !i.modifiers.shift && i.key_pressed(egui::Key::Z),
+                    command && (i.key_pressed(egui::Key::Y) || (i.modifiers.shift && i.key_pressed(egui::Key::Z))),
+                )
+            });
+            if undo {
+                self.undo();
+            } else if redo {
+                self.redo();
+            }
+        }
+        
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                file_dialog::menu(ui, self);
+                ui.weak(self.database_path.display().to_string());
+            });
+            
             ui.horizontal(|ui| {
                 ui.heading("База данных Eucarinogammarus");
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -270,6 +1494,22 @@ impl eframe::App for EucarinogammarusApp {
                         self.refresh_records();
                         self.status_message = "Данные обновлены".to_string();
                     }
+                    
+                    let redo_hint = self.undo_stack.redo_description().unwrap_or_default();
+                    if ui.add_enabled(self.undo_stack.can_redo(), egui::Button::new("Повторить"))
+                        .on_hover_text(format!("Ctrl+Y: {}", redo_hint))
+                        .clicked()
+                    {
+                        self.redo();
+                    }
+                    
+                    let undo_hint = self.undo_stack.undo_description().unwrap_or_default();
+                    if ui.add_enabled(self.undo_stack.can_undo(), egui::Button::new("Отменить"))
+                        .on_hover_text(format!("Ctrl+Z: {}", undo_hint))
+                        .clicked()
+                    {
+                        self.undo();
+                    }
                 });
             });
             
@@ -286,6 +1526,30 @@ impl eframe::App for EucarinogammarusApp {
                 if ui.selectable_label(self.selected_tab == Tab::Delete, "Удалить").clicked() {
                     self.selected_tab = Tab::Delete;
                 }
+                if ui.selectable_label(self.selected_tab == Tab::Bulk, "Массовая правка").clicked() {
+                    self.selected_tab = Tab::Bulk;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Duplicates, "Дубликаты").clicked() {
+                    self.selected_tab = Tab::Duplicates;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Vocabulary, "Словари").clicked() {
+                    self.selected_tab = Tab::Vocabulary;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Quality, "Качество данных").clicked() {
+                    self.selected_tab = Tab::Quality;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Trash, format!("Корзина ({})", self.trash.len())).clicked() {
+                    self.selected_tab = Tab::Trash;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Compare, "Сравнение").clicked() {
+                    self.selected_tab = Tab::Compare;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Export, "Экспорт").clicked() {
+                    self.selected_tab = Tab::Export;
+                }
+                if ui.selectable_label(self.selected_tab == Tab::Import, "Импорт DwC").clicked() {
+                    self.selected_tab = Tab::Import;
+                }
             });
         });
         
@@ -295,13 +1559,68 @@ impl eframe::App for EucarinogammarusApp {
                 Tab::Add => add_tab::render(ui, self),
                 Tab::Edit => edit_tab::render(ui, self),
                 Tab::Delete => delete_tab::render(ui, self),
+                Tab::Bulk => bulk_tab::render(ui, self),
+                Tab::Duplicates => duplicates_tab::render(ui, self),
+                Tab::Vocabulary => vocabulary_tab::render(ui, self),
+                Tab::Quality => quality_tab::render(ui, self),
+                Tab::Trash => trash_tab::render(ui, self),
+                Tab::Compare => compare_tab::render(ui, self),
+                Tab::Export => export_tab::render(ui, self),
+                Tab::Import => import_tab::render(ui, self),
             }
         });
         
+        detail_window::show(ctx, self);
+        file_dialog::show(ctx, self);
+        backup_window::show(ctx, self);
+        merge_window::show(ctx, self);
+        sync_window::show(ctx, self);
+        password_window::show(ctx, self);
+        encryption_window::show(ctx, self);
+        conflict_window::show(ctx, self);
+        error_log::show(ctx, self);
+        
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.label(&self.status_message);
+                if !self.error_log.is_empty() {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let label = format!("Ошибки ({})", self.error_log.len());
+                        if ui.selectable_label(self.show_error_log, label).clicked() {
+                            self.show_error_log = !self.show_error_log;
+                        }
+                    });
+                }
             });
         });
     }
+}
+
+// Как часто проверяются изменения базы другими пользователями
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Конфликт для операции над записью id: первое из её изменений полей,
+// значение которого в базе уже не то, что операция ожидала
+fn operation_conflict(db: &Database, id: i32, writes: Vec<FieldWrite>) -> Result<EditConflict, DbError> {
+    let current = db.record(id)?.ok_or(DbError::NotFound(id))?;
+    let write = writes.into_iter()
+        .filter(|w| w.id == id)
+        .find(|w| current.field(&w.column) != Some(w.expected.as_str()))
+        .ok_or(DbError::Conflict(id))?;
+    
+    Ok(EditConflict {
+        id,
+        name: current.name(),
+        theirs: current.field(&write.column).unwrap_or("").to_string(),
+        column: write.column,
+        mine: write.value,
+        version: db.versions()?.get(&id).copied().unwrap_or_default(),
+        changed_by: db.history(id)?.into_iter().next(),
+        operation: None,
+    })
+}
+
+// Мьютекс базы отравлен паникой в другом потоке
+fn unavailable() -> DbError {
+    DbError::Invalid("База данных недоступна".to_string())
 }
\ No newline at end of file
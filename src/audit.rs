@@ -0,0 +1,147 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::{update_field_expecting, COLUMNS};
+use crate::error::DbError;
+
+// Запись журнала изменений таблицы Eucarinogammarus
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub user_name: String,
+    pub record_id: i32,
+    pub action: String,
+    pub column_name: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+// Имя пользователя операционной системы для журнала
+pub fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+// Установка триггеров журнала для текущего соединения.
+// Триггеры временные (TEMP), поэтому имя пользователя встраивается
+// прямо в их текст и не хранится в самой базе данных
+pub fn install_triggers(conn: &Connection, user_name: &str) -> Result<(), DbError> {
+    let user = quote(user_name);
+
+    let mut updates = String::new();
+    let mut deletes = String::new();
+    for column in COLUMNS {
+        updates.push_str(&format!(
+            "INSERT INTO AuditLog (user_name, record_id, action, column_name, old_value, new_value)
+                SELECT {user}, NEW.id, 'UPDATE', '{column}', OLD.{column}, NEW.{column}
+                WHERE OLD.{column} IS NOT NEW.{column};\n"
+        ));
+        deletes.push_str(&format!(
+            "INSERT INTO AuditLog (user_name, record_id, action, column_name, old_value, new_value)
+                SELECT {user}, OLD.id, 'DELETE', '{column}', OLD.{column}, NULL
+                WHERE COALESCE(OLD.{column}, '') <> '';\n"
+        ));
+    }
+
+    conn.execute_batch(&format!(
+        "DROP TRIGGER IF EXISTS temp.audit_insert;
+        DROP TRIGGER IF EXISTS temp.audit_update;
+        DROP TRIGGER IF EXISTS temp.audit_delete;
+        DROP TRIGGER IF EXISTS temp.audit_trash;
+        CREATE TEMP TRIGGER audit_insert AFTER INSERT ON main.Eucarinogammarus BEGIN
+            INSERT INTO AuditLog (user_name, record_id, action) VALUES ({user}, NEW.id, 'INSERT');
+        END;
+        CREATE TEMP TRIGGER audit_update AFTER UPDATE ON main.Eucarinogammarus BEGIN
+            {updates}
+        END;
+        CREATE TEMP TRIGGER audit_delete AFTER DELETE ON main.Eucarinogammarus BEGIN
+            {deletes}
+        END;
+        CREATE TEMP TRIGGER audit_trash AFTER UPDATE OF deleted_at ON main.Eucarinogammarus
+        WHEN OLD.deleted_at IS NOT NEW.deleted_at BEGIN
+            INSERT INTO AuditLog (user_name, record_id, action)
+                VALUES ({user}, NEW.id, CASE WHEN NEW.deleted_at IS NULL THEN 'RESTORE' ELSE 'TRASH' END);
+        END;"
+    ))?;
+
+    Ok(())
+}
+
+// Запись в журнал события, которое не видно триггерам (например, объединение записей)
+pub fn log_event(
+    conn: &Connection,
+    user_name: &str,
+    record_id: i32,
+    action: &str,
+    old_value: &str,
+    new_value: &str,
+) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO AuditLog (user_name, record_id, action, old_value, new_value) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![user_name, record_id, action, old_value, new_value],
+    )?;
+    Ok(())
+}
+
+// История изменений записи, новые сверху
+pub fn load_history(conn: &Connection, record_id: i32) -> Result<Vec<AuditEntry>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, user_name, record_id, action, column_name, old_value, new_value
+         FROM AuditLog WHERE record_id = ?1 ORDER BY id DESC",
+    )?;
+
+    let entries = stmt.query_map(params![record_id], row_to_entry)?;
+
+    let mut result = Vec::new();
+    for entry in entries {
+        result.push(entry?);
+    }
+
+    Ok(result)
+}
+
+// Откат одного изменения поля неудалённой записи: старое значение
+// записывается обратно, сам откат попадает в журнал как обычное изменение.
+// Если поле с тех пор изменили ещё раз, откат не выполняется: DbError::Conflict
+pub fn revert_change(conn: &Connection, audit_id: i64) -> Result<AuditEntry, DbError> {
+    let entry = conn.query_row(
+        "SELECT id, timestamp, user_name, record_id, action, column_name, old_value, new_value
+         FROM AuditLog WHERE id = ?1",
+        params![audit_id],
+        row_to_entry,
+    ).optional()?.ok_or_else(|| DbError::Invalid("Запись журнала не найдена".to_string()))?;
+
+    let column = match (&entry.action[..], &entry.column_name) {
+        ("UPDATE", Some(column)) if COLUMNS.contains(&column.as_str()) => column.clone(),
+        _ => return Err(DbError::Invalid("Откатить можно только изменение поля".to_string())),
+    };
+
+    // Как и любая правка: запись в корзине или удалённая навсегда — DbError::NotFound
+    update_field_expecting(
+        conn,
+        entry.record_id,
+        &column,
+        entry.new_value.as_deref().unwrap_or(""),
+        entry.old_value.as_deref().unwrap_or(""),
+    )?;
+    Ok(entry)
+}
+
+fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        user_name: row.get(2)?,
+        record_id: row.get(3)?,
+        action: row.get(4)?,
+        column_name: row.get(5)?,
+        old_value: row.get(6)?,
+        new_value: row.get(7)?,
+    })
+}
+
+// Строковый литерал SQL
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -0,0 +1,214 @@
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::crypto;
+use crate::db::SCHEMA_VERSION;
+use crate::error::DbError;
+
+// Снимки базы через online backup API SQLite: копия согласована, даже если
+// база в это время открыта в другой программе. Снимки базы foo.db лежат
+// в каталоге снимков под именами foo-ГГГГММДД-ЧЧММСС-причина.db.
+// Снимки зашифрованной базы шифруются тем же паролем password
+
+// Причина создания снимка; автоматические снимки удаляются по очереди
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotReason {
+    Manual,
+    Startup,
+    Import,
+    BulkEdit,
+    // Состояние базы перед восстановлением другого снимка
+    Restore,
+}
+
+impl SnapshotReason {
+    const ALL: [SnapshotReason; 5] = [
+        SnapshotReason::Manual,
+        SnapshotReason::Startup,
+        SnapshotReason::Import,
+        SnapshotReason::BulkEdit,
+        SnapshotReason::Restore,
+    ];
+
+    // Метка в имени файла
+    fn tag(self) -> &'static str {
+        match self {
+            SnapshotReason::Manual => "manual",
+            SnapshotReason::Startup => "startup",
+            SnapshotReason::Import => "import",
+            SnapshotReason::BulkEdit => "bulk",
+            SnapshotReason::Restore => "restore",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SnapshotReason::Manual => "вручную",
+            SnapshotReason::Startup => "при запуске",
+            SnapshotReason::Import => "перед импортом",
+            SnapshotReason::BulkEdit => "перед массовой правкой",
+            SnapshotReason::Restore => "перед восстановлением",
+        }
+    }
+
+    // Снимки, сделанные вручную, при ротации не удаляются
+    pub fn is_automatic(self) -> bool {
+        self != SnapshotReason::Manual
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    // Время создания в виде ГГГГ-ММ-ДД ЧЧ:ММ:СС
+    pub created: String,
+    pub reason: SnapshotReason,
+    pub size: u64,
+}
+
+// Содержимое снимка для предпросмотра перед восстановлением
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotInfo {
+    pub records: usize,
+    pub trashed: usize,
+    pub schema_version: usize,
+}
+
+// Создание снимка базы conn, файл которой называется db_path, в каталоге dir
+pub fn create(
+    conn: &Connection,
+    db_path: &Path,
+    dir: &Path,
+    reason: SnapshotReason,
+    password: Option<&str>,
+) -> Result<Snapshot, DbError> {
+    fs::create_dir_all(dir)?;
+    let stamp: String = conn.query_row("SELECT strftime('%Y%m%d-%H%M%S', 'now', 'localtime')", [], |row| row.get(0))?;
+
+    // Два снимка за одну секунду получают номер
+    let base = format!("{}-{}-{}", stem(db_path), stamp, reason.tag());
+    let mut path = dir.join(format!("{}.db", base));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.db", base, n));
+        n += 1;
+    }
+
+    let mut dest = crypto::open(&path, OpenFlags::default(), password)?;
+    copy(conn, &mut dest)?;
+    // Копия получает из заголовка режим WAL исходной базы; снимок не меняется,
+    // и при чтении рядом с ним не должны появляться файлы -wal и -shm
+    dest.pragma_update(None, "journal_mode", "DELETE")?;
+    drop(dest);
+    let size = fs::metadata(&path)?.len();
+    Ok(Snapshot { path, created: created(&stamp), reason, size })
+}
+
+// Снимки базы db_path из каталога dir, новые сверху
+pub fn list(db_path: &Path, dir: &Path) -> Result<Vec<Snapshot>, DbError> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}-", stem(db_path));
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let parsed = name.strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".db"))
+            .and_then(parse_name);
+        if let Some((stamp, reason, n)) = parsed {
+            let snapshot = Snapshot {
+                path: entry.path(),
+                created: created(&stamp),
+                reason,
+                size: entry.metadata()?.len(),
+            };
+            found.push((stamp, n, snapshot));
+        }
+    }
+
+    found.sort_by(|a, b| (&b.0, b.1).cmp(&(&a.0, a.1)));
+    Ok(found.into_iter().map(|(_, _, snapshot)| snapshot).collect())
+}
+
+// Удаление старых автоматических снимков: для каждой причины остаются
+// последние keep. Возвращает число удалённых файлов
+pub fn rotate(db_path: &Path, dir: &Path, keep: usize) -> Result<usize, DbError> {
+    let snapshots = list(db_path, dir)?;
+    let mut removed = 0;
+    for reason in SnapshotReason::ALL.into_iter().filter(|r| r.is_automatic()) {
+        for snapshot in snapshots.iter().filter(|s| s.reason == reason).skip(keep) {
+            fs::remove_file(&snapshot.path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+// Число записей в снимке; сам снимок открывается только для чтения
+pub fn inspect(path: &Path, password: Option<&str>) -> Result<SnapshotInfo, DbError> {
+    let conn = crypto::open(path, OpenFlags::SQLITE_OPEN_READ_ONLY, password)?;
+    let schema_version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).map_err(|e| DbError::from(e).at(path))?;
+    let (records, trashed): (i64, i64) = conn.query_row(
+        "SELECT COUNT(*) - COUNT(deleted_at), COUNT(deleted_at) FROM Eucarinogammarus",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).map_err(|e| DbError::from(e).at(path))?;
+    Ok(SnapshotInfo { records: records as usize, trashed: trashed as usize, schema_version })
+}
+
+// Замена содержимого базы conn снимком path. Снимок из более новой
+// версии программы не восстанавливается: его схему эта версия не знает
+pub fn restore(conn: &mut Connection, path: &Path, password: Option<&str>) -> Result<SnapshotInfo, DbError> {
+    let info = inspect(path, password)?;
+    if info.schema_version > SCHEMA_VERSION {
+        return Err(DbError::Invalid(format!(
+            "Снимок {} создан более новой версией программы (схема {}, поддерживается {})",
+            path.display(),
+            info.schema_version,
+            SCHEMA_VERSION
+        )));
+    }
+    let source = crypto::open(path, OpenFlags::SQLITE_OPEN_READ_ONLY, password)?;
+    copy(&source, conn)?;
+    Ok(info)
+}
+
+// Полная копия базы from в базу to через backup API
+fn copy(from: &Connection, to: &mut Connection) -> Result<(), DbError> {
+    Backup::new(from, to)?.run_to_completion(100, Duration::ZERO, None)?;
+    Ok(())
+}
+
+fn stem(db_path: &Path) -> String {
+    db_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+// "20261019-143005-startup-1" -> ("20261019-143005", Startup, 1)
+fn parse_name(rest: &str) -> Option<(String, SnapshotReason, u32)> {
+    let mut parts = rest.splitn(4, '-');
+    let (date, time, tag) = (parts.next()?, parts.next()?, parts.next()?);
+    if date.len() != 8 || time.len() != 6 || !date.chars().chain(time.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let reason = SnapshotReason::ALL.into_iter().find(|r| r.tag() == tag)?;
+    let n = match parts.next() {
+        Some(n) => n.parse().ok()?,
+        None => 0,
+    };
+    Some((format!("{}-{}", date, time), reason, n))
+}
+
+// "20261019-143005" -> "2026-10-19 14:30:05"
+fn created(stamp: &str) -> String {
+    if stamp.len() != 15 || !stamp.is_ascii() {
+        return stamp.to_string();
+    }
+    let part = |from: usize, to: usize| &stamp[from..to];
+    format!("{}-{}-{} {}:{}:{}", part(0, 4), part(4, 6), part(6, 8), part(9, 11), part(11, 13), part(13, 15))
+}
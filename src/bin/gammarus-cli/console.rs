@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::Path;
+
+use eucarinogammarus_db::{Database, DbError, SortDirection, COLUMNS, COLUMN_LABELS};
+use eucarinogammarus_db::config::Config;
+
+use crate::output::{self, Format};
+
+pub fn run_console_app(config: Option<Config>, path: &Path) -> Result<(), Box<dyn Error>> {
+    // Open the database (created if missing) with the schema, audit triggers and initial CSV import
+    let db = crate::open_database(path)?;
+    println!("База: {}", path.display());
+
+    // Like the graphical interface, the menu remembers the database it worked with last
+    if let Some(mut config) = config {
+        config.remember(path);
+        if let Err(e) = config.save() {
+            println!("Настройки не сохранены: {}", e);
+        }
+    }
+
+    // Command-line interface
+    loop {
+        println!("\nВыберите опцию:");
+        println!("1. Просмотр данных");
+        println!("2. Добавить запись");
+        println!("3. Редактировать запись");
+        println!("4. Удалить запись");
+        println!("5. Выход");
+
+        let choice = read_input("Введите ваш выбор: ");
+
+        match choice.trim() {
+            "1" => view_data(&db)?,
+            "2" => add_record(&db)?,
+            "3" => edit_record(&db)?,
+            "4" => delete_record(&db)?,
+            "5" => {
+                println!("Выход...");
+                break;
+            }
+            _ => println!("Неверный выбор. Пожалуйста, попробуйте снова."),
+        }
+    }
+
+    Ok(())
+}
+
+// Prompt and read one line from stdin; an empty string on EOF
+fn read_input(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().ok();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).ok();
+    input.trim_end_matches(['\r', '\n']).to_string()
+}
+
+fn view_data(db: &Database) -> Result<(), Box<dyn Error>> {
+    println!("Введите имя столбца для сортировки (например, `Код`, `Род` и т.д.):");
+    let column = read_input("Столбец: ");
+
+    // The data layer rejects unknown column names, which also prevents SQL injection
+    let records = match db.records_sorted(column.trim(), SortDirection::Ascending) {
+        Ok(records) => records,
+        Err(_) => {
+            println!("Неверное имя столбца. Пожалуйста, попробуйте снова.");
+            return Ok(());
+        }
+    };
+
+    println!();
+    let columns = output::select_columns(&[], Format::Table)?;
+    output::print_records(&records, &columns, Format::Table);
+
+    Ok(())
+}
+
+fn add_record(db: &Database) -> Result<(), Box<dyn Error>> {
+    let values: Vec<String> = COLUMN_LABELS.iter()
+        .map(|label| read_input(&format!("Введите {}: ", label)).trim().to_string())
+        .collect();
+
+    let named: Vec<(&str, &str)> = COLUMNS.iter().copied().zip(values.iter().map(String::as_str)).collect();
+    let errors = db.validate(None, &named)?;
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}: {}", error.column, error.message);
+        }
+        println!("Запись не добавлена.");
+        return Ok(());
+    }
+
+    let values: Vec<&str> = values.iter().map(String::as_str).collect();
+    db.insert_values(&values)?;
+
+    println!("Запись успешно добавлена.");
+    Ok(())
+}
+
+fn edit_record(db: &Database) -> Result<(), Box<dyn Error>> {
+    let id = read_input("Введите ID записи для редактирования: ");
+    println!("Доступные столбцы для редактирования:");
+    println!("{}", COLUMNS.join(", "));
+    let column = read_input("Введите столбец для редактирования: ");
+
+    let column = column.trim();
+    if !COLUMNS.contains(&column) {
+        println!("Неверное имя столбца. Пожалуйста, попробуйте снова.");
+        return Ok(());
+    }
+
+    let id: i32 = match id.trim().parse() {
+        Ok(id) => id,
+        Err(_) => {
+            println!("Неверный ID.");
+            return Ok(());
+        }
+    };
+
+    // Remember the version before prompting, so a change made by someone
+    // else while the value is typed is reported instead of overwritten
+    let version = db.versions()?.get(&id).copied().ok_or(DbError::NotFound(id))?;
+
+    let new_value = read_input("Введите новое значение: ");
+    let new_value = new_value.trim();
+
+    let errors = db.validate(Some(id), &[(column, new_value)])?;
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}: {}", error.column, error.message);
+        }
+        println!("Запись не обновлена.");
+        return Ok(());
+    }
+
+    let record = db.update_checked(id, column, new_value, version)?;
+
+    println!("Запись {} обновлена.", record.name());
+    Ok(())
+}
+
+fn delete_record(db: &Database) -> Result<(), Box<dyn Error>> {
+    let id = read_input("Введите ID записи для удаления: ");
+
+    let id: i32 = match id.trim().parse() {
+        Ok(id) => id,
+        Err(_) => {
+            println!("Неверный ID.");
+            return Ok(());
+        }
+    };
+
+    // Deleted records go to the trash bin, as in the graphical interface
+    let record = db.delete(id)?;
+
+    println!("Запись {} перемещена в корзину.", record.name());
+    Ok(())
+}
\ No newline at end of file
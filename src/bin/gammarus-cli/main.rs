@@ -0,0 +1,578 @@
+mod console;
+mod output;
+mod password;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use eucarinogammarus_db::{Database, DbError, Eucarinogammarus, SortDirection, COLUMNS, COLUMN_LABELS};
+use eucarinogammarus_db::backup::SnapshotReason;
+use eucarinogammarus_db::config::{self, BackupSettings, Config};
+use eucarinogammarus_db::crypto;
+use eucarinogammarus_db::dwca::export::{DwcMapping, ExportOptions};
+use eucarinogammarus_db::dwca::import::{self, Decision};
+use eucarinogammarus_db::merge::{ChangeKind, PendingMerge};
+use output::Format;
+
+#[derive(Parser)]
+#[command(
+    name = "gammarus-cli",
+    version,
+    about = "База данных Eucarinogammarus в командной строке; без команды открывается текстовое меню"
+)]
+struct Cli {
+    #[arg(
+        long,
+        global = true,
+        value_name = "ФАЙЛ",
+        help = "Файл базы; по умолчанию последняя база, открытая в графическом интерфейсе или меню"
+    )]
+    db: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Список записей")]
+    List(ListArgs),
+    #[command(about = "Все поля одной записи")]
+    Show {
+        id: i32,
+        #[arg(long, value_enum, default_value_t, help = "Формат вывода")]
+        format: Format,
+    },
+    #[command(about = "Поиск подстроки во всех полях без учёта регистра")]
+    Search {
+        term: String,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    #[command(about = "Добавление записи")]
+    Add {
+        #[arg(
+            long = "set",
+            value_name = "СТОЛБЕЦ=ЗНАЧЕНИЕ",
+            required = true,
+            value_parser = parse_assignment,
+            help = "Значение поля; можно указать несколько раз"
+        )]
+        values: Vec<(String, String)>,
+    },
+    #[command(about = "Изменение одного поля записи")]
+    Edit {
+        id: i32,
+        column: String,
+        value: String,
+    },
+    #[command(about = "Перемещение записи в корзину")]
+    Delete {
+        id: i32,
+    },
+    #[command(about = "Импорт из CSV или Darwin Core (архив .zip или таблица находок)")]
+    Import {
+        file: String,
+    },
+    #[command(about = "Экспорт в CSV или, для файла .zip, в архив Darwin Core")]
+    Export {
+        file: String,
+        #[arg(long, help = "Файл сопоставления терминов Darwin Core")]
+        mapping: Option<String>,
+    },
+    #[command(about = "Сводка по базе")]
+    Stats {
+        #[arg(long, value_enum, default_value_t, help = "Формат вывода")]
+        format: Format,
+    },
+    #[command(about = "Отличия другого файла базы от текущей: + только в другом, - только в текущей, ~ изменённые")]
+    Diff {
+        file: PathBuf,
+    },
+    #[command(about = "Перенос отличий из другого файла базы: новые записи и заполнение пустых полей")]
+    Merge {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t, help = "Чьё значение сохранять, если поле заполнено в обеих базах")]
+        prefer: Prefer,
+        #[arg(long, help = "Перемещать в корзину записи, которых нет в другом файле")]
+        delete_missing: bool,
+    },
+    #[command(about = "Снимок базы в каталог резервных копий")]
+    Backup,
+    #[command(about = "Список снимков базы, новые сверху")]
+    Snapshots,
+    #[command(about = "Замена базы снимком; текущее состояние сохраняется в отдельный снимок")]
+    Restore {
+        file: PathBuf,
+        #[arg(long, help = "Не спрашивать подтверждения")]
+        yes: bool,
+    },
+    #[command(about = "Выгрузка набора изменений для другой копии базы; по умолчанию все записи")]
+    ExportChanges {
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "ВРЕМЯ",
+            conflicts_with = "incremental",
+            help = "Только записи, изменённые после этого времени (UTC, ГГГГ-ММ-ДДTЧЧ:ММ:СС)"
+        )]
+        since: Option<String>,
+        #[arg(long, help = "Только изменения после последней выгрузки")]
+        incremental: bool,
+    },
+    #[command(about = "Применение набора изменений другой копии базы; повтор ничего не меняет")]
+    ImportChanges {
+        file: PathBuf,
+    },
+    #[command(about = "Журнал выгрузок и импорта наборов изменений")]
+    SyncLog,
+    #[command(about = "Выгрузка базы в каталог текстовых файлов (по файлу на вид) для хранения в git")]
+    ExportText {
+        dir: PathBuf,
+    },
+    #[command(about = "Сборка новой базы FILE из каталога, выгруженного командой export-text")]
+    ImportText {
+        dir: PathBuf,
+        file: PathBuf,
+    },
+    #[command(about = "Шифрование базы паролем (нужна сборка с функцией encryption)")]
+    Encrypt,
+    #[command(about = "Снятие шифрования с базы")]
+    Decrypt,
+    #[command(about = "Смена пароля зашифрованной базы")]
+    Rekey,
+}
+
+// Разрешение конфликтов при слиянии
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum Prefer {
+    // Значение текущей базы
+    #[default]
+    Target,
+    // Значение другого файла
+    Source,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[arg(long, default_value = "id", help = "Столбец сортировки")]
+    sort: String,
+    #[arg(long, help = "Сортировка по убыванию")]
+    desc: bool,
+    #[arg(long, value_enum, default_value_t, help = "Формат вывода")]
+    format: Format,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "СТОЛБЦЫ",
+        help = "Столбцы через запятую: id и имена столбцов базы; по умолчанию зависят от формата"
+    )]
+    columns: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    // Вывод в закрытый канал (например, `gammarus-cli list | head`) завершает
+    // программу молча, как у обычных утилит Unix, а не паникой println!
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+
+    let cli = Cli::parse();
+
+    // Испорченные настройки не мешают работе (путь можно задать через --db)
+    // и не перезаписываются, чтобы их можно было исправить
+    let config = match Config::load() {
+        Ok(config) => Some(config),
+        Err(e) => {
+            eprintln!("Настройки не прочитаны: {}", e);
+            None
+        }
+    };
+    let settings = config.clone().unwrap_or_default();
+    let path = settings.database_path(cli.db.as_deref());
+
+    let result = match cli.command {
+        // Новая база собирается в указанный файл, база по умолчанию не открывается
+        Some(Command::ImportText { dir, file }) => import_text(&dir, &file),
+        Some(command) => run(command, &path, &settings.backup),
+        None => console::run_console_app(config, &path),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Ошибка: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Новая база без CSV для начального заполнения создаётся пустой:
+// в скриптах некому предложить другой выбор
+pub fn open_database(path: &Path) -> Result<Database, Box<dyn Error>> {
+    match Database::open_seeded(path, config::seed_csv_path(path)) {
+        Err(DbError::SeedMissing(csv_path)) => {
+            let db = Database::open(path)?;
+            eprintln!("Файл {} не найден, создана пустая база {}", csv_path.display(), path.display());
+            Ok(db)
+        }
+        // Пароль спрашивается, только если программа умеет открывать зашифрованные базы
+        Err(DbError::Encrypted(_)) if crypto::ENABLED => {
+            let password = password::read(&format!("Пароль базы {}: ", path.display()))?;
+            Ok(Database::open_with_password(path, &password)?)
+        }
+        result => Ok(result?),
+    }
+}
+
+fn run(command: Command, path: &Path, backup: &BackupSettings) -> Result<(), Box<dyn Error>> {
+    let mut db = open_database(path)?;
+
+    match command {
+        Command::List(args) => {
+            let records = load_sorted(&db, &args)?;
+            let columns = output::select_columns(&args.columns, args.format)?;
+            output::print_records(&records, &columns, args.format);
+        }
+        Command::Show { id, format } => {
+            let record = db.record(id)?
+                .ok_or_else(|| format!("Запись с ID {} не найдена", id))?;
+            output::print_record(&record, format);
+        }
+        Command::Search { term, list } => {
+            let records: Vec<Eucarinogammarus> = load_sorted(&db, &list)?
+                .into_iter()
+                .filter(|r| r.matches(&term))
+                .collect();
+            let columns = output::select_columns(&list.columns, list.format)?;
+            output::print_records(&records, &columns, list.format);
+        }
+        Command::Add { values } => add(&db, &values)?,
+        Command::Edit { id, column, value } => {
+            if !COLUMNS.contains(&column.as_str()) {
+                return Err(format!("Неверное имя столбца: {}", column).into());
+            }
+            check(&db, Some(id), &[(column.as_str(), value.as_str())])?;
+            let record = db.update(id, &column, &value)?;
+            eprintln!("Запись {} обновлена", record.name());
+        }
+        Command::Delete { id } => {
+            let record = db.delete(id)?;
+            eprintln!("Запись {} перемещена в корзину", record.name());
+        }
+        Command::Import { file } => {
+            if backup.before_import {
+                db.snapshot(backup, SnapshotReason::Import)?;
+            }
+            import_file(&mut db, &file)?
+        }
+        Command::Export { file, mapping } => export_file(&db, &file, mapping.as_deref())?,
+        Command::Stats { format } => stats(&db, format)?,
+        Command::Diff { file } => print_diff(&db.compare_with(&file)?),
+        Command::Merge { file, prefer, delete_missing } => {
+            let mut pending = db.compare_with(&file)?;
+            pending.prefer_source(prefer == Prefer::Source);
+            for change in pending.changes.iter_mut().filter(|c| c.kind == ChangeKind::Removed) {
+                change.apply = delete_missing;
+            }
+            if backup.before_import {
+                db.snapshot(backup, SnapshotReason::Import)?;
+            }
+            let (summary, _) = db.apply_merge(&pending)?;
+            eprintln!(
+                "Добавлено записей: {}, изменено: {}, перемещено в корзину: {}",
+                summary.added, summary.updated, summary.removed
+            );
+        }
+        Command::Backup => {
+            let snapshot = db.snapshot(backup, SnapshotReason::Manual)?;
+            println!("{}", snapshot.path.display());
+        }
+        Command::Snapshots => {
+            for snapshot in db.snapshots(backup)? {
+                println!("{}\t{}\t{}", snapshot.created, snapshot.reason.label(), snapshot.path.display());
+            }
+        }
+        Command::Restore { file, yes } => {
+            let current = db.count()?;
+            let info = db.inspect_snapshot(&file)?;
+            eprintln!("Записей в снимке: {} (в корзине {}), в базе сейчас: {}", info.records, info.trashed, current);
+            if !yes && !confirm("Заменить базу этим снимком? [д/Н]: ")? {
+                return Err("восстановление отменено".into());
+            }
+            let (before, _) = db.restore_snapshot(backup, &file)?;
+            eprintln!("База восстановлена; прежнее состояние сохранено в {}", before.path.display());
+        }
+        Command::ExportChanges { file, since, incremental } => {
+            let since = if incremental { db.last_export()? } else { since };
+            let changes = db.export_changes(&file, since.as_deref())?;
+            eprintln!("Выгружено записей: {}, удалений: {}", changes.records.len(), changes.purged.len());
+        }
+        Command::ImportChanges { file } => {
+            if backup.before_import {
+                db.snapshot(backup, SnapshotReason::Import)?;
+            }
+            let summary = db.import_changes(&file)?;
+            if summary.repeated {
+                eprintln!("Этот набор изменений уже применялся");
+            }
+            eprintln!(
+                "Добавлено записей: {}, обновлено: {}, удалено: {}, пропущено: {}",
+                summary.inserted, summary.updated, summary.deleted, summary.skipped
+            );
+        }
+        Command::SyncLog => {
+            for entry in db.sync_log()? {
+                let direction = if entry.direction == "export" { "выгрузка" } else { "импорт" };
+                println!("{}\t{}\t{}\t{}", entry.timestamp, direction, entry.records, entry.file);
+            }
+        }
+        Command::ExportText { dir } => {
+            let summary = db.export_text(&dir)?;
+            eprintln!(
+                "Выгружено записей: {}, находок: {}, удалено файлов: {}",
+                summary.records, summary.specimens, summary.removed
+            );
+        }
+        Command::ImportText { dir, file } => import_text(&dir, &file)?,
+        Command::Encrypt => {
+            crypto::supported()?;
+            if db.is_encrypted() {
+                return Err("база уже зашифрована; пароль меняется командой rekey".into());
+            }
+            db.set_password(Some(&password::read_new()?))?;
+            eprintln!(
+                "База зашифрована. Снимки, сделанные раньше, остались незашифрованными: {}",
+                backup.directory(path).display()
+            );
+        }
+        Command::Decrypt => {
+            if !db.is_encrypted() {
+                return Err("база не зашифрована".into());
+            }
+            db.set_password(None)?;
+            eprintln!("Шифрование снято");
+        }
+        Command::Rekey => {
+            if !db.is_encrypted() {
+                return Err("база не зашифрована; зашифровать её можно командой encrypt".into());
+            }
+            db.set_password(Some(&password::read_new()?))?;
+            eprintln!("Пароль изменён. Снимки, сделанные раньше, открываются прежним паролем");
+        }
+    }
+
+    Ok(())
+}
+
+// Подтверждение с терминала; пустой ответ и конец ввода (в скриптах без --yes) — отказ
+fn confirm(prompt: &str) -> io::Result<bool> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "д" | "да" | "y" | "yes"))
+}
+
+fn import_text(dir: &Path, file: &Path) -> Result<(), Box<dyn Error>> {
+    let (_, summary) = Database::create_from_text(file, dir)?;
+    eprintln!("База {} собрана: записей {}, находок {}", file.display(), summary.records, summary.specimens);
+    Ok(())
+}
+
+fn load_sorted(db: &Database, args: &ListArgs) -> Result<Vec<Eucarinogammarus>, Box<dyn Error>> {
+    let direction = if args.desc { SortDirection::Descending } else { SortDirection::Ascending };
+    Ok(db.records_sorted(&args.sort, direction)?)
+}
+
+fn parse_assignment(arg: &str) -> Result<(String, String), String> {
+    let (column, value) = arg.split_once('=').ok_or("ожидается СТОЛБЕЦ=ЗНАЧЕНИЕ")?;
+    let column = column.trim();
+    if !COLUMNS.contains(&column) {
+        return Err(format!("неизвестный столбец {}", column));
+    }
+    Ok((column.to_string(), value.to_string()))
+}
+
+// Нарушения правил проверки выводятся по одному и прерывают команду
+fn check(db: &Database, id: Option<i32>, values: &[(&str, &str)]) -> Result<(), Box<dyn Error>> {
+    let errors = db.validate(id, values)?;
+    for error in &errors {
+        eprintln!("{}: {}", error.column, error.message);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err("значения не прошли проверку".into())
+    }
+}
+
+fn add(db: &Database, assignments: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+    let values: Vec<&str> = COLUMNS.iter()
+        .map(|c| {
+            assignments.iter()
+                .rev()
+                .find(|(column, _)| column == c)
+                .map(|(_, value)| value.as_str())
+                .unwrap_or("")
+        })
+        .collect();
+
+    let named: Vec<(&str, &str)> = COLUMNS.iter().copied().zip(values.iter().copied()).collect();
+    check(db, None, &named)?;
+
+    let id = db.insert_values(&values)?;
+    // ID — единственное, что пишется в stdout, чтобы его можно было подставить в следующую команду
+    println!("{}", id);
+    Ok(())
+}
+
+fn import_file(db: &mut Database, file: &str) -> Result<(), Box<dyn Error>> {
+    let is_csv = Path::new(file).extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        let count = db.import_csv(file)?;
+        eprintln!("Добавлено записей: {}", count);
+        return Ok(());
+    }
+
+    let table = import::read_dwc(file)?;
+    let records = db.records()?;
+    let pending = import::match_taxa(table, &records);
+    let ambiguous = pending.groups.iter().filter(|g| matches!(g.decision, Decision::Skip)).count();
+
+    let summary = db.apply_import(&pending)?;
+    eprintln!(
+        "Новых видов: {}, находок: {}, местонахождений: {}, повторов пропущено: {}, строк пропущено: {}, без рода: {}",
+        summary.taxa_created,
+        summary.specimens_created,
+        summary.localities_created,
+        summary.duplicates_skipped,
+        summary.rows_skipped,
+        summary.rows_unnamed
+    );
+    if ambiguous > 0 {
+        eprintln!("Неоднозначных названий пропущено: {} (сопоставьте их во вкладке «Импорт DwC»)", ambiguous);
+    }
+    Ok(())
+}
+
+fn export_file(db: &Database, file: &str, mapping: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let is_zip = Path::new(file).extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        let count = db.export_csv(file)?;
+        eprintln!("Выгружено записей: {}", count);
+        return Ok(());
+    }
+
+    let mapping = match mapping {
+        Some(path) => DwcMapping::load(path)?,
+        None => DwcMapping::default(),
+    };
+
+    match db.export_dwca(file, &mapping, &ExportOptions::default()) {
+        Ok(summary) => {
+            eprintln!(
+                "Архив создан: таксонов {}, описаний {}, распространение {}",
+                summary.taxa, summary.descriptions, summary.distributions
+            );
+            Ok(())
+        }
+        Err(e) => {
+            if let DbError::ExportRejected(validation) = &e {
+                for issue in &validation.issues {
+                    eprintln!("{}", issue);
+                }
+            }
+            Err(e.into())
+        }
+    }
+}
+
+fn print_diff(pending: &PendingMerge) {
+    for change in &pending.changes {
+        let mark = match change.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        };
+        println!("{} {}", mark, change.name());
+        for field in &change.fields {
+            let conflict = if field.is_conflict() { " (конфликт)" } else { "" };
+            println!("    {}: «{}» → «{}»{}", field.column, field.target, field.source, conflict);
+        }
+    }
+    println!(
+        "Только в другом файле: {}, только в текущей: {}, изменено: {}, без изменений: {}",
+        pending.count(ChangeKind::Added),
+        pending.count(ChangeKind::Removed),
+        pending.count(ChangeKind::Changed),
+        pending.unchanged
+    );
+}
+
+fn stats(db: &Database, format: Format) -> Result<(), Box<dyn Error>> {
+    let records = db.records()?;
+    let trashed = db.trash()?.len();
+
+    let mut genera: BTreeMap<String, usize> = BTreeMap::new();
+    for record in &records {
+        *genera.entry(record.genus.trim().to_string()).or_insert(0) += 1;
+    }
+
+    let filled: Vec<(&str, &str, f64)> = COLUMNS.iter()
+        .zip(COLUMN_LABELS)
+        .map(|(column, label)| {
+            let count = records.iter().filter(|r| !r.field(column).unwrap_or("").trim().is_empty()).count();
+            let percent = if records.is_empty() { 0.0 } else { count as f64 * 100.0 / records.len() as f64 };
+            (*column, label, percent)
+        })
+        .collect();
+
+    // В JSON сводка — один объект, чтобы её можно было разобрать целиком
+    if matches!(format, Format::Json | Format::Jsonl) {
+        let summary = serde_json::json!({
+            "records": records.len(),
+            "trashed": trashed,
+            "genera": genera,
+            "filled_percent": filled.iter()
+                .map(|(column, _, percent)| (column.to_string(), serde_json::json!((percent * 10.0).round() / 10.0)))
+                .collect::<serde_json::Map<_, _>>(),
+        });
+        if format == Format::Json {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            println!("{}", summary);
+        }
+        return Ok(());
+    }
+
+    output::print_table(
+        &["Показатель", "Значение"],
+        &[
+            vec!["Записей".to_string(), records.len().to_string()],
+            vec!["В корзине".to_string(), trashed.to_string()],
+        ],
+        format,
+    );
+
+    println!();
+    let rows: Vec<Vec<String>> = genera.iter()
+        .map(|(genus, count)| {
+            let genus = if genus.is_empty() { "(не указан)" } else { genus };
+            vec![genus.to_string(), count.to_string()]
+        })
+        .collect();
+    output::print_table(&["Род", "Записей"], &rows, format);
+
+    println!();
+    let rows: Vec<Vec<String>> = filled.iter()
+        .map(|(_, label, percent)| vec![label.to_string(), format!("{:.1}%", percent)])
+        .collect();
+    output::print_table(&["Поле", "Заполнено"], &rows, format);
+
+    Ok(())
+}
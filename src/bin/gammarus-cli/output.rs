@@ -0,0 +1,295 @@
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use unicode_width::UnicodeWidthStr;
+
+use eucarinogammarus_db::db::{Eucarinogammarus, COLUMNS, COLUMN_LABELS};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    // Выровненная таблица для терминала
+    #[default]
+    Table,
+    // Значения через табуляцию; для скриптов
+    Tsv,
+    // Массив объектов JSON
+    Json,
+    // Объект JSON на строку
+    Jsonl,
+    // Таблица Markdown для отчётов и заметок
+    Markdown,
+}
+
+// Столбцы по умолчанию для таблиц, которые читает человек; машинные форматы получают все
+const SUMMARY_COLUMNS: [&str; 5] = ["id", "Код", "Род", "Вид", "Размеры_мм"];
+
+// Длинные описания в таблице терминала обрезаются до этой ширины
+const MAX_CELL_WIDTH: usize = 40;
+
+// Столбцы из --columns ("id" или имена из COLUMNS) либо набор по умолчанию для формата
+pub fn select_columns(requested: &[String], format: Format) -> Result<Vec<&'static str>, String> {
+    if requested.is_empty() {
+        return Ok(match format {
+            Format::Table | Format::Markdown => SUMMARY_COLUMNS.to_vec(),
+            _ => std::iter::once("id").chain(COLUMNS).collect(),
+        });
+    }
+
+    requested.iter()
+        .map(|name| {
+            let name = name.trim();
+            std::iter::once("id")
+                .chain(COLUMNS)
+                .find(|c| *c == name)
+                .ok_or_else(|| format!("неизвестный столбец {}", name))
+        })
+        .collect()
+}
+
+pub fn print_records(records: &[Eucarinogammarus], columns: &[&str], format: Format) {
+    match format {
+        Format::Json | Format::Jsonl => {
+            let objects: Vec<Value> = records.iter().map(|r| record_json(r, columns)).collect();
+            print_json(objects, format);
+        }
+        _ => {
+            let header: Vec<&str> = columns.iter().map(|c| header_name(c, format)).collect();
+            let rows: Vec<Vec<String>> = records.iter()
+                .map(|r| columns.iter().map(|c| value(r, c)).collect())
+                .collect();
+            print_table(&header, &rows, format);
+        }
+    }
+}
+
+// Одна запись: объект JSON или по строке на поле
+pub fn print_record(record: &Eucarinogammarus, format: Format) {
+    let columns: Vec<&str> = std::iter::once("id").chain(COLUMNS).collect();
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(&record_json(record, &columns)).unwrap_or_default()),
+        Format::Jsonl => println!("{}", record_json(record, &columns)),
+        _ => {
+            let rows: Vec<Vec<String>> = columns.iter()
+                .map(|c| vec![header_name(c, format).to_string(), value(record, c)])
+                .collect();
+            let header = match format {
+                Format::Tsv => ["column", "value"],
+                _ => ["Поле", "Значение"],
+            };
+            // Значения полей в карточке записи не обрезаются
+            print_full_table(&header, &rows, format);
+        }
+    }
+}
+
+// Таблица с заголовком в одном из табличных форматов. JSON-форматы получают
+// объекты, ключи которых — заголовки столбцов
+pub fn print_table(header: &[&str], rows: &[Vec<String>], format: Format) {
+    match format {
+        Format::Table => {
+            for line in aligned_lines(header, rows, Some(MAX_CELL_WIDTH)) {
+                println!("{}", line);
+            }
+        }
+        _ => print_full_table(header, rows, format),
+    }
+}
+
+fn print_full_table(header: &[&str], rows: &[Vec<String>], format: Format) {
+    match format {
+        Format::Table | Format::Tsv | Format::Markdown => {
+            for line in text_lines(header, rows, format) {
+                println!("{}", line);
+            }
+        }
+        Format::Json | Format::Jsonl => {
+            let objects: Vec<Value> = rows.iter()
+                .map(|row| {
+                    let object: Map<String, Value> = header.iter()
+                        .zip(row)
+                        .map(|(h, v)| (h.to_string(), Value::from(v.as_str())))
+                        .collect();
+                    Value::Object(object)
+                })
+                .collect();
+            print_json(objects, format);
+        }
+    }
+}
+
+pub fn print_json(objects: Vec<Value>, format: Format) {
+    if format == Format::Jsonl {
+        for object in objects {
+            println!("{}", object);
+        }
+    } else {
+        println!("{}", serde_json::to_string_pretty(&Value::Array(objects)).unwrap_or_default());
+    }
+}
+
+// Строки таблицы в текстовом формате; значения не обрезаются
+fn text_lines(header: &[&str], rows: &[Vec<String>], format: Format) -> Vec<String> {
+    match format {
+        Format::Tsv => std::iter::once(header.join("\t"))
+            .chain(rows.iter().map(|row| row.iter().map(|v| tsv_value(v)).collect::<Vec<_>>().join("\t")))
+            .collect(),
+        Format::Markdown => {
+            let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+            let mut lines = vec![
+                line(header.iter().map(|h| markdown_value(h)).collect()),
+                format!("|{}|", vec![" --- "; header.len()].join("|")),
+            ];
+            lines.extend(rows.iter().map(|row| line(row.iter().map(|v| markdown_value(v)).collect())));
+            lines
+        }
+        _ => aligned_lines(header, rows, None),
+    }
+}
+
+// Столбцы выравниваются по ширине на экране, а не по числу байт или символов,
+// чтобы кириллица и широкие символы не сдвигали таблицу
+fn aligned_lines(header: &[&str], rows: &[Vec<String>], max_width: Option<usize>) -> Vec<String> {
+    let cells: Vec<Vec<String>> = std::iter::once(header.iter().map(|h| h.to_string()).collect())
+        .chain(rows.iter().map(|row| {
+            row.iter()
+                .map(|v| {
+                    let v = v.split_whitespace().collect::<Vec<_>>().join(" ");
+                    match max_width {
+                        Some(width) => truncate(&v, width),
+                        None => v,
+                    }
+                })
+                .collect()
+        }))
+        .collect();
+
+    let mut widths = vec![0; header.len()];
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (i, row) in cells.iter().enumerate() {
+        let line: Vec<String> = row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.width())))
+            .collect();
+        lines.push(line.join("  ").trim_end().to_string());
+        if i == 0 {
+            let rule: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+            lines.push(rule.join("  "));
+        }
+    }
+    lines
+}
+
+fn truncate(value: &str, max_width: usize) -> String {
+    if value.width() <= max_width {
+        return value.to_string();
+    }
+    let mut result = String::new();
+    for c in value.chars() {
+        if result.width() + c.to_string().width() + 1 > max_width {
+            break;
+        }
+        result.push(c);
+    }
+    result.push('…');
+    result
+}
+
+fn record_json(record: &Eucarinogammarus, columns: &[&str]) -> Value {
+    let object: Map<String, Value> = columns.iter()
+        .map(|c| {
+            let v = match *c {
+                "id" => Value::from(record.id),
+                _ => Value::from(record.field(c).unwrap_or("")),
+            };
+            (c.to_string(), v)
+        })
+        .collect();
+    Value::Object(object)
+}
+
+fn value(record: &Eucarinogammarus, column: &str) -> String {
+    match column {
+        "id" => record.id.to_string(),
+        _ => record.field(column).unwrap_or("").trim().to_string(),
+    }
+}
+
+// Для машинных форматов — имя столбца базы, для человека — подпись
+fn header_name(column: &str, format: Format) -> &str {
+    match (format, column) {
+        (Format::Tsv, _) => column,
+        (_, "id") => "ID",
+        _ => COLUMNS.iter()
+            .position(|c| *c == column)
+            .map(|i| COLUMN_LABELS[i])
+            .unwrap_or(column),
+    }
+}
+
+// Табуляции и переводы строк внутри значения сломали бы разбор TSV
+fn tsv_value(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
+
+fn markdown_value(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_selected_by_name_or_default() {
+        assert_eq!(select_columns(&[], Format::Table).unwrap(), SUMMARY_COLUMNS);
+        let all = select_columns(&[], Format::Json).unwrap();
+        assert_eq!((all[0], all.len()), ("id", COLUMNS.len() + 1));
+
+        let requested = vec![" Вид ".to_string(), "id".to_string()];
+        assert_eq!(select_columns(&requested, Format::Tsv).unwrap(), ["Вид", "id"]);
+        assert!(select_columns(&["Цвет".to_string()], Format::Table).is_err());
+    }
+
+    #[test]
+    fn tsv_and_markdown_values_are_escaped() {
+        assert_eq!(tsv_value("строка\tвторая\r\nтретья"), "строка вторая  третья");
+        assert_eq!(markdown_value("I|II\nIII"), "I\\|II III");
+
+        let rows = vec![vec!["1".to_string(), "a\tb".to_string()]];
+        assert_eq!(text_lines(&["id", "Тело"], &rows, Format::Tsv), ["id\tТело", "1\ta b"]);
+        let rows = vec![vec!["1".to_string(), "a|b".to_string()]];
+        assert_eq!(
+            text_lines(&["ID", "Тело"], &rows, Format::Markdown),
+            ["| ID | Тело |", "| --- | --- |", "| 1 | a\\|b |"]
+        );
+    }
+
+    #[test]
+    fn truncation_counts_screen_width() {
+        assert_eq!(truncate("гладкое", 7), "гладкое");
+        assert_eq!(truncate("гладкое тело", 8), "гладкое…");
+        // Иероглиф занимает две позиции на экране
+        assert_eq!(truncate("端脚目端脚目", 5), "端脚…");
+        assert_eq!(truncate("端脚目", 6), "端脚目");
+    }
+
+    #[test]
+    fn aligned_columns_use_screen_width() {
+        let rows = vec![
+            vec!["1".to_string(), "端脚".to_string(), "x".to_string()],
+            vec!["22".to_string(), "Вид".to_string(), "очень длинное   описание".to_string()],
+        ];
+        let lines = aligned_lines(&["ID", "Вид", "Тело"], &rows, Some(10));
+        assert_eq!(lines, [
+            "ID  Вид   Тело",
+            "──  ────  ──────────",
+            "1   端脚  x",
+            "22  Вид   очень дли…",
+        ]);
+    }
+}
@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+// Чтение пароля: с терминала — без отображения вводимых символов,
+// из канала (в скриптах) — первой строкой stdin
+pub fn read(prompt: &str) -> io::Result<String> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+
+    let echo = EchoOff::new();
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    drop(echo);
+    if io::stdin().is_terminal() {
+        eprintln!();
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// Новый пароль вводится дважды
+pub fn read_new() -> Result<String, Box<dyn Error>> {
+    let password = read("Новый пароль: ")?;
+    if password.is_empty() {
+        return Err("пароль не может быть пустым".into());
+    }
+    if read("Повторите пароль: ")? != password {
+        return Err("пароли не совпадают".into());
+    }
+    Ok(password)
+}
+
+// Отключение эха терминала до конца ввода; прежний режим возвращается в drop
+#[cfg(unix)]
+struct EchoOff(Option<libc::termios>);
+
+#[cfg(unix)]
+impl EchoOff {
+    fn new() -> Self {
+        if !io::stdin().is_terminal() {
+            return EchoOff(None);
+        }
+        unsafe {
+            let mut term: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut term) != 0 {
+                return EchoOff(None);
+            }
+            let saved = term;
+            term.c_lflag &= !libc::ECHO;
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &term);
+            EchoOff(Some(saved))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.0 {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+            }
+        }
+    }
+}
+
+// На других платформах вводимый пароль виден
+#[cfg(not(unix))]
+struct EchoOff;
+
+#[cfg(not(unix))]
+impl EchoOff {
+    fn new() -> Self {
+        EchoOff
+    }
+}
@@ -0,0 +1,113 @@
+use regex::Regex;
+use rusqlite::Connection;
+
+use crate::db::{self, Eucarinogammarus, COLUMNS};
+use crate::undo::Command;
+use crate::error::DbError;
+
+// Способ массового изменения столбца
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkMode {
+    Set,
+    Replace,
+    RegexReplace,
+    Append,
+}
+
+impl BulkMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BulkMode::Set => "Установить значение",
+            BulkMode::Replace => "Найти и заменить",
+            BulkMode::RegexReplace => "Найти и заменить (регулярное выражение)",
+            BulkMode::Append => "Дописать в конец",
+        }
+    }
+}
+
+// Изменение одной записи, показываемое в предпросмотре
+#[derive(Debug, Clone)]
+pub struct BulkChange {
+    pub id: i32,
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+// Новые значения столбца для выбранных записей; записи без изменений пропускаются
+pub fn preview(
+    records: &[&Eucarinogammarus],
+    column: &str,
+    mode: BulkMode,
+    find: &str,
+    value: &str,
+) -> Result<Vec<BulkChange>, DbError> {
+    if !COLUMNS.contains(&column) {
+        return Err(DbError::InvalidColumn(column.to_string()));
+    }
+    if matches!(mode, BulkMode::Replace | BulkMode::RegexReplace) && find.is_empty() {
+        return Err(DbError::Invalid("Не задан искомый текст".to_string()));
+    }
+
+    let regex = match mode {
+        BulkMode::RegexReplace => Some(Regex::new(find)?),
+        _ => None,
+    };
+
+    let mut changes = Vec::new();
+    for record in records {
+        let old = record.field(column).unwrap_or("");
+        let new = match mode {
+            BulkMode::Set => value.to_string(),
+            BulkMode::Replace => old.replace(find, value),
+            BulkMode::RegexReplace => regex.as_ref().map(|r| r.replace_all(old, value).into_owned()).unwrap_or_default(),
+            BulkMode::Append => format!("{}{}", old, value),
+        };
+
+        if new != old {
+            changes.push(BulkChange {
+                id: record.id,
+                name: format!("{} {}", record.genus, record.species),
+                old: old.to_string(),
+                new,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+// Применение изменений в одной транзакции; возвращает команду для отмены.
+// Если значение уже не то, что было в предпросмотре (его изменил другой
+// пользователь), транзакция откатывается с DbError::Conflict
+pub fn apply(conn: &Connection, column: &str, changes: &[BulkChange]) -> Result<Command, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let commands = write_changes(&tx, column, changes)?;
+    tx.commit()?;
+
+    Ok(Command::Bulk {
+        description: format!("массовая правка {} ({} записей)", column, changes.len()),
+        commands,
+    })
+}
+
+// Запись изменений без своей транзакции — для операций, которые в той же
+// транзакции меняют что-то ещё; возвращает команды отмены для каждой записи
+pub(crate) fn write_changes(conn: &Connection, column: &str, changes: &[BulkChange]) -> Result<Vec<Command>, DbError> {
+    if !COLUMNS.contains(&column) {
+        return Err(DbError::InvalidColumn(column.to_string()));
+    }
+
+    for change in changes {
+        db::update_field_expecting(conn, change.id, column, &change.old, &change.new)?;
+    }
+
+    Ok(changes.iter()
+        .map(|c| Command::Update {
+            id: c.id,
+            column: column.to_string(),
+            old: c.old.clone(),
+            new: c.new.clone(),
+        })
+        .collect())
+}
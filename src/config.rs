@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::DbError;
+
+// База по умолчанию, если путь не задан ни аргументом --db, ни настройками
+pub const DEFAULT_DATABASE: &str = "eucarinogammarus.db";
+
+// CSV для начального заполнения пустой базы; ищется рядом с файлом базы
+pub const SEED_CSV: &str = "Eucarinogammarus.csv";
+
+// Сколько недавних баз помнит меню «Файл»
+const MAX_RECENT: usize = 10;
+
+// Настройки пользователя, общие для всех интерфейсов. Хранятся в
+// config.toml в каталоге настроек системы (~/.config/eucarinogammarus,
+// %APPDATA%\eucarinogammarus и т.п.)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub last_database: Option<PathBuf>,
+    pub recent_databases: Vec<PathBuf>,
+    pub backup: BackupSettings,
+}
+
+// Когда делать снимки базы и сколько их хранить (см. backup)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub on_startup: bool,
+    pub before_import: bool,
+    pub before_bulk_edit: bool,
+    // Сколько автоматических снимков каждого вида хранить; снимки, сделанные вручную, не удаляются
+    pub keep: usize,
+    // Каталог снимков; по умолчанию backups рядом с файлом базы
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            on_startup: true,
+            before_import: true,
+            before_bulk_edit: true,
+            keep: 10,
+            directory: None,
+        }
+    }
+}
+
+impl BackupSettings {
+    // Каталог снимков базы path
+    pub fn directory(&self, path: &Path) -> PathBuf {
+        match (&self.directory, path.parent()) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(parent)) => parent.join("backups"),
+            (None, None) => PathBuf::from("backups"),
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("eucarinogammarus").join("config.toml"))
+    }
+
+    // Чтение настроек; отсутствующий файл даёт настройки по умолчанию
+    pub fn load() -> Result<Self, DbError> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, DbError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self) -> Result<(), DbError> {
+        let path = Self::path().ok_or_else(|| DbError::Invalid("Не найден каталог настроек".to_string()))?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), DbError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Путь к базе: аргумент командной строки, затем последняя открытая база,
+    // затем eucarinogammarus.db в текущем каталоге
+    pub fn database_path(&self, arg: Option<&Path>) -> PathBuf {
+        arg.map(Path::to_path_buf)
+            .or_else(|| self.last_database.clone())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DATABASE))
+    }
+
+    // Запоминание открытой базы: она становится последней и первой в списке недавних
+    pub fn remember(&mut self, path: &Path) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.recent_databases.retain(|p| *p != path);
+        self.recent_databases.insert(0, path.clone());
+        self.recent_databases.truncate(MAX_RECENT);
+        self.last_database = Some(path);
+    }
+}
+
+// CSV для начального импорта в базу path
+pub fn seed_csv_path(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(dir) => dir.join(SEED_CSV),
+        None => PathBuf::from(SEED_CSV),
+    }
+}
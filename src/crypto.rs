@@ -0,0 +1,74 @@
+use rusqlite::{params, Connection, DatabaseName, OpenFlags};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::DbError;
+
+// Шифрование файла базы паролем через SQLCipher. Поддержка включается
+// функцией encryption при сборке; без неё зашифрованную базу открыть нельзя,
+// а операции с паролем возвращают ошибку. Пароль нигде не сохраняется
+
+pub const ENABLED: bool = cfg!(feature = "encryption");
+
+// Открытие файла базы; с паролем — как зашифрованной
+pub fn open(path: &Path, flags: OpenFlags, password: Option<&str>) -> Result<Connection, DbError> {
+    let conn = Connection::open_with_flags(path, flags).map_err(|e| DbError::from(e).at(path))?;
+    if let Some(password) = password {
+        set_key(&conn, password)?;
+    }
+    Ok(conn)
+}
+
+// Пароль для только что открытого соединения; проверяется первым запросом
+pub fn set_key(conn: &Connection, password: &str) -> Result<(), DbError> {
+    supported()?;
+    conn.pragma_update(None, "key", password)?;
+    Ok(())
+}
+
+// Файл есть, но заголовок не как у базы SQLite, а размер кратен странице:
+// похоже на зашифрованную базу, а не на посторонний файл
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    let read = File::open(path).and_then(|mut file| {
+        file.read_exact(&mut header)?;
+        Ok(file.metadata()?.len())
+    });
+    match read {
+        Ok(len) => &header != b"SQLite format 3\0" && len % 512 == 0,
+        Err(_) => false,
+    }
+}
+
+// Копия базы conn в новый файл dest, зашифрованная паролем password;
+// без пароля копия не зашифрована
+pub fn export(conn: &Connection, dest: &Path, password: Option<&str>) -> Result<(), DbError> {
+    supported()?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS export KEY ?2",
+        params![dest.to_string_lossy(), password.unwrap_or("")],
+    )?;
+    // sqlcipher_export не переносит номер версии схемы
+    let result = conn.query_row("SELECT sqlcipher_export('export')", [], |_| Ok(()))
+        .and_then(|_| conn.query_row("PRAGMA main.user_version", [], |row| row.get::<_, i64>(0)))
+        .and_then(|version| conn.pragma_update(Some(DatabaseName::Attached("export")), "user_version", version));
+    conn.execute("DETACH DATABASE export", [])?;
+    Ok(result?)
+}
+
+// Смена пароля зашифрованной базы на месте
+pub fn rekey(conn: &Connection, password: &str) -> Result<(), DbError> {
+    supported()?;
+    conn.pragma_update(None, "rekey", password)?;
+    Ok(())
+}
+
+// Ошибка, если программа собрана без поддержки шифрования
+pub fn supported() -> Result<(), DbError> {
+    if ENABLED {
+        Ok(())
+    } else {
+        Err(DbError::Invalid("Программа собрана без поддержки шифрования (функция encryption)".to_string()))
+    }
+}
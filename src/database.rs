@@ -0,0 +1,440 @@
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::audit::{self, AuditEntry};
+use crate::backup::{self, Snapshot, SnapshotInfo, SnapshotReason};
+use crate::config::BackupSettings;
+use crate::crypto;
+use crate::db::{self, Eucarinogammarus, FieldError, SortDirection, COLUMNS};
+use crate::dwca::export::{self, DwcMapping, ExportOptions, ExportSummary};
+use crate::dwca::import::{self, ImportSummary, PendingImport};
+use crate::error::DbError;
+use crate::merge::{self, MergeSummary, PendingMerge};
+use crate::sync::{self, ChangeSet, SyncEntry, SyncSummary};
+use crate::textdir::{self, TextSummary};
+use crate::trash::{self, TrashedRecord};
+use crate::undo::Command;
+
+// Сколько запись ждёт, пока базу освободит другое соединение
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Открытая база данных Eucarinogammarus: соединение с применённой схемой
+// и триггерами журнала. Через него работают все интерфейсы и сторонние
+// программы анализа; модули db, trash, audit и др. остаются доступны
+// для операций, которых здесь нет, через conn()
+pub struct Database {
+    conn: Connection,
+    path: Option<PathBuf>,
+    user_name: String,
+    // Пароль зашифрованной базы; нужен для снимков и копий
+    password: Option<String>,
+}
+
+impl Database {
+    // Открытие файла базы (создаётся, если его нет) с применением миграций
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DbError> {
+        Self::open_file(path.as_ref(), None)
+    }
+
+    // Открытие зашифрованной базы; неверный пароль — DbError::Encrypted
+    pub fn open_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Self, DbError> {
+        Self::open_file(path.as_ref(), Some(password))
+    }
+
+    // База в памяти; удобна для тестов и разовых расчётов
+    pub fn open_in_memory() -> Result<Self, DbError> {
+        Self::from_connection(Connection::open_in_memory()?, None, None)
+    }
+
+    // Другой файл базы с тем же паролем, что у этой (например, её копия)
+    pub fn open_copy<P: AsRef<Path>>(&self, path: P) -> Result<Self, DbError> {
+        Self::open_file(path.as_ref(), self.password.as_deref())
+    }
+
+    // Открытие базы; новый файл сначала заполняется из CSV. Если CSV нет
+    // или импорт не удался, файл базы не остаётся на диске
+    pub fn open_seeded<P: AsRef<Path>, Q: AsRef<Path>>(path: P, csv_path: Q) -> Result<Self, DbError> {
+        let (path, csv_path) = (path.as_ref(), csv_path.as_ref());
+        if path.exists() {
+            return Self::open(path);
+        }
+        if !csv_path.is_file() {
+            return Err(DbError::SeedMissing(csv_path.to_path_buf()));
+        }
+
+        let mut database = Self::open(path)?;
+        let result = database.conn.transaction()
+            .map_err(DbError::from)
+            .and_then(|tx| {
+                db::import_csv(&tx, &csv_path.to_string_lossy())?;
+                Ok(tx.commit()?)
+            });
+        match result {
+            Ok(()) => Ok(database),
+            Err(e) => {
+                drop(database);
+                let _ = fs::remove_file(path);
+                Err(e)
+            }
+        }
+    }
+
+    // Новая база path, собранная из текстового каталога dir (см. textdir).
+    // Существующий файл не перезаписывается; при ошибке файл не остаётся
+    pub fn create_from_text<P: AsRef<Path>, Q: AsRef<Path>>(path: P, dir: Q) -> Result<(Self, TextSummary), DbError> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(DbError::Invalid(format!("Файл {} уже существует", path.display())));
+        }
+
+        let database = Self::open(path)?;
+        match textdir::import(&database.conn, dir.as_ref()) {
+            Ok(summary) => Ok((database, summary)),
+            Err(e) => {
+                drop(database);
+                let _ = fs::remove_file(path);
+                Err(e)
+            }
+        }
+    }
+
+    fn open_file(path: &Path, password: Option<&str>) -> Result<Self, DbError> {
+        Connection::open(path)
+            .map_err(DbError::from)
+            .and_then(|conn| {
+                if let Some(password) = password {
+                    crypto::set_key(&conn, password)?;
+                }
+                Self::from_connection(conn, Some(path.to_path_buf()), password)
+            })
+            .map_err(|e| e.at(path))
+    }
+
+    fn from_connection(conn: Connection, path: Option<PathBuf>, password: Option<&str>) -> Result<Self, DbError> {
+        let database = Self {
+            conn,
+            path,
+            user_name: audit::current_user(),
+            password: password.map(str::to_string),
+        };
+        // Файл базы могут открыть сразу несколько пользователей: в режиме WAL
+        // чтение не ждёт чужой записи, а запись ждёт освобождения базы до
+        // BUSY_TIMEOUT и лишь потом возвращает DbError::Locked
+        if database.path.is_some() {
+            database.conn.busy_timeout(BUSY_TIMEOUT)?;
+            database.conn.pragma_update(None, "journal_mode", "WAL")?;
+        }
+        database.migrate()?;
+        audit::install_triggers(&database.conn, &database.user_name)?;
+        Ok(database)
+    }
+
+    // Применение недостающих миграций; повторный вызов ничего не меняет
+    pub fn migrate(&self) -> Result<(), DbError> {
+        db::init_schema(&self.conn)
+    }
+
+    // Номер последней применённой миграции
+    pub fn schema_version(&self) -> Result<usize, DbError> {
+        Ok(self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    // Файл базы; None для базы в памяти
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    // Копия базы в новый файл (существующий файл не перезаписывается).
+    // Сама база остаётся открытой; чтобы работать с копией, её открывают заново
+    pub fn save_as<P: AsRef<Path>>(&self, path: P) -> Result<(), DbError> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(DbError::Invalid(format!("Файл {} уже существует", path.display())));
+        }
+        self.conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+        Ok(())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.password.is_some()
+    }
+
+    // Включение (Some для незашифрованной базы), смена (Some) или снятие (None)
+    // шифрования. Для включения и снятия база переписывается во временный файл,
+    // который затем заменяет прежний; снимки, сделанные раньше, не меняются
+    pub fn set_password(&mut self, password: Option<&str>) -> Result<(), DbError> {
+        if password.is_some_and(str::is_empty) {
+            return Err(DbError::Invalid("Пароль не может быть пустым".to_string()));
+        }
+        match (self.password.is_some(), password) {
+            (false, None) => return Ok(()),
+            (true, Some(password)) => {
+                crypto::rekey(&self.conn, password)?;
+                self.password = Some(password.to_string());
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let path = self.file()?.to_path_buf();
+        let temp = path.with_extension("rekey");
+        if temp.exists() {
+            fs::remove_file(&temp)?;
+        }
+        crypto::export(&self.conn, &temp, password)?;
+
+        // Соединение закрывается до замены файла: на Windows открытый файл не заменить
+        let previous = self.password.take();
+        drop(std::mem::replace(&mut self.conn, Connection::open_in_memory()?));
+        if let Err(e) = fs::rename(&temp, &path) {
+            let _ = fs::remove_file(&temp);
+            *self = Self::open_file(&path, previous.as_deref())?;
+            return Err(e.into());
+        }
+        *self = Self::open_file(&path, password)?;
+        Ok(())
+    }
+
+    // Снимок базы через backup API SQLite; старые автоматические снимки
+    // удаляются по settings.keep
+    pub fn snapshot(&self, settings: &BackupSettings, reason: SnapshotReason) -> Result<Snapshot, DbError> {
+        let path = self.file()?;
+        let dir = settings.directory(path);
+        let snapshot = backup::create(&self.conn, path, &dir, reason, self.password.as_deref())?;
+        backup::rotate(path, &dir, settings.keep)?;
+        Ok(snapshot)
+    }
+
+    // Снимки этой базы, новые сверху
+    pub fn snapshots(&self, settings: &BackupSettings) -> Result<Vec<Snapshot>, DbError> {
+        let path = self.file()?;
+        backup::list(path, &settings.directory(path))
+    }
+
+    // Замена содержимого базы снимком. Текущее состояние перед этим
+    // сохраняется в снимок «перед восстановлением», который и возвращается
+    pub fn restore_snapshot(&mut self, settings: &BackupSettings, snapshot: &Path) -> Result<(Snapshot, SnapshotInfo), DbError> {
+        // Ротация — после восстановления, чтобы не удалить сам восстанавливаемый снимок
+        let path = self.file()?.to_path_buf();
+        let dir = settings.directory(&path);
+        let password = self.password.clone();
+        let before = backup::create(&self.conn, &path, &dir, SnapshotReason::Restore, password.as_deref())?;
+        let info = backup::restore(&mut self.conn, snapshot, password.as_deref())?;
+        self.migrate()?;
+        audit::install_triggers(&self.conn, &self.user_name)?;
+        backup::rotate(&path, &dir, settings.keep)?;
+        Ok((before, info))
+    }
+
+    // Содержимое снимка этой базы для предпросмотра
+    pub fn inspect_snapshot(&self, snapshot: &Path) -> Result<SnapshotInfo, DbError> {
+        backup::inspect(snapshot, self.password.as_deref())
+    }
+
+    fn file(&self) -> Result<&Path, DbError> {
+        self.path().ok_or_else(|| DbError::Invalid("У базы в памяти нет файла".to_string()))
+    }
+
+    // Пользователь, от имени которого пишется журнал изменений
+    pub fn user_name(&self) -> &str {
+        &self.user_name
+    }
+
+    pub fn conn(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn conn_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+
+    // Число неудалённых записей
+    pub fn count(&self) -> Result<usize, DbError> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM Eucarinogammarus WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    pub fn records(&self) -> Result<Vec<Eucarinogammarus>, DbError> {
+        db::load_records(&self.conn)
+    }
+
+    // Записи, упорядоченные по "id" или столбцу из COLUMNS
+    pub fn records_sorted(&self, column: &str, direction: SortDirection) -> Result<Vec<Eucarinogammarus>, DbError> {
+        db::load_records_sorted(&self.conn, column, direction)
+    }
+
+    // Запись по ID; записи в корзине не возвращаются
+    pub fn record(&self, id: i32) -> Result<Option<Eucarinogammarus>, DbError> {
+        db::load_record(&self.conn, id)
+    }
+
+    // Записи, в любом поле которых есть подстрока term (без учёта регистра)
+    pub fn search(&self, term: &str) -> Result<Vec<Eucarinogammarus>, DbError> {
+        Ok(self.records()?.into_iter().filter(|r| r.matches(term)).collect())
+    }
+
+    // Добавление записи; поле id не используется. Возвращает ID новой записи
+    pub fn insert(&self, record: &Eucarinogammarus) -> Result<i32, DbError> {
+        self.insert_values(&record.values())
+    }
+
+    // Добавление записи по значениям в порядке COLUMNS
+    pub fn insert_values(&self, values: &[&str]) -> Result<i32, DbError> {
+        Ok(db::insert_values(&self.conn, values)? as i32)
+    }
+
+    // Изменение одного поля; возвращает запись после изменения.
+    // Записи в корзине не изменяются: для них, как и для несуществующих, DbError::NotFound
+    pub fn update(&self, id: i32, column: &str, value: &str) -> Result<Eucarinogammarus, DbError> {
+        db::update_field(&self.conn, id, column, value)?;
+        self.record(id)?.ok_or(DbError::NotFound(id))
+    }
+
+    // Изменение одного поля, если запись не менялась с версии version
+    // (см. versions); если её успел изменить другой пользователь — DbError::Conflict
+    pub fn update_checked(&self, id: i32, column: &str, value: &str, version: i64) -> Result<Eucarinogammarus, DbError> {
+        db::update_field_checked(&self.conn, id, column, value, version)?;
+        self.record(id)?.ok_or(DbError::NotFound(id))
+    }
+
+    // Версии неудалённых записей по ID
+    pub fn versions(&self) -> Result<HashMap<i32, i64>, DbError> {
+        db::load_versions(&self.conn)
+    }
+
+    // Счётчик изменений базы другими соединениями (PRAGMA data_version):
+    // если он изменился с прошлого чтения, загруженные записи устарели
+    pub fn data_version(&self) -> Result<i64, DbError> {
+        Ok(self.conn.query_row("PRAGMA data_version", [], |row| row.get(0))?)
+    }
+
+    // Запись всех полей record в запись с тем же ID одной транзакцией, если
+    // запись не менялась с версии version (см. versions); иначе DbError::Conflict.
+    // Журнал получает только действительно изменённые поля
+    pub fn update_record(&mut self, record: &Eucarinogammarus, version: i64) -> Result<Eucarinogammarus, DbError> {
+        let tx = self.conn.transaction()?;
+        // Версия проверяется на первом поле; дальше транзакция держит блокировку
+        // записи, и другое соединение не может изменить запись до фиксации
+        for (i, column) in COLUMNS.iter().enumerate() {
+            let value = record.field(column).unwrap_or("");
+            if i == 0 {
+                db::update_field_checked(&tx, record.id, column, value, version)?;
+            } else {
+                db::update_field(&tx, record.id, column, value)?;
+            }
+        }
+        tx.commit()?;
+        self.record(record.id)?.ok_or(DbError::NotFound(record.id))
+    }
+
+    // Проверка значений по db::VALIDATION_RULES; id — проверяемая запись, если она уже есть
+    pub fn validate(&self, id: Option<i32>, values: &[(&str, &str)]) -> Result<Vec<FieldError>, DbError> {
+        db::validate_values(&self.conn, id, values)
+    }
+
+    // Нарушения правил проверки во всех записях
+    pub fn validate_all(&self) -> Result<Vec<FieldError>, DbError> {
+        db::validate_all(&self.conn)
+    }
+
+    // Перемещение записи в корзину; возвращает удалённую запись
+    pub fn delete(&self, id: i32) -> Result<Eucarinogammarus, DbError> {
+        let record = self.record(id)?.ok_or(DbError::NotFound(id))?;
+        trash::soft_delete(&self.conn, id, &self.user_name)?;
+        Ok(record)
+    }
+
+    // Восстановление из корзины; возвращает восстановленную запись
+    pub fn restore(&self, id: i32) -> Result<Eucarinogammarus, DbError> {
+        trash::restore(&self.conn, id)?;
+        self.record(id)?.ok_or(DbError::NotFound(id))
+    }
+
+    // Окончательное удаление записи, уже находящейся в корзине; возвращает её
+    pub fn purge(&mut self, id: i32) -> Result<Eucarinogammarus, DbError> {
+        let trashed = trash::load_trashed(&self.conn, id)?.ok_or(DbError::NotFound(id))?;
+        trash::purge(&mut self.conn, id)?;
+        Ok(trashed.record)
+    }
+
+    pub fn trash(&self) -> Result<Vec<TrashedRecord>, DbError> {
+        trash::load_trash(&self.conn)
+    }
+
+    pub fn history(&self, id: i32) -> Result<Vec<AuditEntry>, DbError> {
+        audit::load_history(&self.conn, id)
+    }
+
+    // Импорт из CSV; возвращает число добавленных записей
+    // Все строки добавляются в одной транзакции: при ошибке база не меняется
+    pub fn import_csv(&self, file_path: &str) -> Result<usize, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let count = db::import_csv(&tx, file_path)?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    // Экспорт неудалённых записей в CSV; возвращает их число
+    pub fn export_csv(&self, file_path: &str) -> Result<usize, DbError> {
+        let records = self.records()?;
+        db::export_csv(&records, file_path)?;
+        Ok(records.len())
+    }
+
+    // Экспорт в архив Darwin Core; ошибка проверки — export::ValidationError
+    pub fn export_dwca(&self, file_path: &str, mapping: &DwcMapping, options: &ExportOptions) -> Result<ExportSummary, DbError> {
+        export::export_dwca(&self.conn, file_path, mapping, options)
+    }
+
+    // Сравнение с другим файлом базы для слияния (см. merge); другой файл не изменяется
+    pub fn compare_with<P: AsRef<Path>>(&self, path: P) -> Result<PendingMerge, DbError> {
+        let path = path.as_ref();
+        let same = |a: &Path, b: &Path| matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b);
+        if self.path.as_deref().is_some_and(|own| same(own, path)) {
+            return Err(DbError::Invalid("Базу нельзя сравнить саму с собой".to_string()));
+        }
+        let source = merge::read_source(path, self.password.as_deref())?;
+        Ok(merge::compare(&self.records()?, &source, path))
+    }
+
+    // Перенос выбранных отличий из другой базы; команда нужна для отмены
+    pub fn apply_merge(&self, pending: &PendingMerge) -> Result<(MergeSummary, Command), DbError> {
+        merge::apply(&self.conn, pending, &self.user_name)
+    }
+
+    // Выгрузка набора изменений для другой копии базы (см. sync);
+    // без since выгружаются все записи
+    pub fn export_changes<P: AsRef<Path>>(&self, path: P, since: Option<&str>) -> Result<ChangeSet, DbError> {
+        sync::export(&self.conn, path.as_ref(), since)
+    }
+
+    // Применение набора изменений другой копии; повторное применение ничего не меняет
+    pub fn import_changes<P: AsRef<Path>>(&self, path: P) -> Result<SyncSummary, DbError> {
+        sync::import(&self.conn, path.as_ref())
+    }
+
+    // Выгрузка в каталог текстовых файлов для git (см. textdir)
+    pub fn export_text<P: AsRef<Path>>(&self, dir: P) -> Result<TextSummary, DbError> {
+        textdir::export(&self.conn, dir.as_ref())
+    }
+
+    pub fn sync_log(&self) -> Result<Vec<SyncEntry>, DbError> {
+        sync::load_log(&self.conn)
+    }
+
+    pub fn last_export(&self) -> Result<Option<String>, DbError> {
+        sync::last_export(&self.conn)
+    }
+
+    // Применение просмотренного импорта Darwin Core (см. dwca::import)
+    pub fn apply_import(&mut self, pending: &PendingImport) -> Result<ImportSummary, DbError> {
+        import::apply_import(&mut self.conn, pending)
+    }
+}
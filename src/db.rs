@@ -1,11 +1,19 @@
-use rusqlite::{params, Connection, Result};
-use std::error::Error;
-use crate::app::SortDirection;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashMap;
 use std::fs::File;
 use csv::Reader;
+use regex::Regex;
+
+use crate::error::DbError;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
 
 // Структура для хранения данных
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Eucarinogammarus {
     pub id: i32,
     pub code: String,
@@ -34,10 +42,497 @@ pub struct Eucarinogammarus {
     pub telson: String,
 }
 
-// Функция для импорта данных из CSV
-pub fn import_csv(conn: &Connection, file_path: &str) -> Result<(), Box<dyn Error>> {
+// Столбцы таблицы Eucarinogammarus (кроме id) в порядке CSV
+pub const COLUMNS: [&str; 24] = [
+    "Код", "Род", "Вид", "Размеры_мм", "Тело", "Окраска", "Распространение",
+    "Глубина_м", "Вооруж_тела", "Средний_ряд_I_VII", "Средн_ряд_VIII_X",
+    "Сред_ряд_урозом", "Боковой_ряд", "Краевой_ряд", "Особен_воор",
+    "Эпимир_пласт", "Верх_антенны", "Прид_жгутик", "Нижн_антенны",
+    "Базип_III_V", "Уроподы_III", "Головн_сегм", "Глаза", "Тельсон"
+];
+
+// Названия столбцов для отображения, в том же порядке, что и COLUMNS
+pub const COLUMN_LABELS: [&str; 24] = [
+    "Код", "Род", "Вид", "Размеры мм", "Тело", "Окраска", "Распространение",
+    "Глубина м", "Вооруж. тела", "Средний ряд I-VII", "Средн. ряд VIII-X",
+    "Сред. ряд урозом", "Боковой ряд", "Краевой ряд", "Особен. воор.",
+    "Эпимир. пласт.", "Верх. антенны", "Прид. жгутик", "Нижн. антенны",
+    "Базип. III-V", "Уроподы III", "Головн. сегм.", "Глаза", "Тельсон"
+];
+
+// Миграции схемы по порядку; номер применённой хранится в PRAGMA user_version
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS Eucarinogammarus (
+        id INTEGER PRIMARY KEY,
+        Код TEXT,
+        Род TEXT,
+        Вид TEXT,
+        Размеры_мм TEXT,
+        Тело TEXT,
+        Окраска TEXT,
+        Распространение TEXT,
+        Глубина_м TEXT,
+        Вооруж_тела TEXT,
+        Средний_ряд_I_VII TEXT,
+        Средн_ряд_VIII_X TEXT,
+        Сред_ряд_урозом TEXT,
+        Боковой_ряд TEXT,
+        Краевой_ряд TEXT,
+        Особен_воор TEXT,
+        Эпимир_пласт TEXT,
+        Верх_антенны TEXT,
+        Прид_жгутик TEXT,
+        Нижн_антенны TEXT,
+        Базип_III_V TEXT,
+        Уроподы_III TEXT,
+        Головн_сегм TEXT,
+        Глаза TEXT,
+        Тельсон TEXT
+    )",
+    "CREATE TABLE IF NOT EXISTS Locality (
+        id INTEGER PRIMARY KEY,
+        locality TEXT NOT NULL DEFAULT '',
+        country_code TEXT NOT NULL DEFAULT '',
+        water_body TEXT NOT NULL DEFAULT '',
+        decimal_latitude TEXT NOT NULL DEFAULT '',
+        decimal_longitude TEXT NOT NULL DEFAULT '',
+        min_depth_m TEXT NOT NULL DEFAULT '',
+        max_depth_m TEXT NOT NULL DEFAULT ''
+    );
+    CREATE TABLE IF NOT EXISTS Specimen (
+        id INTEGER PRIMARY KEY,
+        taxon_id INTEGER NOT NULL REFERENCES Eucarinogammarus(id),
+        locality_id INTEGER REFERENCES Locality(id),
+        occurrence_id TEXT NOT NULL DEFAULT '',
+        catalog_number TEXT NOT NULL DEFAULT '',
+        institution_code TEXT NOT NULL DEFAULT '',
+        basis_of_record TEXT NOT NULL DEFAULT '',
+        recorded_by TEXT NOT NULL DEFAULT '',
+        event_date TEXT NOT NULL DEFAULT '',
+        individual_count TEXT NOT NULL DEFAULT '',
+        source TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX IF NOT EXISTS specimen_taxon ON Specimen(taxon_id);
+    CREATE INDEX IF NOT EXISTS specimen_occurrence ON Specimen(occurrence_id);",
+    "CREATE TABLE IF NOT EXISTS AuditLog (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+        user_name TEXT NOT NULL DEFAULT '',
+        record_id INTEGER NOT NULL,
+        action TEXT NOT NULL,
+        column_name TEXT,
+        old_value TEXT,
+        new_value TEXT
+    );
+    CREATE INDEX IF NOT EXISTS audit_record ON AuditLog(record_id);",
+    "ALTER TABLE Eucarinogammarus ADD COLUMN deleted_at TEXT;
+    ALTER TABLE Eucarinogammarus ADD COLUMN deleted_by TEXT;",
+    "CREATE TABLE IF NOT EXISTS Vocabulary (
+        id INTEGER PRIMARY KEY,
+        column_name TEXT NOT NULL,
+        term TEXT NOT NULL,
+        UNIQUE (column_name, term)
+    );",
+    // UUID и время изменения записи для сопоставления копий базы (см. sync).
+    // Значения ставят триггеры, поэтому их получают и записи, добавленные
+    // сторонними программами; время — UTC, его можно сравнивать как строки.
+    // UUID версии 4 собирается из randomblob прямо в SQL
+    "ALTER TABLE Eucarinogammarus ADD COLUMN uuid TEXT;
+    ALTER TABLE Eucarinogammarus ADD COLUMN modified_at TEXT;
+    UPDATE Eucarinogammarus SET
+        uuid = lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+            substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', abs(random()) % 4 + 1, 1) ||
+            substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6))),
+        modified_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now');
+    CREATE UNIQUE INDEX IF NOT EXISTS record_uuid ON Eucarinogammarus(uuid);
+    CREATE TRIGGER IF NOT EXISTS record_created AFTER INSERT ON Eucarinogammarus
+    WHEN NEW.uuid IS NULL BEGIN
+        UPDATE Eucarinogammarus SET
+            uuid = lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+                substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', abs(random()) % 4 + 1, 1) ||
+                substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6))),
+            modified_at = COALESCE(NEW.modified_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS record_modified AFTER UPDATE OF
+        Код, Род, Вид, Размеры_мм, Тело, Окраска,
+        Распространение, Глубина_м, Вооруж_тела, Средний_ряд_I_VII, Средн_ряд_VIII_X, Сред_ряд_урозом,
+        Боковой_ряд, Краевой_ряд, Особен_воор, Эпимир_пласт, Верх_антенны, Прид_жгутик,
+        Нижн_антенны, Базип_III_V, Уроподы_III, Головн_сегм, Глаза, Тельсон,
+        deleted_at
+    ON Eucarinogammarus
+    WHEN NEW.modified_at IS OLD.modified_at AND (
+        NEW.Код IS NOT OLD.Код OR NEW.Род IS NOT OLD.Род OR NEW.Вид IS NOT OLD.Вид OR NEW.Размеры_мм IS NOT OLD.Размеры_мм OR
+        NEW.Тело IS NOT OLD.Тело OR NEW.Окраска IS NOT OLD.Окраска OR NEW.Распространение IS NOT OLD.Распространение OR NEW.Глубина_м IS NOT OLD.Глубина_м OR
+        NEW.Вооруж_тела IS NOT OLD.Вооруж_тела OR NEW.Средний_ряд_I_VII IS NOT OLD.Средний_ряд_I_VII OR NEW.Средн_ряд_VIII_X IS NOT OLD.Средн_ряд_VIII_X OR NEW.Сред_ряд_урозом IS NOT OLD.Сред_ряд_урозом OR
+        NEW.Боковой_ряд IS NOT OLD.Боковой_ряд OR NEW.Краевой_ряд IS NOT OLD.Краевой_ряд OR NEW.Особен_воор IS NOT OLD.Особен_воор OR NEW.Эпимир_пласт IS NOT OLD.Эпимир_пласт OR
+        NEW.Верх_антенны IS NOT OLD.Верх_антенны OR NEW.Прид_жгутик IS NOT OLD.Прид_жгутик OR NEW.Нижн_антенны IS NOT OLD.Нижн_антенны OR NEW.Базип_III_V IS NOT OLD.Базип_III_V OR
+        NEW.Уроподы_III IS NOT OLD.Уроподы_III OR NEW.Головн_сегм IS NOT OLD.Головн_сегм OR NEW.Глаза IS NOT OLD.Глаза OR NEW.Тельсон IS NOT OLD.Тельсон OR
+        NEW.deleted_at IS NOT OLD.deleted_at
+    ) BEGIN
+        UPDATE Eucarinogammarus SET modified_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+    CREATE TABLE IF NOT EXISTS Tombstone (
+        uuid TEXT PRIMARY KEY,
+        deleted_at TEXT NOT NULL
+    );
+    CREATE TRIGGER IF NOT EXISTS record_purged AFTER DELETE ON Eucarinogammarus
+    WHEN OLD.uuid IS NOT NULL BEGIN
+        INSERT OR REPLACE INTO Tombstone (uuid, deleted_at) VALUES (OLD.uuid, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END;
+    CREATE TABLE IF NOT EXISTS SyncLog (
+        id INTEGER PRIMARY KEY,
+        changeset TEXT NOT NULL,
+        direction TEXT NOT NULL,
+        file TEXT NOT NULL,
+        timestamp TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+        records INTEGER NOT NULL
+    );",
+    // 7: номер версии записи для проверки одновременных правок
+    "ALTER TABLE Eucarinogammarus ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+    CREATE TRIGGER IF NOT EXISTS record_version AFTER UPDATE OF
+        Код, Род, Вид, Размеры_мм, Тело, Окраска,
+        Распространение, Глубина_м, Вооруж_тела, Средний_ряд_I_VII, Средн_ряд_VIII_X, Сред_ряд_урозом,
+        Боковой_ряд, Краевой_ряд, Особен_воор, Эпимир_пласт, Верх_антенны, Прид_жгутик,
+        Нижн_антенны, Базип_III_V, Уроподы_III, Головн_сегм, Глаза, Тельсон,
+        deleted_at
+    ON Eucarinogammarus
+    WHEN NEW.version = OLD.version BEGIN
+        UPDATE Eucarinogammarus SET version = OLD.version + 1 WHERE id = NEW.id;
+    END;",
+];
+
+// Версия схемы после применения всех миграций
+pub const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+// Создание таблиц и применение недостающих миграций
+pub fn init_schema(conn: &Connection) -> Result<(), DbError> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(sql)?;
+        conn.pragma_update(None, "user_version", i + 1)?;
+    }
+
+    Ok(())
+}
+
+// Изменение одного поля неудалённой записи; нет такой записи — DbError::NotFound
+pub fn update_field(conn: &Connection, id: i32, column: &str, value: &str) -> Result<(), DbError> {
+    if !COLUMNS.contains(&column) {
+        return Err(DbError::InvalidColumn(column.to_string()));
+    }
+
+    let query = format!("UPDATE Eucarinogammarus SET {} = ?1 WHERE id = ?2 AND deleted_at IS NULL", column);
+    if conn.execute(&query, params![value, id])? == 0 {
+        return Err(DbError::NotFound(id));
+    }
+    Ok(())
+}
+
+// Изменение поля, только если запись не менялась с версии version
+// (см. load_versions); иначе DbError::Conflict
+pub fn update_field_checked(conn: &Connection, id: i32, column: &str, value: &str, version: i64) -> Result<(), DbError> {
+    if !COLUMNS.contains(&column) {
+        return Err(DbError::InvalidColumn(column.to_string()));
+    }
+
+    let query = format!(
+        "UPDATE Eucarinogammarus SET {} = ?1 WHERE id = ?2 AND deleted_at IS NULL AND version = ?3",
+        column
+    );
+    if conn.execute(&query, params![value, id, version])? == 0 {
+        return match load_record(conn, id)? {
+            Some(_) => Err(DbError::Conflict(id)),
+            None => Err(DbError::NotFound(id)),
+        };
+    }
+    Ok(())
+}
+
+// Изменение поля, только если в нём всё ещё значение expected, которое
+// было прочитано до правки (NULL читается как пустая строка); иначе DbError::Conflict. Так проверяются операции,
+// которые меняют одну запись несколько раз или отменяются в другом порядке:
+// версия записи для них меняется после каждого шага
+pub fn update_field_expecting(conn: &Connection, id: i32, column: &str, expected: &str, value: &str) -> Result<(), DbError> {
+    if !COLUMNS.contains(&column) {
+        return Err(DbError::InvalidColumn(column.to_string()));
+    }
+
+    let query = format!(
+        "UPDATE Eucarinogammarus SET {0} = ?1 WHERE id = ?2 AND deleted_at IS NULL AND COALESCE({0}, '') = ?3",
+        column
+    );
+    if conn.execute(&query, params![value, id, expected])? == 0 {
+        return match load_record(conn, id)? {
+            Some(_) => Err(DbError::Conflict(id)),
+            None => Err(DbError::NotFound(id)),
+        };
+    }
+    Ok(())
+}
+
+// Версии неудалённых записей по ID; версия растёт при каждом изменении записи
+pub fn load_versions(conn: &Connection) -> Result<HashMap<i32, i64>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, version FROM Eucarinogammarus WHERE deleted_at IS NULL")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<Result<_>>()?)
+}
+
+// Добавление вида только с родом и видом; остальные поля пустые
+pub fn insert_taxon(conn: &Connection, genus: &str, species: &str) -> Result<i64, DbError> {
+    let values: Vec<&str> = COLUMNS.iter()
+        .map(|c| match *c {
+            "Род" => genus,
+            "Вид" => species,
+            _ => "",
+        })
+        .collect();
+
+    insert_values(conn, &values)
+}
+
+// Добавление записи; значения идут в порядке COLUMNS
+pub fn insert_values(conn: &Connection, values: &[&str]) -> Result<i64, DbError> {
+    if values.len() != COLUMNS.len() {
+        return Err(DbError::Invalid(format!("Ожидается {} значений, получено {}", COLUMNS.len(), values.len())));
+    }
+
+    let placeholders: Vec<String> = (1..=COLUMNS.len()).map(|i| format!("?{}", i)).collect();
+    let query = format!(
+        "INSERT INTO Eucarinogammarus ({}) VALUES ({})",
+        COLUMNS.join(", "),
+        placeholders.join(", ")
+    );
+
+    conn.execute(&query, rusqlite::params_from_iter(values))?;
+    Ok(conn.last_insert_rowid())
+}
+
+impl Eucarinogammarus {
+    // Значение поля по имени столбца базы данных
+    pub fn field(&self, column: &str) -> Option<&str> {
+        let value = match column {
+            "Код" => &self.code,
+            "Род" => &self.genus,
+            "Вид" => &self.species,
+            "Размеры_мм" => &self.size_mm,
+            "Тело" => &self.body,
+            "Окраска" => &self.coloration,
+            "Распространение" => &self.distribution,
+            "Глубина_м" => &self.depth_m,
+            "Вооруж_тела" => &self.body_armament,
+            "Средний_ряд_I_VII" => &self.median_row_i_vii,
+            "Средн_ряд_VIII_X" => &self.median_row_viii_x,
+            "Сред_ряд_урозом" => &self.median_row_urozom,
+            "Боковой_ряд" => &self.lateral_row,
+            "Краевой_ряд" => &self.marginal_row,
+            "Особен_воор" => &self.special_armament,
+            "Эпимир_пласт" => &self.epimeral_plate,
+            "Верх_антенны" => &self.upper_antennae,
+            "Прид_жгутик" => &self.accessory_flagellum,
+            "Нижн_антенны" => &self.lower_antennae,
+            "Базип_III_V" => &self.basipodite_iii_v,
+            "Уроподы_III" => &self.uropods_iii,
+            "Головн_сегм" => &self.head_segment,
+            "Глаза" => &self.eyes,
+            "Тельсон" => &self.telson,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    // Значения всех полей в порядке COLUMNS
+    pub fn values(&self) -> Vec<&str> {
+        COLUMNS.iter().map(|c| self.field(c).unwrap_or("")).collect()
+    }
+
+    // Род, вид и ID для сообщений
+    pub fn name(&self) -> String {
+        format!("{} {} (ID {})", self.genus, self.species, self.id)
+    }
+
+    // Поиск подстроки без учёта регистра по всем полям; пустая строка подходит любой записи
+    pub fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        term.is_empty() || COLUMNS.iter().any(|c| self.field(c).unwrap_or("").to_lowercase().contains(&term))
+    }
+}
+
+// Запись из строки запроса со столбцами id и COLUMNS по порядку.
+// Поля, не заполненные при добавлении (NULL), читаются как пустые строки
+pub fn record_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Eucarinogammarus> {
+    let text = |i: usize| -> rusqlite::Result<String> {
+        Ok(row.get::<_, Option<String>>(i)?.unwrap_or_default())
+    };
+
+    Ok(Eucarinogammarus {
+        id: row.get(0)?,
+        code: text(1)?,
+        genus: text(2)?,
+        species: text(3)?,
+        size_mm: text(4)?,
+        body: text(5)?,
+        coloration: text(6)?,
+        distribution: text(7)?,
+        depth_m: text(8)?,
+        body_armament: text(9)?,
+        median_row_i_vii: text(10)?,
+        median_row_viii_x: text(11)?,
+        median_row_urozom: text(12)?,
+        lateral_row: text(13)?,
+        marginal_row: text(14)?,
+        special_armament: text(15)?,
+        epimeral_plate: text(16)?,
+        upper_antennae: text(17)?,
+        accessory_flagellum: text(18)?,
+        lower_antennae: text(19)?,
+        basipodite_iii_v: text(20)?,
+        uropods_iii: text(21)?,
+        head_segment: text(22)?,
+        eyes: text(23)?,
+        telson: text(24)?,
+    })
+}
+
+// Вид правила проверки значения столбца
+#[derive(Debug, Clone, Copy)]
+pub enum Rule {
+    // Значение не может быть пустым
+    Required,
+    // Непустое значение не повторяется среди неудалённых записей
+    Unique,
+    // Непустое значение соответствует регулярному выражению
+    Pattern(&'static str),
+    // Непустое значение входит в утверждённый словарь столбца (таблица
+    // Vocabulary); пока словарь пуст, допустимо любое значение
+    Vocabulary,
+    // Непустое значение — число или диапазон "мин-макс"
+    NumericRange,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ValidationRule {
+    pub column: &'static str,
+    pub rule: Rule,
+}
+
+// Правила проверки данных; применяются при добавлении, редактировании и в отчёте
+pub const VALIDATION_RULES: &[ValidationRule] = &[
+    ValidationRule { column: "Код", rule: Rule::Required },
+    ValidationRule { column: "Код", rule: Rule::Unique },
+    ValidationRule { column: "Род", rule: Rule::Required },
+    ValidationRule { column: "Род", rule: Rule::Vocabulary },
+    ValidationRule { column: "Вид", rule: Rule::Required },
+    ValidationRule { column: "Вид", rule: Rule::Pattern(r"^[a-z][a-z-]*$") },
+    ValidationRule { column: "Размеры_мм", rule: Rule::NumericRange },
+    ValidationRule { column: "Глубина_м", rule: Rule::NumericRange },
+];
+
+// Нарушение правила в конкретном поле
+#[derive(Debug, Clone)]
+pub struct FieldError {
+    pub record_id: Option<i32>,
+    pub column: String,
+    pub value: String,
+    pub message: String,
+}
+
+// Проверка значений записи. id — проверяемая запись, которая при
+// проверке уникальности не сравнивается сама с собой
+pub fn validate_values(
+    conn: &Connection,
+    id: Option<i32>,
+    values: &[(&str, &str)],
+) -> Result<Vec<FieldError>, DbError> {
+    let mut errors = Vec::new();
+
+    for (column, value) in values {
+        let value = value.trim();
+        for rule in VALIDATION_RULES.iter().filter(|r| r.column == *column) {
+            let message = match rule.rule {
+                Rule::Required if value.is_empty() => Some("обязательное поле".to_string()),
+                Rule::Required => None,
+                _ if value.is_empty() => None,
+                Rule::Unique => {
+                    let query = format!(
+                        "SELECT COUNT(*) FROM Eucarinogammarus WHERE TRIM({}) = ?1 AND id IS NOT ?2 AND deleted_at IS NULL",
+                        column
+                    );
+                    let count: i64 = conn.query_row(&query, params![value, id], |row| row.get(0))?;
+                    (count > 0).then(|| "значение уже используется".to_string())
+                }
+                Rule::Pattern(pattern) => {
+                    (!Regex::new(pattern)?.is_match(value)).then(|| format!("не соответствует шаблону {}", pattern))
+                }
+                Rule::Vocabulary => {
+                    let mut stmt = conn.prepare_cached("SELECT term FROM Vocabulary WHERE column_name = ?1 ORDER BY term")?;
+                    let allowed = stmt.query_map(params![column], |row| row.get::<_, String>(0))?
+                        .collect::<Result<Vec<String>>>()?;
+                    (!allowed.is_empty() && !allowed.iter().any(|term| term == value))
+                        .then(|| format!("нет в словаре; допустимые значения: {}", allowed.join(", ")))
+                }
+                Rule::NumericRange => {
+                    (!is_numeric_range(value)).then(|| "ожидается число или диапазон, например 12-15".to_string())
+                }
+            };
+
+            if let Some(message) = message {
+                errors.push(FieldError {
+                    record_id: id,
+                    column: column.to_string(),
+                    value: value.to_string(),
+                    message,
+                });
+            }
+        }
+    }
+
+    Ok(errors)
+}
+
+// Отчёт о качестве данных: нарушения правил во всех неудалённых записях
+pub fn validate_all(conn: &Connection) -> Result<Vec<FieldError>, DbError> {
+    let mut errors = Vec::new();
+    for record in load_records(conn)? {
+        let values: Vec<(&str, &str)> = COLUMNS.iter()
+            .map(|c| (*c, record.field(c).unwrap_or("")))
+            .collect();
+        errors.extend(validate_values(conn, Some(record.id), &values)?);
+    }
+    Ok(errors)
+}
+
+fn is_numeric_range(value: &str) -> bool {
+    let parts: Vec<&str> = value.split(['-', '–']).map(str::trim).collect();
+    let numbers: Vec<f64> = parts.iter()
+        .filter_map(|p| p.replace(',', ".").parse::<f64>().ok())
+        .collect();
+    match numbers.as_slice() {
+        [_] => parts.len() == 1,
+        [min, max] => parts.len() == 2 && min <= max,
+        _ => false,
+    }
+}
+
+// Экспорт записей в CSV. Вторая строка — подписи столбцов: import_csv
+// пропускает её, поэтому файл можно загрузить обратно
+pub fn export_csv(records: &[Eucarinogammarus], file_path: &str) -> Result<(), DbError> {
+    let mut wtr = csv::Writer::from_path(file_path)?;
+    wtr.write_record(COLUMNS)?;
+    wtr.write_record(COLUMN_LABELS)?;
+    for record in records {
+        wtr.write_record(COLUMNS.iter().map(|c| record.field(c).unwrap_or("")))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Функция для импорта данных из CSV; возвращает число добавленных записей
+pub fn import_csv(conn: &Connection, file_path: &str) -> Result<usize, DbError> {
     let file = File::open(file_path)?;
     let mut rdr = Reader::from_reader(file);
+    let mut count = 0;
 
     // Skip the header row
     for result in rdr.records().skip(1) {
@@ -83,50 +578,22 @@ pub fn import_csv(conn: &Connection, file_path: &str) -> Result<(), Box<dyn Erro
                 record.get(23).unwrap_or(""),
             ],
         )?;
+        count += 1;
     }
 
-    println!("Data imported successfully.");
-    Ok(())
+    Ok(count)
 }
 
 // Функция для загрузки записей из базы данных
-pub fn load_records(conn: &Connection) -> Result<Vec<Eucarinogammarus>, Box<dyn Error>> {
+pub fn load_records(conn: &Connection) -> Result<Vec<Eucarinogammarus>, DbError> {
     let mut stmt = conn.prepare("SELECT id, Код, Род, Вид, Размеры_мм, Тело, Окраска, Распространение, 
                                         Глубина_м, Вооруж_тела, Средний_ряд_I_VII, Средн_ряд_VIII_X, Сред_ряд_урозом, 
                                         Боковой_ряд, Краевой_ряд, Особен_воор, Эпимир_пласт, Верх_антенны, 
                                         Прид_жгутик, Нижн_антенны, Базип_III_V, Уроподы_III, Головн_сегм, 
                                         Глаза, Тельсон 
-                                 FROM Eucarinogammarus")?;
+                                 FROM Eucarinogammarus WHERE deleted_at IS NULL")?;
     
-    let records = stmt.query_map([], |row| {
-        Ok(Eucarinogammarus {
-            id: row.get(0)?,
-            code: row.get(1)?,
-            genus: row.get(2)?,
-            species: row.get(3)?,
-            size_mm: row.get(4)?,
-            body: row.get(5)?,
-            coloration: row.get(6)?,
-            distribution: row.get(7)?,
-            depth_m: row.get(8)?,
-            body_armament: row.get(9)?,
-            median_row_i_vii: row.get(10)?,
-            median_row_viii_x: row.get(11)?,
-            median_row_urozom: row.get(12)?,
-            lateral_row: row.get(13)?,
-            marginal_row: row.get(14)?,
-            special_armament: row.get(15)?,
-            epimeral_plate: row.get(16)?,
-            upper_antennae: row.get(17)?,
-            accessory_flagellum: row.get(18)?,
-            lower_antennae: row.get(19)?,
-            basipodite_iii_v: row.get(20)?,
-            uropods_iii: row.get(21)?,
-            head_segment: row.get(22)?,
-            eyes: row.get(23)?,
-            telson: row.get(24)?,
-        })
-    })?;
+    let records = stmt.query_map([], record_from_row)?;
     
     let mut result = Vec::new();
     for record in records {
@@ -136,8 +603,27 @@ pub fn load_records(conn: &Connection) -> Result<Vec<Eucarinogammarus>, Box<dyn
     Ok(result)
 }
 
+// Одна неудалённая запись по ID
+pub fn load_record(conn: &Connection, id: i32) -> Result<Option<Eucarinogammarus>, DbError> {
+    let record = conn.query_row(
+        "SELECT id, Код, Род, Вид, Размеры_мм, Тело, Окраска, Распространение,
+                Глубина_м, Вооруж_тела, Средний_ряд_I_VII, Средн_ряд_VIII_X, Сред_ряд_урозом,
+                Боковой_ряд, Краевой_ряд, Особен_воор, Эпимир_пласт, Верх_антенны,
+                Прид_жгутик, Нижн_антенны, Базип_III_V, Уроподы_III, Головн_сегм,
+                Глаза, Тельсон
+         FROM Eucarinogammarus WHERE id = ?1 AND deleted_at IS NULL",
+        params![id],
+        record_from_row,
+    ).optional()?;
+    Ok(record)
+}
+
 // Функция для загрузки записей из базы данных с сортировкой
-pub fn load_records_sorted(conn: &Connection, sort_column: &str, direction: SortDirection) -> Result<Vec<Eucarinogammarus>, Box<dyn Error>> {
+pub fn load_records_sorted(conn: &Connection, sort_column: &str, direction: SortDirection) -> Result<Vec<Eucarinogammarus>, DbError> {
+    if sort_column != "id" && !COLUMNS.contains(&sort_column) {
+        return Err(DbError::InvalidColumn(sort_column.to_string()));
+    }
+    
     let direction_str = match direction {
         SortDirection::Ascending => "ASC",
         SortDirection::Descending => "DESC",
@@ -149,7 +635,7 @@ pub fn load_records_sorted(conn: &Connection, sort_column: &str, direction: Sort
                 Боковой_ряд, Краевой_ряд, Особен_воор, Эпимир_пласт, Верх_антенны, 
                 Прид_жгутик, Нижн_антенны, Базип_III_V, Уроподы_III, Головн_сегм, 
                 Глаза, Тельсон 
-         FROM Eucarinogammarus ORDER BY {} {}",
+         FROM Eucarinogammarus WHERE deleted_at IS NULL ORDER BY {} {}",
         sort_column
 ,
         direction_str
@@ -157,35 +643,7 @@ pub fn load_records_sorted(conn: &Connection, sort_column: &str, direction: Sort
     
     let mut stmt = conn.prepare(&query)?;
     
-    let records = stmt.query_map([], |row| {
-        Ok(Eucarinogammarus {
-            id: row.get(0)?,
-            code: row.get(1)?,
-            genus: row.get(2)?,
-            species: row.get(3)?,
-            size_mm: row.get(4)?,
-            body: row.get(5)?,
-            coloration: row.get(6)?,
-            distribution: row.get(7)?,
-            depth_m: row.get(8)?,
-            body_armament: row.get(9)?,
-            median_row_i_vii: row.get(10)?,
-            median_row_viii_x: row.get(11)?,
-            median_row_urozom: row.get(12)?,
-            lateral_row: row.get(13)?,
-            marginal_row: row.get(14)?,
-            special_armament: row.get(15)?,
-            epimeral_plate: row.get(16)?,
-            upper_antennae: row.get(17)?,
-            accessory_flagellum: row.get(18)?,
-            lower_antennae: row.get(19)?,
-            basipodite_iii_v: row.get(20)?,
-            uropods_iii: row.get(21)?,
-            head_segment: row.get(22)?,
-            eyes: row.get(23)?,
-            telson: row.get(24)?,
-        })
-    })?;
+    let records = stmt.query_map([], record_from_row)?;
     
     let mut result = Vec::new();
     for record in records {
@@ -0,0 +1,58 @@
+// Пословное сравнение текстов для режима сравнения видов
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffOp {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+// Разница между двумя текстами по словам на основе наибольшей общей подпоследовательности
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffOp> {
+    let a: Vec<&str> = old.split_whitespace().collect();
+    let b: Vec<&str> = new.split_whitespace().collect();
+
+    // lcs[i][j] — длина общей подпоследовательности для a[i..] и b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<DiffOp> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let op = if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+            DiffOp::Same(a[i - 1].to_string())
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            i += 1;
+            DiffOp::Removed(a[i - 1].to_string())
+        } else {
+            j += 1;
+            DiffOp::Added(b[j - 1].to_string())
+        };
+        push_merged(&mut ops, op);
+    }
+
+    ops
+}
+
+// Соседние слова с одной операцией объединяются в одну часть
+fn push_merged(ops: &mut Vec<DiffOp>, op: DiffOp) {
+    match (ops.last_mut(), op) {
+        (Some(DiffOp::Same(last)), DiffOp::Same(word))
+        | (Some(DiffOp::Removed(last)), DiffOp::Removed(word))
+        | (Some(DiffOp::Added(last)), DiffOp::Added(word)) => {
+            last.push(' ');
+            last.push_str(&word);
+        }
+        (_, op) => ops.push(op),
+    }
+}
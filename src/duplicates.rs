@@ -0,0 +1,195 @@
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+use crate::audit;
+use crate::db::{self, Eucarinogammarus, COLUMNS};
+use crate::trash;
+use crate::undo::Command;
+use crate::error::DbError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateKind {
+    Exact,
+    Near,
+}
+
+// Группа записей, вероятно описывающих один вид
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    pub ids: Vec<i32>,
+    pub score: f64,
+}
+
+// Поиск дубликатов: точные совпадения рода, вида и кода, затем
+// похожие записи со сходством не ниже порога (от 0 до 1)
+pub fn find_duplicates(records: &[Eucarinogammarus], threshold: f64) -> Vec<DuplicateGroup> {
+    let mut groups = Vec::new();
+
+    let mut exact: HashMap<(String, String, String), Vec<i32>> = HashMap::new();
+    for record in records {
+        let key = (normalize(&record.genus), normalize(&record.species), normalize(&record.code));
+        exact.entry(key).or_default().push(record.id);
+    }
+    let mut exact: Vec<Vec<i32>> = exact.into_values().filter(|ids| ids.len() > 1).collect();
+    exact.sort();
+    for ids in exact {
+        groups.push(DuplicateGroup { kind: DuplicateKind::Exact, ids, score: 1.0 });
+    }
+
+    // Пары записей выше порога объединяются в группы (система непересекающихся множеств)
+    let texts: Vec<String> = records.iter().map(full_text).collect();
+    let genera: Vec<String> = records.iter().map(|r| normalize(&r.genus)).collect();
+    let epithets: Vec<String> = records.iter().map(|r| normalize(&r.species)).collect();
+    let mut parent: Vec<usize> = (0..records.len()).collect();
+    let mut best: HashMap<usize, f64> = HashMap::new();
+
+    for i in 0..records.len() {
+        for j in (i + 1)..records.len() {
+            if in_same_exact_group(&groups, records[i].id, records[j].id) {
+                continue;
+            }
+            // Эпитеты сравниваются отдельно от рода: общий род сам по себе
+            // не делает разные виды похожими
+            let name = similarity(&genera[i], &genera[j]) * similarity(&epithets[i], &epithets[j]);
+            let score = match word_overlap(&texts[i], &texts[j]) {
+                Some(overlap) => (name + overlap) / 2.0,
+                None => name,
+            };
+            if score >= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[b] = a;
+                let score_b = best.get(&b).copied().unwrap_or(0.0);
+                let entry = best.entry(a).or_insert(0.0);
+                *entry = entry.max(score).max(score_b);
+            }
+        }
+    }
+
+    let mut near: HashMap<usize, Vec<i32>> = HashMap::new();
+    for (i, record) in records.iter().enumerate() {
+        let root = find(&mut parent, i);
+        near.entry(root).or_default().push(record.id);
+    }
+    let mut near: Vec<(usize, Vec<i32>)> = near.into_iter().filter(|(_, ids)| ids.len() > 1).collect();
+    near.sort_by(|a, b| a.1.cmp(&b.1));
+    for (root, ids) in near {
+        let score = best.get(&root).copied().unwrap_or(threshold);
+        groups.push(DuplicateGroup { kind: DuplicateKind::Near, ids, score });
+    }
+
+    groups
+}
+
+// Объединение записей: в оставляемую запись переносятся выбранные значения,
+// остальные записи уходят в корзину, их находки переходят к оставляемой.
+// Все шаги попадают в журнал изменений; отмена возвращает поля и записи,
+// но не переносит находки обратно. Если оставляемую запись тем временем
+// изменил другой пользователь, ничего не меняется: DbError::Conflict
+pub fn merge(
+    conn: &Connection,
+    keep_id: i32,
+    values: &[(String, String)],
+    records: &[&Eucarinogammarus],
+    user_name: &str,
+) -> Result<Command, DbError> {
+    let keeper = records.iter().find(|r| r.id == keep_id).ok_or(DbError::NotFound(keep_id))?;
+    let mut commands = Vec::new();
+
+    let tx = conn.unchecked_transaction()?;
+
+    for (column, value) in values {
+        if !COLUMNS.contains(&column.as_str()) {
+            return Err(DbError::InvalidColumn(column.clone()));
+        }
+        let old = keeper.field(column).unwrap_or("");
+        if old == value {
+            continue;
+        }
+        db::update_field_expecting(&tx, keep_id, column, old, value)?;
+        commands.push(Command::Update {
+            id: keep_id,
+            column: column.clone(),
+            old: old.to_string(),
+            new: value.clone(),
+        });
+    }
+
+    for record in records.iter().filter(|r| r.id != keep_id) {
+        tx.execute(
+            "UPDATE Specimen SET taxon_id = ?1 WHERE taxon_id = ?2",
+            params![keep_id, record.id],
+        )?;
+        trash::soft_delete(&tx, record.id, user_name)?;
+        audit::log_event(&tx, user_name, record.id, "MERGE", &record.id.to_string(), &keep_id.to_string())?;
+        commands.push(Command::Delete {
+            id: record.id,
+            name: record.name(),
+        });
+    }
+
+    tx.commit()?;
+
+    Ok(Command::Bulk {
+        description: format!("объединение {} записей в ID {}", records.len(), keep_id),
+        commands,
+    })
+}
+
+fn in_same_exact_group(groups: &[DuplicateGroup], a: i32, b: i32) -> bool {
+    groups.iter().any(|g| g.kind == DuplicateKind::Exact && g.ids.contains(&a) && g.ids.contains(&b))
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+fn normalize(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn full_text(record: &Eucarinogammarus) -> String {
+    let text: Vec<&str> = COLUMNS.iter()
+        .filter(|c| !matches!(**c, "Код" | "Род" | "Вид"))
+        .filter_map(|c| record.field(c))
+        .collect();
+    normalize(&text.join(" "))
+}
+
+// Сходство строк по расстоянию Левенштейна, от 0 до 1
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+
+    1.0 - prev[b.len()] as f64 / longest as f64
+}
+
+// Доля общих слов в описаниях (коэффициент Жаккара); None, если описания
+// нет хотя бы у одной записи: сравнивать нечего
+fn word_overlap(a: &str, b: &str) -> Option<f64> {
+    let a: std::collections::HashSet<&str> = a.split_whitespace().collect();
+    let b: std::collections::HashSet<&str> = b.split_whitespace().collect();
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    Some(a.intersection(&b).count() as f64 / a.union(&b).count() as f64)
+}
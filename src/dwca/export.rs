@@ -0,0 +1,444 @@
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use csv::Reader;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::db::{Eucarinogammarus, load_records, COLUMNS};
+use crate::error::DbError;
+
+// Пространства имён терминов Darwin Core и Dublin Core
+const DWC_NS: &str = "http://rs.tdwg.org/dwc/terms/";
+const DC_NS: &str = "http://purl.org/dc/terms/";
+
+// Обязательные термины ядра Taxon, без которых GBIF не примет чеклист
+const REQUIRED_TAXON_TERMS: [&str; 3] = ["taxonID", "scientificName", "taxonRank"];
+
+// Тип строки архива: ядро Taxon или одно из расширений
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowType {
+    Taxon,
+    Description,
+    Distribution,
+}
+
+impl RowType {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "taxon" => Some(RowType::Taxon),
+            "description" => Some(RowType::Description),
+            "distribution" => Some(RowType::Distribution),
+            _ => None,
+        }
+    }
+
+    fn uri(&self) -> &'static str {
+        match self {
+            RowType::Taxon => "http://rs.tdwg.org/dwc/terms/Taxon",
+            RowType::Description => "http://rs.gbif.org/terms/1.0/Description",
+            RowType::Distribution => "http://rs.gbif.org/terms/1.0/Distribution",
+        }
+    }
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            RowType::Taxon => "taxon.txt",
+            RowType::Description => "description.txt",
+            RowType::Distribution => "distribution.txt",
+        }
+    }
+}
+
+// Источник значения: столбец таблицы или постоянное значение
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Column(String),
+    Constant(String),
+}
+
+// Одно правило сопоставления. Для расширения Description термин
+// задаёт значение dc:type (название признака), а не имя термина
+#[derive(Debug, Clone)]
+pub struct TermMapping {
+    pub source: Source,
+    pub row_type: RowType,
+    pub term: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DwcMapping {
+    pub entries: Vec<TermMapping>,
+}
+
+impl Default for DwcMapping {
+    fn default() -> Self {
+        let column = |name: &str, row_type: RowType, term: &str| TermMapping {
+            source: Source::Column(name.to_string()),
+            row_type,
+            term: term.to_string(),
+        };
+        let constant = |value: &str, term: &str| TermMapping {
+            source: Source::Constant(value.to_string()),
+            row_type: RowType::Taxon,
+            term: term.to_string(),
+        };
+
+        let mut entries = vec![
+            column("Код", RowType::Taxon, "taxonID"),
+            column("Род", RowType::Taxon, "genus"),
+            column("Вид", RowType::Taxon, "specificEpithet"),
+            constant("species", "taxonRank"),
+            constant("Animalia", "kingdom"),
+            constant("Arthropoda", "phylum"),
+            constant("Malacostraca", "class"),
+            constant("Amphipoda", "order"),
+            column("Распространение", RowType::Distribution, "locality"),
+            column("Глубина_м", RowType::Distribution, "occurrenceRemarks"),
+        ];
+
+        // Все морфологические признаки идут в расширение Description
+        for name in COLUMNS.iter().filter(|c| !matches!(**c, "Код" | "Род" | "Вид" | "Распространение" | "Глубина_м")) {
+            entries.push(column(name, RowType::Description, name));
+        }
+
+        Self { entries }
+    }
+}
+
+impl DwcMapping {
+    // Загрузка сопоставления из CSV-файла со столбцами: столбец, тип строки, термин.
+    // Значение, начинающееся с '=', считается константой (например, "=Animalia")
+    pub fn load(file_path: &str) -> Result<Self, DbError> {
+        let file = File::open(file_path)?;
+        let mut rdr = Reader::from_reader(file);
+        let mut entries = Vec::new();
+
+        for result in rdr.records() {
+            let record = result?;
+            let source = record.get(0).unwrap_or("").trim();
+            let row_type = record.get(1).unwrap_or("");
+            let term = record.get(2).unwrap_or("").trim();
+
+            let row_type = RowType::parse(row_type)
+                .ok_or_else(|| DbError::Parse(format!("Неизвестный тип строки: {}", row_type)))?;
+            let source = match source.strip_prefix('=') {
+                Some(value) => Source::Constant(value.to_string()),
+                None if COLUMNS.contains(&source) => Source::Column(source.to_string()),
+                None => return Err(DbError::InvalidColumn(source.to_string())),
+            };
+
+            entries.push(TermMapping { source, row_type, term: term.to_string() });
+        }
+
+        Ok(Self { entries })
+    }
+
+    fn entries_for(&self, row_type: RowType) -> impl Iterator<Item = &TermMapping> {
+        self.entries.iter().filter(move |e| e.row_type == row_type)
+    }
+
+    // Термины ядра Taxon в порядке столбцов taxon.txt
+    fn taxon_terms(&self) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for entry in self.entries_for(RowType::Taxon) {
+            if !terms.contains(&entry.term) {
+                terms.push(entry.term.clone());
+            }
+        }
+        if !terms.iter().any(|t| t == "scientificName") {
+            terms.insert(1.min(terms.len()), "scientificName".to_string());
+        }
+        terms
+    }
+
+    fn distribution_terms(&self) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for entry in self.entries_for(RowType::Distribution) {
+            if !terms.contains(&entry.term) {
+                terms.push(entry.term.clone());
+            }
+        }
+        terms
+    }
+
+    fn value(&self, record: &Eucarinogammarus, row_type: RowType, term: &str) -> String {
+        let values: Vec<&str> = self.entries_for(row_type)
+            .filter(|e| e.term == term)
+            .filter_map(|e| match &e.source {
+                Source::Column(name) => record.field(name),
+                Source::Constant(value) => Some(value.as_str()),
+            })
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect();
+
+        if values.is_empty() && row_type == RowType::Taxon && term == "scientificName" {
+            let genus = self.value(record, RowType::Taxon, "genus");
+            let epithet = self.value(record, RowType::Taxon, "specificEpithet");
+            return format!("{} {}", genus, epithet).trim().to_string();
+        }
+
+        values.join("; ")
+    }
+}
+
+// Метаданные набора данных для eml.xml
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub title: String,
+    pub creator: String,
+    pub description: String,
+    pub language: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            title: "Eucarinogammarus".to_string(),
+            creator: String::new(),
+            description: String::new(),
+            language: "ru".to_string(),
+        }
+    }
+}
+
+// Нарушение, найденное при проверке записей перед экспортом
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub record_id: i32,
+    pub term: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ID {}: {} — {}", self.record_id, self.term, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub struct ValidationError {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Экспорт отменён: найдено нарушений — {}", self.issues.len())
+    }
+}
+
+impl Error for ValidationError {}
+
+// Итог экспорта
+#[derive(Debug, Clone, Default)]
+pub struct ExportSummary {
+    pub taxa: usize,
+    pub descriptions: usize,
+    pub distributions: usize,
+}
+
+// Проверка обязательных терминов и уникальности taxonID
+pub fn validate(records: &[Eucarinogammarus], mapping: &DwcMapping) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut seen_ids = HashSet::new();
+
+    for record in records {
+        for term in REQUIRED_TAXON_TERMS {
+            if mapping.value(record, RowType::Taxon, term).is_empty() {
+                issues.push(ValidationIssue {
+                    record_id: record.id,
+                    term: term.to_string(),
+                    message: "обязательный термин не заполнен".to_string(),
+                });
+            }
+        }
+
+        let taxon_id = mapping.value(record, RowType::Taxon, "taxonID");
+        if !taxon_id.is_empty() && !seen_ids.insert(taxon_id.clone()) {
+            issues.push(ValidationIssue {
+                record_id: record.id,
+                term: "taxonID".to_string(),
+                message: format!("значение {} встречается повторно", taxon_id),
+            });
+        }
+    }
+
+    issues
+}
+
+// Экспорт таблицы Eucarinogammarus в zip-архив Darwin Core
+pub fn export_dwca(
+    conn: &Connection,
+    file_path: &str,
+    mapping: &DwcMapping,
+    options: &ExportOptions,
+) -> Result<ExportSummary, DbError> {
+    let records = load_records(conn)?;
+
+    let issues = validate(&records, mapping);
+    if !issues.is_empty() {
+        return Err(DbError::ExportRejected(ValidationError { issues }));
+    }
+
+    let taxon_terms = mapping.taxon_terms();
+    let distribution_terms = mapping.distribution_terms();
+    let mut summary = ExportSummary::default();
+
+    // Ядро: одна строка на вид
+    let mut taxon = String::new();
+    push_row(&mut taxon, std::iter::once("id".to_string()).chain(taxon_terms.iter().cloned()));
+    for record in &records {
+        let values = taxon_terms.iter().map(|t| mapping.value(record, RowType::Taxon, t));
+        push_row(&mut taxon, std::iter::once(record.id.to_string()).chain(values));
+        summary.taxa += 1;
+    }
+
+    // Расширение Description: одна строка на заполненный признак
+    let mut description = String::new();
+    push_row(&mut description, ["coreid", "description", "type", "language"].map(String::from));
+    for record in &records {
+        for entry in mapping.entries_for(RowType::Description) {
+            let value = match &entry.source {
+                Source::Column(name) => record.field(name).unwrap_or("").trim().to_string(),
+                Source::Constant(value) => value.clone(),
+            };
+            if value.is_empty() {
+                continue;
+            }
+            push_row(&mut description, [
+                record.id.to_string(),
+                value,
+                entry.term.clone(),
+                options.language.clone(),
+            ]);
+            summary.descriptions += 1;
+        }
+    }
+
+    // Расширение Distribution: одна строка на вид, если есть данные
+    let mut distribution = String::new();
+    push_row(&mut distribution, std::iter::once("coreid".to_string()).chain(distribution_terms.iter().cloned()));
+    for record in &records {
+        let values: Vec<String> = distribution_terms.iter()
+            .map(|t| mapping.value(record, RowType::Distribution, t))
+            .collect();
+        if values.iter().all(|v| v.is_empty()) {
+            continue;
+        }
+        push_row(&mut distribution, std::iter::once(record.id.to_string()).chain(values));
+        summary.distributions += 1;
+    }
+
+    let file = File::create(file_path)?;
+    let mut zip = ZipWriter::new(file);
+    let file_options = FileOptions::default();
+
+    zip.start_file("meta.xml", file_options)?;
+    zip.write_all(meta_xml(&taxon_terms, &distribution_terms).as_bytes())?;
+    zip.start_file("eml.xml", file_options)?;
+    zip.write_all(eml_xml(options).as_bytes())?;
+    zip.start_file(RowType::Taxon.file_name(), file_options)?;
+    zip.write_all(taxon.as_bytes())?;
+    zip.start_file(RowType::Description.file_name(), file_options)?;
+    zip.write_all(description.as_bytes())?;
+    zip.start_file(RowType::Distribution.file_name(), file_options)?;
+    zip.write_all(distribution.as_bytes())?;
+    zip.finish()?;
+
+    Ok(summary)
+}
+
+// Строка файла с разделителем табуляции; табуляции и переводы строк в значениях заменяются пробелами
+fn push_row<I: IntoIterator<Item = String>>(out: &mut String, values: I) {
+    let line: Vec<String> = values.into_iter()
+        .map(|v| v.replace(['\t', '\r', '\n'], " "))
+        .collect();
+    out.push_str(&line.join("\t"));
+    out.push('\n');
+}
+
+fn term_uri(term: &str) -> String {
+    if term.starts_with("http://") || term.starts_with("https://") {
+        term.to_string()
+    } else {
+        format!("{}{}", DWC_NS, term)
+    }
+}
+
+fn file_header(row_type: RowType, kind: &str) -> String {
+    format!(
+        "  <{} encoding=\"UTF-8\" fieldsTerminatedBy=\"\\t\" linesTerminatedBy=\"\\n\" fieldsEnclosedBy=\"\" ignoreHeaderLines=\"1\" rowType=\"{}\">\n    <files><location>{}</location></files>\n",
+        kind,
+        row_type.uri(),
+        row_type.file_name()
+    )
+}
+
+fn meta_xml(taxon_terms: &[String], distribution_terms: &[String]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<archive xmlns=\"http://rs.tdwg.org/dwc/text/\" metadata=\"eml.xml\">\n");
+
+    xml.push_str(&file_header(RowType::Taxon, "core"));
+    xml.push_str("    <id index=\"0\"/>\n");
+    for (i, term) in taxon_terms.iter().enumerate() {
+        xml.push_str(&format!("    <field index=\"{}\" term=\"{}\"/>\n", i + 1, escape_xml(&term_uri(term))));
+    }
+    xml.push_str("  </core>\n");
+
+    xml.push_str(&file_header(RowType::Description, "extension"));
+    xml.push_str("    <coreid index=\"0\"/>\n");
+    xml.push_str(&format!("    <field index=\"1\" term=\"{}description\"/>\n", DC_NS));
+    xml.push_str(&format!("    <field index=\"2\" term=\"{}type\"/>\n", DC_NS));
+    xml.push_str(&format!("    <field index=\"3\" term=\"{}language\"/>\n", DC_NS));
+    xml.push_str("  </extension>\n");
+
+    xml.push_str(&file_header(RowType::Distribution, "extension"));
+    xml.push_str("    <coreid index=\"0\"/>\n");
+    for (i, term) in distribution_terms.iter().enumerate() {
+        xml.push_str(&format!("    <field index=\"{}\" term=\"{}\"/>\n", i + 1, escape_xml(&term_uri(term))));
+    }
+    xml.push_str("  </extension>\n");
+
+    xml.push_str("</archive>\n");
+    xml
+}
+
+fn eml_xml(options: &ExportOptions) -> String {
+    let creator = if options.creator.trim().is_empty() {
+        String::new()
+    } else {
+        format!(
+            "    <creator><individualName><surName>{0}</surName></individualName></creator>\n    <contact><individualName><surName>{0}</surName></individualName></contact>\n",
+            escape_xml(options.creator.trim())
+        )
+    };
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <eml:eml xmlns:eml=\"eml://ecoinformatics.org/eml-2.1.1\" packageId=\"eucarinogammarus\" system=\"http://gbif.org\" scope=\"system\" xml:lang=\"{lang}\">\n\
+         \x20 <dataset>\n\
+         \x20   <title>{title}</title>\n\
+         {creator}\
+         \x20   <language>{lang}</language>\n\
+         \x20   <abstract><para>{description}</para></abstract>\n\
+         \x20 </dataset>\n\
+         </eml:eml>\n",
+        lang = escape_xml(&options.language),
+        title = escape_xml(&options.title),
+        creator = creator,
+        description = escape_xml(&options.description),
+    )
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
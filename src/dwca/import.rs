@@ -0,0 +1,454 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use csv::ReaderBuilder;
+use zip::ZipArchive;
+
+use crate::db::{Eucarinogammarus, insert_taxon};
+use crate::error::DbError;
+
+// Термины, которые используются при импорте; остальные столбцы отбрасываются
+const KNOWN_TERMS: [&str; 20] = [
+    "occurrenceID", "catalogNumber", "institutionCode", "basisOfRecord", "recordedBy",
+    "eventDate", "individualCount", "locality", "countryCode", "waterBody",
+    "decimalLatitude", "decimalLongitude", "minimumDepthInMeters", "maximumDepthInMeters",
+    "scientificName", "genus", "specificEpithet", "taxonRank", "taxonID", "datasetName",
+];
+
+// Вид ядра архива: находки (GBIF download) или чеклист
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoreKind {
+    Occurrence,
+    Taxon,
+}
+
+// Прочитанная таблица ядра; значения строк проиндексированы коротким именем термина
+#[derive(Debug, Clone)]
+pub struct DwcTable {
+    pub kind: CoreKind,
+    pub source: String,
+    pub rows: Vec<HashMap<String, String>>,
+}
+
+// Решение пользователя для группы строк с одним названием
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    Assign(i32),
+    CreateTaxon,
+    Skip,
+}
+
+// Группа строк с одинаковыми родом и видом и найденные для них записи
+#[derive(Debug, Clone)]
+pub struct NameGroup {
+    pub genus: String,
+    pub species: String,
+    pub candidates: Vec<i32>,
+    pub rows: Vec<usize>,
+    pub decision: Decision,
+}
+
+impl NameGroup {
+    pub fn is_ambiguous(&self) -> bool {
+        self.candidates.len() > 1
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.candidates.is_empty()
+    }
+}
+
+// Импорт, ожидающий просмотра пользователем
+#[derive(Debug, Clone)]
+pub struct PendingImport {
+    pub table: DwcTable,
+    pub groups: Vec<NameGroup>,
+    // Строки без рода: сопоставить их не с чем, они не импортируются
+    pub unnamed: usize,
+}
+
+// Итог применения импорта
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub taxa_created: usize,
+    pub taxon_ids: Vec<i32>,
+    pub specimens_created: usize,
+    pub localities_created: usize,
+    pub duplicates_skipped: usize,
+    pub rows_skipped: usize,
+    pub rows_unnamed: usize,
+}
+
+// Чтение текстового файла Darwin Core или zip-архива DwC-A
+pub fn read_dwc(file_path: &str) -> Result<DwcTable, DbError> {
+    if file_path.to_lowercase().ends_with(".zip") {
+        read_archive(file_path)
+    } else {
+        let mut text = String::new();
+        BufReader::new(File::open(file_path)?).read_to_string(&mut text)?;
+        let delimiter = guess_delimiter(&text);
+        read_text(&text, &CoreDescriptor::from_header(delimiter), file_path)
+    }
+}
+
+// Описание файла ядра из meta.xml
+#[derive(Debug, Clone)]
+struct CoreDescriptor {
+    location: Option<String>,
+    kind: Option<CoreKind>,
+    delimiter: u8,
+    quote: Option<u8>,
+    header_lines: usize,
+    fields: Vec<(usize, String)>,
+    defaults: Vec<(String, String)>,
+}
+
+impl CoreDescriptor {
+    fn from_header(delimiter: u8) -> Self {
+        Self {
+            location: None,
+            kind: None,
+            delimiter,
+            quote: Some(b'"'),
+            header_lines: 1,
+            fields: Vec::new(),
+            defaults: Vec::new(),
+        }
+    }
+
+    // Разбор элемента <core> из meta.xml. Полноценный XML-парсер не нужен:
+    // файл генерируется по стандарту и содержит только атрибуты и пустые элементы
+    fn from_meta(meta: &str) -> Option<Self> {
+        let start = meta.find("<core")?;
+        let end = meta[start..].find("</core>").map(|e| start + e)?;
+        let core = &meta[start..end];
+        let open_tag = &core[..core.find('>')?];
+
+        let delimiter = match attribute(open_tag, "fieldsTerminatedBy").as_deref() {
+            Some("\\t") | None => b'\t',
+            Some(",") => b',',
+            Some(";") => b';',
+            Some(other) => other.bytes().next().unwrap_or(b'\t'),
+        };
+        // По стандарту DwC-A без атрибута поля заключаются в кавычки;
+        // пустое значение отключает их
+        let quote = match attribute(open_tag, "fieldsEnclosedBy") {
+            Some(q) => unescape_xml(&q).bytes().next(),
+            None => Some(b'"'),
+        };
+        let header_lines = attribute(open_tag, "ignoreHeaderLines")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let kind = attribute(open_tag, "rowType").map(|t| {
+            if short_term(&t) == "Taxon" { CoreKind::Taxon } else { CoreKind::Occurrence }
+        });
+
+        let location = core.find("<location>").and_then(|s| {
+            let rest = &core[s + "<location>".len()..];
+            rest.find("</location>").map(|e| unescape_xml(rest[..e].trim()))
+        });
+
+        let mut fields = Vec::new();
+        let mut defaults = Vec::new();
+        for tag in core.split("<field").skip(1) {
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            let term = match attribute(tag, "term") {
+                Some(term) => short_term(&term),
+                None => continue,
+            };
+            match attribute(tag, "index").and_then(|i| i.parse().ok()) {
+                Some(index) => fields.push((index, term)),
+                None => {
+                    if let Some(value) = attribute(tag, "default") {
+                        defaults.push((term, unescape_xml(&value)));
+                    }
+                }
+            }
+        }
+
+        Some(Self { location, kind, delimiter, quote, header_lines, fields, defaults })
+    }
+}
+
+fn read_archive(file_path: &str) -> Result<DwcTable, DbError> {
+    let mut archive = ZipArchive::new(File::open(file_path)?)?;
+
+    let descriptor = match archive.by_name("meta.xml") {
+        Ok(mut meta) => {
+            let mut text = String::new();
+            meta.read_to_string(&mut text)?;
+            CoreDescriptor::from_meta(&text).ok_or_else(|| DbError::Parse("в meta.xml не найден элемент core".to_string()))?
+        }
+        Err(_) => CoreDescriptor::from_header(b'\t'),
+    };
+
+    // Без meta.xml берём первый подходящий файл данных
+    let location = match &descriptor.location {
+        Some(location) => location.clone(),
+        None => ["occurrence.txt", "taxon.txt"].iter()
+            .map(|s| s.to_string())
+            .chain(archive.file_names().map(String::from))
+            .find(|name| {
+                let lower = name.to_lowercase();
+                (lower.ends_with(".txt") || lower.ends_with(".csv")) && archive.file_names().any(|n| n == name)
+            })
+            .ok_or_else(|| DbError::Parse("в архиве нет файлов данных".to_string()))?,
+    };
+
+    let mut text = String::new();
+    archive.by_name(&location)?.read_to_string(&mut text)?;
+    read_text(&text, &descriptor, &location)
+}
+
+fn read_text(text: &str, descriptor: &CoreDescriptor, source: &str) -> Result<DwcTable, DbError> {
+    let mut builder = ReaderBuilder::new();
+    builder
+        .delimiter(descriptor.delimiter)
+        .has_headers(false)
+        .flexible(true);
+    match descriptor.quote {
+        Some(quote) => builder.quote(quote),
+        None => builder.quoting(false),
+    };
+    let mut rdr = builder.from_reader(text.as_bytes());
+    let mut records = rdr.records();
+
+    // Столбцы берутся из meta.xml, иначе из строки заголовка
+    let mut fields = descriptor.fields.clone();
+    for i in 0..descriptor.header_lines {
+        let header = match records.next() {
+            Some(header) => header?,
+            None => break,
+        };
+        if i == 0 && fields.is_empty() {
+            fields = header.iter().enumerate().map(|(index, name)| (index, short_term(name))).collect();
+        }
+    }
+    fields.retain(|(_, term)| KNOWN_TERMS.contains(&term.as_str()));
+
+    let mut rows = Vec::new();
+    for result in records {
+        let record = result?;
+        let mut row: HashMap<String, String> = descriptor.defaults.iter().cloned().collect();
+        for (index, term) in &fields {
+            let value = record.get(*index).unwrap_or("").trim();
+            if !value.is_empty() {
+                row.insert(term.clone(), value.to_string());
+            }
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+    }
+
+    // Чеклист узнаётся по rowType или по отсутствию признаков находки
+    let kind = descriptor.kind.unwrap_or_else(|| {
+        let is_occurrence = fields.iter().any(|(_, t)| t == "occurrenceID" || t == "catalogNumber" || t == "eventDate");
+        if is_occurrence { CoreKind::Occurrence } else { CoreKind::Taxon }
+    });
+
+    Ok(DwcTable { kind, source: source.to_string(), rows })
+}
+
+// Сопоставление строк с записями Eucarinogammarus по роду и виду
+pub fn match_taxa(table: DwcTable, records: &[Eucarinogammarus]) -> PendingImport {
+    let mut groups: Vec<NameGroup> = Vec::new();
+    let mut unnamed = 0;
+
+    for (index, row) in table.rows.iter().enumerate() {
+        let (genus, species) = row_name(row);
+        if genus.is_empty() {
+            unnamed += 1;
+            continue;
+        }
+
+        if let Some(group) = groups.iter_mut()
+            .find(|g| g.genus.to_lowercase() == genus.to_lowercase() && g.species.to_lowercase() == species.to_lowercase())
+        {
+            group.rows.push(index);
+            continue;
+        }
+
+        let candidates: Vec<i32> = records.iter()
+            .filter(|r| r.genus.trim().to_lowercase() == genus.to_lowercase()
+                && r.species.trim().to_lowercase() == species.to_lowercase())
+            .map(|r| r.id)
+            .collect();
+
+        // Однозначное совпадение принимается сразу, неоднозначное требует выбора
+        let decision = match candidates.as_slice() {
+            [id] => Decision::Assign(*id),
+            [] => Decision::CreateTaxon,
+            _ => Decision::Skip,
+        };
+
+        groups.push(NameGroup { genus, species, candidates, rows: vec![index], decision });
+    }
+
+    PendingImport { table, groups, unnamed }
+}
+
+// Применение импорта в одной транзакции. Описания видов не изменяются:
+// создаются только новые виды, находки и местонахождения
+pub fn apply_import(conn: &mut Connection, pending: &PendingImport) -> Result<ImportSummary, DbError> {
+    let tx = conn.transaction()?;
+    let mut summary = ImportSummary { rows_unnamed: pending.unnamed, ..Default::default() };
+
+    for group in &pending.groups {
+        let taxon_id = match group.decision {
+            Decision::Skip => {
+                summary.rows_skipped += group.rows.len();
+                continue;
+            }
+            Decision::Assign(id) => id as i64,
+            Decision::CreateTaxon => {
+                let id = insert_taxon(&tx, &group.genus, &group.species)?;
+                summary.taxa_created += 1;
+                summary.taxon_ids.push(id as i32);
+                id
+            }
+        };
+
+        if pending.table.kind == CoreKind::Taxon {
+            continue;
+        }
+
+        for &index in &group.rows {
+            let row = &pending.table.rows[index];
+            let get = |term: &str| row.get(term).map(String::as_str).unwrap_or("");
+
+            // Повторный импорт той же находки пропускается
+            let occurrence_id = get("occurrenceID");
+            if !occurrence_id.is_empty() {
+                let exists: Option<i64> = tx.query_row(
+                    "SELECT id FROM Specimen WHERE occurrence_id = ?1",
+                    params![occurrence_id],
+                    |r| r.get(0),
+                ).optional()?;
+                if exists.is_some() {
+                    summary.duplicates_skipped += 1;
+                    continue;
+                }
+            }
+
+            let locality_id = find_or_create_locality(&tx, row, &mut summary)?;
+
+            tx.execute(
+                "INSERT INTO Specimen (
+                    taxon_id, locality_id, occurrence_id, catalog_number, institution_code,
+                    basis_of_record, recorded_by, event_date, individual_count, source
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    taxon_id,
+                    locality_id,
+                    occurrence_id,
+                    get("catalogNumber"),
+                    get("institutionCode"),
+                    get("basisOfRecord"),
+                    get("recordedBy"),
+                    get("eventDate"),
+                    get("individualCount"),
+                    if get("datasetName").is_empty() { pending.table.source.as_str() } else { get("datasetName") },
+                ],
+            )?;
+            summary.specimens_created += 1;
+        }
+    }
+
+    tx.commit()?;
+    Ok(summary)
+}
+
+fn find_or_create_locality(
+    conn: &Connection,
+    row: &HashMap<String, String>,
+    summary: &mut ImportSummary,
+) -> Result<Option<i64>, DbError> {
+    let get = |term: &str| row.get(term).map(String::as_str).unwrap_or("");
+    let values = [
+        get("locality"),
+        get("countryCode"),
+        get("waterBody"),
+        get("decimalLatitude"),
+        get("decimalLongitude"),
+        get("minimumDepthInMeters"),
+        get("maximumDepthInMeters"),
+    ];
+
+    if values.iter().all(|v| v.is_empty()) {
+        return Ok(None);
+    }
+
+    let existing: Option<i64> = conn.query_row(
+        "SELECT id FROM Locality WHERE locality = ?1 AND country_code = ?2 AND water_body = ?3
+            AND decimal_latitude = ?4 AND decimal_longitude = ?5 AND min_depth_m = ?6 AND max_depth_m = ?7",
+        params![values[0], values[1], values[2], values[3], values[4], values[5], values[6]],
+        |r| r.get(0),
+    ).optional()?;
+
+    if existing.is_some() {
+        return Ok(existing);
+    }
+
+    conn.execute(
+        "INSERT INTO Locality (
+            locality, country_code, water_body, decimal_latitude, decimal_longitude, min_depth_m, max_depth_m
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![values[0], values[1], values[2], values[3], values[4], values[5], values[6]],
+    )?;
+    summary.localities_created += 1;
+    Ok(Some(conn.last_insert_rowid()))
+}
+
+// Род и видовой эпитет строки; при их отсутствии берутся из scientificName
+fn row_name(row: &HashMap<String, String>) -> (String, String) {
+    let genus = row.get("genus").cloned().unwrap_or_default();
+    let species = row.get("specificEpithet").cloned().unwrap_or_default();
+    if !genus.is_empty() && !species.is_empty() {
+        return (genus, species);
+    }
+
+    let name = row.get("scientificName").cloned().unwrap_or_default();
+    let mut words = name.split_whitespace();
+    let parsed_genus = words.next().unwrap_or("").to_string();
+    let parsed_species = words.next()
+        .filter(|w| w.chars().next().is_some_and(|c| c.is_lowercase()))
+        .unwrap_or("")
+        .to_string();
+
+    (
+        if genus.is_empty() { parsed_genus } else { genus },
+        if species.is_empty() { parsed_species } else { species },
+    )
+}
+
+fn guess_delimiter(text: &str) -> u8 {
+    let header = text.lines().next().unwrap_or("");
+    if header.contains('\t') { b'\t' } else { b',' }
+}
+
+// Короткое имя термина: "http://rs.tdwg.org/dwc/terms/genus" -> "genus"
+fn short_term(term: &str) -> String {
+    term.trim()
+        .rsplit(['/', '#', ':'])
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')?;
+    Some(tag[start..start + end].to_string())
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
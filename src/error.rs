@@ -0,0 +1,191 @@
+use rusqlite::ErrorCode;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::crypto;
+use crate::dwca::export::ValidationError;
+
+// Ошибка слоя данных. Сообщения рассчитаны на пользователя и показываются как есть
+#[derive(Debug)]
+pub enum DbError {
+    // Нет неудалённой записи с таким ID
+    NotFound(i32),
+    // Запись изменена в базе после того, как её прочитали для правки
+    Conflict(i32),
+    // Имя столбца не из COLUMNS
+    InvalidColumn(String),
+    // Недопустимые входные данные или действие
+    Invalid(String),
+    // Нарушено ограничение схемы (UNIQUE, NOT NULL и т.п.)
+    Constraint(String),
+    // Файл базы занят другой программой (идёт запись или открыта транзакция)
+    Locked(Option<PathBuf>),
+    // Файл существует, но это не база SQLite или он повреждён
+    NotADatabase(PathBuf),
+    // База зашифрована, а пароль не указан или неверен
+    Encrypted(PathBuf),
+    // Файл нельзя открыть или создать: нет каталога или прав доступа
+    CannotOpen(PathBuf),
+    // Нет CSV для начального заполнения новой базы
+    SeedMissing(PathBuf),
+    // Записи не прошли проверку перед экспортом Darwin Core
+    ExportRejected(ValidationError),
+    Io(io::Error),
+    // Файл не удалось разобрать: CSV, Darwin Core, настройки
+    Parse(String),
+    Sqlite(rusqlite::Error),
+}
+
+impl DbError {
+    // Уточнение ошибки SQLite, возникшей при открытии файла path
+    pub fn at(self, path: &Path) -> Self {
+        let code = match &self {
+            DbError::Locked(None) => return DbError::Locked(Some(path.to_path_buf())),
+            DbError::Sqlite(rusqlite::Error::SqliteFailure(e, _)) => e.code,
+            _ => return self,
+        };
+        match code {
+            ErrorCode::NotADatabase if crypto::is_encrypted(path) => DbError::Encrypted(path.to_path_buf()),
+            ErrorCode::NotADatabase | ErrorCode::DatabaseCorrupt => DbError::NotADatabase(path.to_path_buf()),
+            ErrorCode::CannotOpen | ErrorCode::PermissionDenied | ErrorCode::ReadOnly => {
+                DbError::CannotOpen(path.to_path_buf())
+            }
+            _ => self,
+        }
+    }
+
+    // Краткое название вида ошибки для журнала ошибок
+    pub fn title(&self) -> &'static str {
+        match self {
+            DbError::NotFound(_) => "Запись не найдена",
+            DbError::Conflict(_) => "Конфликт изменений",
+            DbError::InvalidColumn(_) | DbError::Invalid(_) => "Неверные данные",
+            DbError::Constraint(_) => "Нарушено ограничение",
+            DbError::Locked(_) => "База занята",
+            DbError::NotADatabase(_) | DbError::CannotOpen(_) | DbError::SeedMissing(_) => "Файл базы",
+            DbError::Encrypted(_) => "Зашифрованная база",
+            DbError::ExportRejected(_) => "Проверка перед экспортом",
+            DbError::Io(_) => "Ввод-вывод",
+            DbError::Parse(_) => "Формат файла",
+            DbError::Sqlite(_) => "База данных",
+        }
+    }
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::NotFound(id) => write!(f, "Запись с ID {} не найдена", id),
+            DbError::Conflict(id) => {
+                write!(f, "Запись с ID {} изменена другим пользователем после того, как вы её открыли", id)
+            }
+            DbError::InvalidColumn(column) => write!(f, "Неверное имя столбца: {}", column),
+            DbError::Invalid(message) => write!(f, "{}", message),
+            DbError::Constraint(message) => write!(f, "Изменение нарушает ограничение базы: {}", message),
+            DbError::Locked(Some(path)) => write!(f, "База {} занята другой программой", path.display()),
+            DbError::Locked(None) => write!(f, "База занята другой программой; повторите действие позже"),
+            DbError::NotADatabase(path) => {
+                write!(f, "Файл {} не является базой данных SQLite или повреждён", path.display())
+            }
+            DbError::Encrypted(path) if crypto::ENABLED => {
+                write!(f, "База {} зашифрована: нужен верный пароль", path.display())
+            }
+            DbError::Encrypted(path) => {
+                write!(f, "База {} зашифрована, а программа собрана без поддержки шифрования", path.display())
+            }
+            DbError::CannotOpen(path) => {
+                write!(f, "Не удаётся открыть файл {}: нет каталога или прав доступа", path.display())
+            }
+            DbError::SeedMissing(path) => {
+                write!(f, "Не найден файл {} для начального заполнения новой базы", path.display())
+            }
+            DbError::ExportRejected(e) => write!(f, "{}", e),
+            DbError::Io(e) => match e.kind() {
+                io::ErrorKind::NotFound => write!(f, "Файл не найден"),
+                io::ErrorKind::PermissionDenied => write!(f, "Нет прав доступа к файлу"),
+                _ => write!(f, "Ошибка чтения или записи файла: {}", e),
+            },
+            DbError::Parse(message) => write!(f, "Не удалось разобрать файл: {}", message),
+            DbError::Sqlite(e) => write!(f, "Ошибка базы данных: {}", e),
+        }
+    }
+}
+
+impl Error for DbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DbError::ExportRejected(e) => Some(e),
+            DbError::Io(e) => Some(e),
+            DbError::Sqlite(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DbError {
+    fn from(e: io::Error) -> Self {
+        DbError::Io(e)
+    }
+}
+
+impl From<csv::Error> for DbError {
+    fn from(e: csv::Error) -> Self {
+        let message = format!("CSV: {}", e);
+        match e.into_kind() {
+            csv::ErrorKind::Io(e) => DbError::Io(e),
+            _ => DbError::Parse(message),
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for DbError {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(e) => DbError::Io(e),
+            e => DbError::Parse(format!("zip: {}", e)),
+        }
+    }
+}
+
+impl From<toml::de::Error> for DbError {
+    fn from(e: toml::de::Error) -> Self {
+        DbError::Parse(e.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for DbError {
+    fn from(e: toml::ser::Error) -> Self {
+        DbError::Invalid(e.to_string())
+    }
+}
+
+impl From<regex::Error> for DbError {
+    fn from(e: regex::Error) -> Self {
+        DbError::Invalid(format!("Неверное регулярное выражение: {}", e))
+    }
+}
+
+impl From<ValidationError> for DbError {
+    fn from(e: ValidationError) -> Self {
+        DbError::ExportRejected(e)
+    }
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        let code = match &e {
+            rusqlite::Error::SqliteFailure(failure, _) => failure.code,
+            _ => return DbError::Sqlite(e),
+        };
+        match code {
+            ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => DbError::Locked(None),
+            ErrorCode::ConstraintViolation => match &e {
+                rusqlite::Error::SqliteFailure(_, Some(message)) => DbError::Constraint(message.clone()),
+                _ => DbError::Constraint(e.to_string()),
+            },
+            _ => DbError::Sqlite(e),
+        }
+    }
+}
@@ -1,99 +1,31 @@
 use native_windows_gui as nwg;
 use native_windows_derive as nwd;
 use nwd::NwgUi;
-use nwg::{NativeUi, ControlHandle};
-use rusqlite::{params, Connection, Result};
+use nwg::NativeUi;
 use std::error::Error;
 use std::cell::RefCell;
 use std::rc::Rc;
-use crate::db::{Eucarinogammarus, import_csv, load_records, load_records_sorted};
+use std::path::PathBuf;
+use eucarinogammarus_db::{Database, DbError, Eucarinogammarus, SortDirection, COLUMNS};
+use eucarinogammarus_db::config::{self, Config};
 
-pub fn run_gui_app() -> Result<(), Box<dyn Error>> {
+// db_path — файл из аргумента --db; без него открывается последняя база из настроек
+pub fn run_gui_app(db_path: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
     // Инициализация библиотеки native-windows-gui
-    nwg::init().expect("Не удалось инициализировать GUI");
-    
-    // Создание соединения с базой данных
-    let conn = Connection::open("eucarinogammarus.db")?;
-    
-    // Создание таблицы, если она не существует
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS Eucarinogammarus (
-            id INTEGER PRIMARY KEY,
-            Код TEXT,
-            Род TEXT,
-            Вид TEXT,
-            Размеры_мм TEXT,
-            Тело TEXT,
-            Окраска TEXT,
-            Распространение TEXT,
-            Глубина_м TEXT,
-            Вооруж_тела TEXT,
-            Средний_ряд_I_VII TEXT,
-            Средн_ряд_VIII_X TEXT,
-            Сред_ряд_урозом TEXT,
-            Боковой_ряд TEXT,
-            Краевой_ряд TEXT,
-            Особен_воор TEXT,
-            Эпимир_пласт TEXT,
-            Верх_антенны TEXT,
-            Прид_жгутик TEXT,
-            Нижн_антенны TEXT,
-            Базип_III_V TEXT,
-            Уроподы_III TEXT,
-            Головн_сегм TEXT,
-            Глаза TEXT,
-            Тельсон TEXT
-        )",
-        [],
-    )?;
-    
-    // Импорт данных из CSV, если таблица пуста
-    let mut stmt = conn.prepare("SELECT COUNT(*) FROM Eucarinogammarus")?;
-    let count: i64 = stmt.query_row([], |row| row.get(0))?;
-    
-    if count == 0 {
-        import_csv(&conn, "Eucarinogammarus.csv")?;
-    }
+    nwg::init()?;
+    
+    // Подключение к базе данных: схема, журнал изменений и начальный импорт
+    let mut settings = Config::load()?;
+    let path = settings.database_path(db_path.as_deref());
+    let db = Database::open_seeded(&path, config::seed_csv_path(&path))?;
+    settings.remember(&path);
+    settings.save()?;
     
     // Загрузка записей
-    let records = load_records(&conn)?;
+    let records = db.records()?;
     
     // Создание и запуск приложения
-    let app = EucarinogammarusApp {
-        conn: Rc::new(RefCell::new(conn)),
-        records: Rc::new(RefCell::new(records)),
-        window: Default::default(),
-        tabs: Default::default(),
-        
-        // Вкладка просмотра
-        search_input: Default::default(),
-        sort_button: Default::default(),
-        refresh_button: Default::default(),
-        records_list: Default::default(),
-        
-        // Вкладка добавления
-        code_input: Default::default(),
-        genus_input: Default::default(),
-        species_input: Default::default(),
-        size_mm_input: Default::default(),
-        body_input: Default::default(),
-        add_button: Default::default(),
-        
-        // Вкладка редактирования
-        edit_id_input: Default::default(),
-        edit_column_input: Default::default(),
-        edit_value_input: Default::default(),
-        edit_button: Default::default(),
-        
-        // Вкладка удаления
-        delete_id_input: Default::default(),
-        delete_button: Default::default(),
-        
-        // Статусная строка
-        status_label: Default::default(),
-    };
-    
-    let _app = EucarinogammarusApp::build_ui(app).expect("Не удалось построить UI");
+    let _app = EucarinogammarusApp::build_ui(EucarinogammarusApp::new(db, records))?;
     
     // Запуск цикла обработки сообщений
     nwg::dispatch_thread_events();
@@ -101,112 +33,123 @@ pub fn run_gui_app() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-#[derive(Default, NwgUi)]
+#[derive(NwgUi)]
 pub struct EucarinogammarusApp {
-    // Данные
-    #[nwg_control(skip)]
-    conn: Rc<RefCell<Connection>>,
+    // Данные; поля без атрибутов nwg_control derive не трогает
+    db: Rc<RefCell<Database>>,
     
-    #[nwg_control(skip)]
     records: Rc<RefCell<Vec<Eucarinogammarus>>>,
     
     // Главное окно
     #[nwg_control(size: (800, 600), position: (300, 300), title: "База данных Eucarinogammarus")]
+    #[nwg_events(OnInit: [EucarinogammarusApp::init], OnWindowClose: [nwg::stop_thread_dispatch()])]
     window: nwg::Window,
     
     // Вкладки
     #[nwg_control(parent: window, position: (0, 0), size: (800, 550))]
-    #[nwg_events(OnNotice: [EucarinogammarusApp::on_tab_change])]
+    #[nwg_events(TabsContainerChanged: [EucarinogammarusApp::on_tab_change])]
     tabs: nwg::TabsContainer,
     
     // Вкладка просмотра
-    #[nwg_control(parent: tabs, text: "Поиск:", position: (10, 10), size: (50, 25))]
+    #[nwg_control(parent: tabs, text: "Просмотр")]
+    view_tab: nwg::Tab,
+    
+    #[nwg_control(parent: view_tab, text: "Поиск:", position: (10, 10), size: (50, 25))]
     search_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (70, 10), size: (200, 25))]
+    #[nwg_control(parent: view_tab, position: (70, 10), size: (200, 25))]
     search_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Сортировать по:", position: (280, 10), size: (100, 25))]
+    #[nwg_control(parent: view_tab, text: "Сортировать по:", position: (280, 10), size: (100, 25))]
     sort_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, text: "ID", position: (390, 10), size: (100, 25))]
+    #[nwg_control(parent: view_tab, text: "ID", position: (390, 10), size: (100, 25))]
     #[nwg_events(OnButtonClick: [EucarinogammarusApp::on_sort_click])]
     sort_button: nwg::Button,
     
-    #[nwg_control(parent: tabs, text: "Обновить", position: (500, 10), size: (100, 25))]
+    #[nwg_control(parent: view_tab, text: "Обновить", position: (500, 10), size: (100, 25))]
     #[nwg_events(OnButtonClick: [EucarinogammarusApp::on_refresh_click])]
     refresh_button: nwg::Button,
     
-    #[nwg_control(parent: tabs, position: (10, 50), size: (780, 450), list_style: nwg::ListViewStyle::Detailed)]
+    #[nwg_control(parent: view_tab, position: (10, 50), size: (780, 450), list_style: nwg::ListViewStyle::Detailed)]
     #[nwg_events(OnListViewClick: [EucarinogammarusApp::on_record_select])]
     records_list: nwg::ListView,
     
     // Вкладка добавления
-    #[nwg_control(parent: tabs, text: "Код:", position: (10, 10), size: (100, 25))]
+    #[nwg_control(parent: tabs, text: "Добавить")]
+    add_tab: nwg::Tab,
+    
+    #[nwg_control(parent: add_tab, text: "Код:", position: (10, 10), size: (100, 25))]
     code_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 10), size: (200, 25))]
+    #[nwg_control(parent: add_tab, position: (120, 10), size: (200, 25))]
     code_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Род:", position: (10, 45), size: (100, 25))]
+    #[nwg_control(parent: add_tab, text: "Род:", position: (10, 45), size: (100, 25))]
     genus_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 45), size: (200, 25))]
+    #[nwg_control(parent: add_tab, position: (120, 45), size: (200, 25))]
     genus_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Вид:", position: (10, 80), size: (100, 25))]
+    #[nwg_control(parent: add_tab, text: "Вид:", position: (10, 80), size: (100, 25))]
     species_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 80), size: (200, 25))]
+    #[nwg_control(parent: add_tab, position: (120, 80), size: (200, 25))]
     species_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Размеры мм:", position: (10, 115), size: (100, 25))]
+    #[nwg_control(parent: add_tab, text: "Размеры мм:", position: (10, 115), size: (100, 25))]
     size_mm_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 115), size: (200, 25))]
+    #[nwg_control(parent: add_tab, position: (120, 115), size: (200, 25))]
     size_mm_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Тело:", position: (10, 150), size: (100, 25))]
+    #[nwg_control(parent: add_tab, text: "Тело:", position: (10, 150), size: (100, 25))]
     body_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 150), size: (200, 25))]
+    #[nwg_control(parent: add_tab, position: (120, 150), size: (200, 25))]
     body_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Добавить запись", position: (120, 200), size: (150, 30))]
+    #[nwg_control(parent: add_tab, text: "Добавить запись", position: (120, 200), size: (150, 30))]
     #[nwg_events(OnButtonClick: [EucarinogammarusApp::on_add_click])]
     add_button: nwg::Button,
     
     // Вкладка редактирования
-    #[nwg_control(parent: tabs, text: "ID записи:", position: (10, 10), size: (100, 25))]
+    #[nwg_control(parent: tabs, text: "Редактировать")]
+    edit_tab: nwg::Tab,
+    
+    #[nwg_control(parent: edit_tab, text: "ID записи:", position: (10, 10), size: (100, 25))]
     edit_id_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 10), size: (200, 25))]
+    #[nwg_control(parent: edit_tab, position: (120, 10), size: (200, 25))]
     edit_id_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Столбец:", position: (10, 45), size: (100, 25))]
+    #[nwg_control(parent: edit_tab, text: "Столбец:", position: (10, 45), size: (100, 25))]
     edit_column_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 45), size: (200, 25))]
+    #[nwg_control(parent: edit_tab, position: (120, 45), size: (200, 25))]
     edit_column_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Новое значение:", position: (10, 80), size: (100, 25))]
+    #[nwg_control(parent: edit_tab, text: "Новое значение:", position: (10, 80), size: (100, 25))]
     edit_value_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (120, 80), size: (200, 25))]
+    #[nwg_control(parent: edit_tab, position: (120, 80), size: (200, 25))]
     edit_value_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Обновить запись", position: (120, 130), size: (150, 30))]
+    #[nwg_control(parent: edit_tab, text: "Обновить запись", position: (120, 130), size: (150, 30))]
     #[nwg_events(OnButtonClick: [EucarinogammarusApp::on_edit_click])]
     edit_button: nwg::Button,
     
     // Вкладка удаления
-    #[nwg_control(parent: tabs, text: "ID записи для удаления:", position: (10, 10), size: (150, 25))]
+    #[nwg_control(parent: tabs, text: "Удалить")]
+    delete_tab: nwg::Tab,
+    
+    #[nwg_control(parent: delete_tab, text: "ID записи для удаления:", position: (10, 10), size: (150, 25))]
     delete_id_label: nwg::Label,
     
-    #[nwg_control(parent: tabs, position: (170, 10), size: (200, 25))]
+    #[nwg_control(parent: delete_tab, position: (170, 10), size: (200, 25))]
     delete_id_input: nwg::TextInput,
     
-    #[nwg_control(parent: tabs, text: "Удалить запись", position: (170, 50), size: (150, 30))]
+    #[nwg_control(parent: delete_tab, text: "Удалить запись", position: (170, 50), size: (150, 30))]
     #[nwg_events(OnButtonClick: [EucarinogammarusApp::on_delete_click])]
     delete_button: nwg::Button,
     
@@ -216,19 +159,59 @@ pub struct EucarinogammarusApp {
 }
 
 impl EucarinogammarusApp {
+    // У Database нет значения по умолчанию, поэтому данные передаются сюда,
+    // а элементы управления остаются пустыми до build_ui
+    fn new(db: Database, records: Vec<Eucarinogammarus>) -> Self {
+        Self {
+            db: Rc::new(RefCell::new(db)),
+            records: Rc::new(RefCell::new(records)),
+            window: Default::default(),
+            tabs: Default::default(),
+            view_tab: Default::default(),
+            add_tab: Default::default(),
+            edit_tab: Default::default(),
+            delete_tab: Default::default(),
+            search_label: Default::default(),
+            search_input: Default::default(),
+            sort_label: Default::default(),
+            sort_button: Default::default(),
+            refresh_button: Default::default(),
+            records_list: Default::default(),
+            code_label: Default::default(),
+            code_input: Default::default(),
+            genus_label: Default::default(),
+            genus_input: Default::default(),
+            species_label: Default::default(),
+            species_input: Default::default(),
+            size_mm_label: Default::default(),
+            size_mm_input: Default::default(),
+            body_label: Default::default(),
+            body_input: Default::default(),
+            add_button: Default::default(),
+            edit_id_label: Default::default(),
+            edit_id_input: Default::default(),
+            edit_column_label: Default::default(),
+            edit_column_input: Default::default(),
+            edit_value_label: Default::default(),
+            edit_value_input: Default::default(),
+            edit_button: Default::default(),
+            delete_id_label: Default::default(),
+            delete_id_input: Default::default(),
+            delete_button: Default::default(),
+            status_label: Default::default(),
+        }
+    }
+    
     fn init(&self) {
-        // Инициализация вкладок
-        self.tabs.add_tab("Просмотр", Some(1));
-        self.tabs.add_tab("Добавить", Some(2));
-        self.tabs.add_tab("Редактировать", Some(3));
-        self.tabs.add_tab("Удалить", Some(4));
-        
         // Инициализация списка записей
-        self.records_list.insert_column("ID", 50);
-        self.records_list.insert_column("Код", 100);
-        self.records_list.insert_column("Род", 150);
-        self.records_list.insert_column("Вид", 150);
-        self.records_list.insert_column("Размеры мм", 100);
+        for (index, (text, width)) in [("ID", 50), ("Код", 100), ("Род", 150), ("Вид", 150), ("Размеры мм", 100)].into_iter().enumerate() {
+            self.records_list.insert_column(nwg::InsertListViewColumn {
+                index: Some(index as i32),
+                fmt: None,
+                width: Some(width),
+                text: Some(text.to_string()),
+            });
+        }
         
         // Заполнение списка записей
         self.refresh_records_list();
@@ -243,11 +226,14 @@ impl EucarinogammarusApp {
         
         // Заполнение списка
         for record in records.iter() {
-            let row = self.records_list.insert_item(record.id.to_string());
-            self.records_list.set_item_text(row, 1, &record.code);
-            self.records_list.set_item_text(row, 2, &record.genus);
-            self.records_list.set_item_text(row, 3, &record.species);
-            self.records_list.set_item_text(row, 4, &record.size_mm);
+            let id = record.id.to_string();
+            self.records_list.insert_items_row(None, &[
+                id.as_str(),
+                record.code.as_str(),
+                record.genus.as_str(),
+                record.species.as_str(),
+                record.size_mm.as_str(),
+            ]);
         }
     }
     
@@ -278,8 +264,9 @@ impl EucarinogammarusApp {
         self.sort_button.set_text(new_sort);
         
         // Обновление записей с учетом сортировки
-        if let Ok(conn) = self.conn.try_borrow() {
-            if let Ok(records) = load_records_sorted(&conn, new_sort) {
+        if let Ok(db) = self.db.try_borrow() {
+            let column = if new_sort == "ID" { "id" } else { new_sort };
+            if let Ok(records) = db.records_sorted(column, SortDirection::Ascending) {
                 *self.records.borrow_mut() = records;
                 self.refresh_records_list();
             }
@@ -288,8 +275,8 @@ impl EucarinogammarusApp {
     
     fn on_refresh_click(&self) {
         // Обновление записей
-        if let Ok(conn) = self.conn.try_borrow() {
-            if let Ok(records) = load_records(&conn) {
+        if let Ok(db) = self.db.try_borrow() {
+            if let Ok(records) = db.records() {
                 *self.records.borrow_mut() = records;
                 self.refresh_records_list();
                 self.status_label.set_text("Данные обновлены");
@@ -301,13 +288,11 @@ impl EucarinogammarusApp {
         // Обработка выбора записи в списке
         let selected = self.records_list.selected_item();
         
-        if selected >= 0 {
-            // Получение ID выбранной записи
-            let id = self.records_list.item_text(selected as usize, 0);
-            
+        // Получение ID выбранной записи
+        if let Some(item) = selected.and_then(|row| self.records_list.item(row, 0, 16)) {
             // Установка ID в поля редактирования и удаления
-            self.edit_id_input.set_text(&id);
-            self.delete_id_input.set_text(&id);
+            self.edit_id_input.set_text(&item.text);
+            self.delete_id_input.set_text(&item.text);
         }
     }
     
@@ -320,15 +305,23 @@ impl EucarinogammarusApp {
         let body = self.body_input.text();
         
         // Добавление записи в базу данных
-        if let Ok(conn) = self.conn.try_borrow() {
-            let result = conn.execute(
-                "INSERT INTO Eucarinogammarus (
-                    Код, Род, Вид, Размеры_мм, Тело
-                ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5
-                )",
-                params![code, genus, species, size_mm, body],
-            );
+        if let Ok(db) = self.db.try_borrow() {
+            let values: Vec<&str> = COLUMNS.iter()
+                .map(|c| match *c {
+                    "Код" => code.as_str(),
+                    "Род" => genus.as_str(),
+                    "Вид" => species.as_str(),
+                    "Размеры_мм" => size_mm.as_str(),
+                    "Тело" => body.as_str(),
+                    _ => "",
+                })
+                .collect();
+            
+            let named: Vec<(&str, &str)> = COLUMNS.iter().copied().zip(values.iter().copied()).collect();
+            let result = db.validate(None, &named).and_then(|errors| match errors.first() {
+                Some(error) => Err(DbError::Invalid(format!("{}: {}", error.column, error.message))),
+                None => db.insert_values(&values),
+            });
             
             match result {
                 Ok(_) => {
@@ -340,7 +333,7 @@ impl EucarinogammarusApp {
                     self.body_input.set_text("");
                     
                     // Обновление списка записей
-                    if let Ok(records) = load_records(&conn) {
+                    if let Ok(records) = db.records() {
                         *self.records.borrow_mut() = records;
                         self.refresh_records_list();
                     }
@@ -361,15 +354,7 @@ impl EucarinogammarusApp {
         let value = self.edit_value_input.text();
         
         // Проверка валидности столбца
-        let valid_columns = vec![
-            "Код", "Род", "Вид", "Размеры_мм", "Тело", "Окраска", "Распространение",
-            "Глубина_м", "Вооруж_тела", "Средний_ряд_I_VII", "Средн_ряд_VIII_X",
-            "Сред_ряд_урозом", "Боковой_ряд", "Краевой_ряд", "Особен_воор",
-            "Эпимир_пласт", "Верх_антенны", "Прид_жгутик", "Нижн_антенны",
-            "Базип_III_V", "Уроподы_III", "Головн_сегм", "Глаза", "Тельсон"
-        ];
-        
-        if !valid_columns.contains(&column.as_str()) {
+        if !COLUMNS.contains(&column.as_str()) {
             self.status_label.set_text("Неверное имя столбца");
             return;
         }
@@ -381,21 +366,22 @@ impl EucarinogammarusApp {
         }
         
         // Обновление записи в базе данных
-        if let Ok(conn) = self.conn.try_borrow() {
-            let query = format!(
-                "UPDATE Eucarinogammarus SET {} = ?1 WHERE id = ?2",
-                column
-            );
+        if let Ok(db) = self.db.try_borrow() {
+            let result = db.validate(Some(id_num), &[(column.as_str(), value.as_str())])
+                .and_then(|errors| match errors.first() {
+                    Some(error) => Err(DbError::Invalid(error.message.clone())),
+                    None => db.update(id_num, &column, &value),
+                });
             
-            match conn.execute(&query, params![value, id_num]) {
-                Ok(_) => {
+            match result {
+                Ok(record) => {
                     // Обновление списка записей
-                    if let Ok(records) = load_records(&conn) {
+                    if let Ok(records) = db.records() {
                         *self.records.borrow_mut() = records;
                         self.refresh_records_list();
                     }
                     
-                    self.status_label.set_text("Запись успешно обновлена");
+                    self.status_label.set_text(&format!("Запись {} обновлена", record.name()));
                 },
                 Err(e) => {
                     self.status_label.set_text(&format!("Ошибка: {}", e));
@@ -415,16 +401,17 @@ impl EucarinogammarusApp {
         }
         
         // Удаление записи из базы данных
-        if let Ok(conn) = self.conn.try_borrow() {
-            match conn.execute("DELETE FROM Eucarinogammarus WHERE id = ?1", params![id_num]) {
-                Ok(_) => {
+        if let Ok(db) = self.db.try_borrow() {
+            // Удалённые записи попадают в корзину, как и в основном интерфейсе
+            match db.delete(id_num) {
+                Ok(record) => {
                     // Обновление списка записей
-                    if let Ok(records) = load_records(&conn) {
+                    if let Ok(records) = db.records() {
                         *self.records.borrow_mut() = records;
                         self.refresh_records_list();
                     }
                     
-                    self.status_label.set_text("Запись успешно удалена");
+                    self.status_label.set_text(&format!("Запись {} перемещена в корзину", record.name()));
                 },
                 Err(e) => {
                     self.status_label.set_text(&format!("Ошибка: {}", e));
@@ -0,0 +1,25 @@
+// Слой данных, общий для всех интерфейсов: графического, Win32 и командной строки.
+// Основная точка входа — Database; остальные модули открыты для операций,
+// которые удобнее вызывать напрямую с соединением
+pub mod config;
+pub mod database;
+pub mod db;
+pub mod audit;
+pub mod backup;
+pub mod bulk;
+pub mod crypto;
+pub mod dwca;
+pub mod error;
+pub mod diff;
+pub mod duplicates;
+pub mod merge;
+pub mod sync;
+pub mod textdir;
+pub mod similarity;
+pub mod trash;
+pub mod undo;
+pub mod vocabulary;
+
+pub use database::Database;
+pub use error::DbError;
+pub use db::{Eucarinogammarus, FieldError, SortDirection, COLUMNS, COLUMN_LABELS};
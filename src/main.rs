@@ -1,21 +1,84 @@
-mod db;
+#[cfg(feature = "egui")]
 mod app;
+#[cfg(feature = "egui")]
 mod views;
+#[cfg(all(windows, feature = "nwg"))]
+mod gui;
 
-use eframe::egui;
 use std::error::Error;
+use std::path::PathBuf;
 
+// Аргументы запуска графического интерфейса
+#[derive(Debug, Default)]
+struct Args {
+    // Файл базы вместо последней открытой (--db ФАЙЛ или --db=ФАЙЛ)
+    db: Option<PathBuf>,
+    // Интерфейс Win32 вместо egui
+    nwg: bool,
+}
+
+fn parse_args() -> Result<Args, Box<dyn Error>> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--nwg" => args.nwg = true,
+            "--db" => args.db = Some(iter.next().ok_or("после --db ожидается путь к файлу базы")?.into()),
+            _ => match arg.strip_prefix("--db=") {
+                Some(path) => args.db = Some(path.into()),
+                None => return Err(format!("неизвестный аргумент {}", arg).into()),
+            },
+        }
+    }
+    Ok(args)
+}
+
+// Интерфейс Win32 запускается аргументом --nwg, если он собран; по умолчанию — egui.
+// Командная строка вынесена в отдельную программу gammarus-cli
 fn main() -> Result<(), Box<dyn Error>> {
+    let args = parse_args()?;
+    if args.nwg {
+        run_nwg(args.db)
+    } else {
+        run_default(args.db)
+    }
+}
+
+#[cfg(all(windows, feature = "nwg"))]
+fn run_nwg(db: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    gui::run_gui_app(db)
+}
+
+#[cfg(not(all(windows, feature = "nwg")))]
+fn run_nwg(_db: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    Err("Интерфейс Win32 не собран: он доступен только в Windows с feature nwg".into())
+}
+
+#[cfg(feature = "egui")]
+fn run_default(db: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
     let options = eframe::NativeOptions {
-        initial_window_size: Some(egui::vec2(800.0, 600.0)),
+        initial_window_size: Some(eframe::egui::vec2(800.0, 600.0)),
         ..Default::default()
     };
     
+    // База открывается до создания окна: ошибки открытия файла показывает
+    // экран запуска, а без базы в памяти окну показывать нечего
+    let opened = app::StartupDatabase::open(db)?;
     eframe::run_native(
         "База данных Eucarinogammarus",
         options,
-        Box::new(|cc| Box::new(app::EucarinogammarusApp::new(cc))),
+        Box::new(move |cc| Box::new(app::EucarinogammarusApp::new(cc, opened))),
     )?;
     
     Ok(())
 }
+
+#[cfg(all(not(feature = "egui"), windows, feature = "nwg"))]
+fn run_default(db: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    gui::run_gui_app(db)
+}
+
+#[cfg(all(not(feature = "egui"), not(all(windows, feature = "nwg"))))]
+fn run_default(_db: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    Err("Графический интерфейс не собран: включите feature egui или nwg либо используйте gammarus-cli".into())
+}
@@ -0,0 +1,236 @@
+use rusqlite::{Connection, OpenFlags};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::crypto;
+use crate::db::{self, Eucarinogammarus, COLUMNS};
+use crate::error::DbError;
+use crate::trash;
+use crate::undo::Command;
+
+// Сравнение текущей базы (целевой) с другим файлом базы (источником)
+// и перенос выбранных отличий из источника. Записи сопоставляются по роду
+// и виду без учёта регистра и пробелов; если в одной базе несколько записей
+// с одним названием, они сопоставляются по порядку ID
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    // Запись есть только в источнике
+    Added,
+    // Запись есть только в целевой базе
+    Removed,
+    // Запись есть в обеих базах, но отличается значениями полей
+    Changed,
+}
+
+// Отличие одного поля; take_source — перенести значение источника
+#[derive(Debug, Clone)]
+pub struct FieldChange {
+    pub column: &'static str,
+    pub target: String,
+    pub source: String,
+    pub take_source: bool,
+}
+
+impl FieldChange {
+    // Конфликт — оба значения заполнены и различаются; пустое поле
+    // просто дополняется значением другой базы
+    pub fn is_conflict(&self) -> bool {
+        !self.target.trim().is_empty() && !self.source.trim().is_empty()
+    }
+}
+
+// Отличие одной записи; apply — решение пользователя для Added и Removed
+#[derive(Debug, Clone)]
+pub struct RecordChange {
+    pub kind: ChangeKind,
+    pub target: Option<Eucarinogammarus>,
+    pub source: Option<Eucarinogammarus>,
+    pub fields: Vec<FieldChange>,
+    pub apply: bool,
+}
+
+impl RecordChange {
+    // Род, вид и ID для списков; для новой записи ID источника не показывается
+    pub fn name(&self) -> String {
+        match (&self.target, &self.source) {
+            (Some(record), _) => record.name(),
+            (None, Some(record)) => format!("{} {}", record.genus, record.species),
+            (None, None) => String::new(),
+        }
+    }
+
+    pub fn conflicts(&self) -> usize {
+        self.fields.iter().filter(|f| f.is_conflict()).count()
+    }
+}
+
+// Сравнение, ожидающее просмотра пользователем
+#[derive(Debug, Clone)]
+pub struct PendingMerge {
+    pub source: PathBuf,
+    pub changes: Vec<RecordChange>,
+    // Записей без отличий
+    pub unchanged: usize,
+}
+
+impl PendingMerge {
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    // Разрешение всех конфликтов в пользу источника или целевой базы
+    pub fn prefer_source(&mut self, prefer: bool) {
+        for field in self.changes.iter_mut().flat_map(|c| c.fields.iter_mut()) {
+            if field.is_conflict() {
+                field.take_source = prefer;
+            }
+        }
+    }
+}
+
+// Итог слияния
+#[derive(Debug, Clone, Default)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+// Неудалённые записи другого файла базы; файл открывается только для чтения,
+// зашифрованный — паролем password
+pub fn read_source(path: &Path, password: Option<&str>) -> Result<Vec<Eucarinogammarus>, DbError> {
+    if !path.is_file() {
+        return Err(DbError::Invalid(format!("Файл {} не найден", path.display())));
+    }
+    let conn = crypto::open(path, OpenFlags::SQLITE_OPEN_READ_ONLY, password)?;
+    db::load_records(&conn).map_err(|e| e.at(path))
+}
+
+// Сравнение записей целевой базы target с записями источника source.
+// По умолчанию новые записи добавляются, отсутствующие в источнике остаются,
+// пустые поля дополняются, а в конфликтах сохраняется значение целевой базы
+pub fn compare(target: &[Eucarinogammarus], source: &[Eucarinogammarus], source_path: &Path) -> PendingMerge {
+    let mut pairs: BTreeMap<String, (Vec<&Eucarinogammarus>, Vec<&Eucarinogammarus>)> = BTreeMap::new();
+    for record in target {
+        pairs.entry(match_key(record)).or_default().0.push(record);
+    }
+    for record in source {
+        pairs.entry(match_key(record)).or_default().1.push(record);
+    }
+
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    for (targets, sources) in pairs.into_values() {
+        for i in 0..targets.len().max(sources.len()) {
+            let (target, source) = (targets.get(i).copied(), sources.get(i).copied());
+            match (target, source) {
+                (Some(target), Some(source)) => {
+                    let fields = field_changes(target, source);
+                    if fields.is_empty() {
+                        unchanged += 1;
+                        continue;
+                    }
+                    changes.push(RecordChange {
+                        kind: ChangeKind::Changed,
+                        target: Some(target.clone()),
+                        source: Some(source.clone()),
+                        fields,
+                        apply: true,
+                    });
+                }
+                (None, Some(source)) => changes.push(RecordChange {
+                    kind: ChangeKind::Added,
+                    target: None,
+                    source: Some(source.clone()),
+                    fields: Vec::new(),
+                    apply: true,
+                }),
+                (Some(target), None) => changes.push(RecordChange {
+                    kind: ChangeKind::Removed,
+                    target: Some(target.clone()),
+                    source: None,
+                    fields: Vec::new(),
+                    apply: false,
+                }),
+                (None, None) => {}
+            }
+        }
+    }
+
+    PendingMerge { source: source_path.to_path_buf(), changes, unchanged }
+}
+
+// Применение выбранных отличий одной транзакцией; возвращает итог и команду для отмены.
+// Поле, изменённое в целевой базе после сравнения, прерывает слияние с DbError::Conflict
+pub fn apply(conn: &Connection, pending: &PendingMerge, user_name: &str) -> Result<(MergeSummary, Command), DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let mut summary = MergeSummary::default();
+    let mut commands = Vec::new();
+
+    for change in &pending.changes {
+        match (change.kind, &change.target, &change.source) {
+            (ChangeKind::Added, _, Some(source)) if change.apply => {
+                let id = db::insert_values(&tx, &source.values())? as i32;
+                commands.push(Command::Insert { id, name: Eucarinogammarus { id, ..source.clone() }.name() });
+                summary.added += 1;
+            }
+            (ChangeKind::Removed, Some(target), _) if change.apply => {
+                trash::soft_delete(&tx, target.id, user_name)?;
+                commands.push(Command::Delete { id: target.id, name: target.name() });
+                summary.removed += 1;
+            }
+            (ChangeKind::Changed, Some(target), _) => {
+                let mut updated = false;
+                for field in change.fields.iter().filter(|f| f.take_source) {
+                    db::update_field_expecting(&tx, target.id, field.column, &field.target, &field.source)?;
+                    commands.push(Command::Update {
+                        id: target.id,
+                        column: field.column.to_string(),
+                        old: field.target.clone(),
+                        new: field.source.clone(),
+                    });
+                    updated = true;
+                }
+                if updated {
+                    summary.updated += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tx.commit()?;
+
+    let file = pending.source.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let command = Command::Bulk {
+        description: format!("слияние с {} ({} записей)", file, summary.added + summary.updated + summary.removed),
+        commands,
+    };
+    Ok((summary, command))
+}
+
+pub(crate) fn match_key(record: &Eucarinogammarus) -> String {
+    format!("{} {}", normalize(&record.genus), normalize(&record.species))
+}
+
+fn normalize(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn field_changes(target: &Eucarinogammarus, source: &Eucarinogammarus) -> Vec<FieldChange> {
+    COLUMNS.iter()
+        .filter_map(|column| {
+            let (old, new) = (target.field(column).unwrap_or(""), source.field(column).unwrap_or(""));
+            if old == new {
+                return None;
+            }
+            Some(FieldChange {
+                column,
+                target: old.to_string(),
+                source: new.to_string(),
+                take_source: old.trim().is_empty(),
+            })
+        })
+        .collect()
+}
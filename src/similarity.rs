@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+
+use crate::db::{Eucarinogammarus, COLUMNS};
+use crate::error::DbError;
+
+// Столбцы, не описывающие морфологию, в сравнении не участвуют
+const EXCLUDED: [&str; 5] = ["Код", "Род", "Вид", "Распространение", "Глубина_м"];
+
+// Числовые признаки: расстояние считается по середине диапазона
+const NUMERIC: [&str; 1] = ["Размеры_мм"];
+
+// Значение из не более чем стольких слов считается кодированным состоянием
+const CODED_MAX_WORDS: usize = 3;
+
+// Симметричная матрица попарных расстояний Гауэра
+#[derive(Debug, Clone)]
+pub struct DistanceMatrix {
+    pub ids: Vec<i32>,
+    pub labels: Vec<String>,
+    pub values: Vec<Vec<f64>>,
+}
+
+impl DistanceMatrix {
+    // Ближайшие к записи виды по возрастанию расстояния
+    pub fn most_similar(&self, id: i32, count: usize) -> Vec<(i32, f64)> {
+        let row = match self.ids.iter().position(|x| *x == id) {
+            Some(row) => row,
+            None => return Vec::new(),
+        };
+
+        let mut nearest: Vec<(i32, f64)> = self.ids.iter()
+            .zip(&self.values[row])
+            .filter(|(other, _)| **other != id)
+            .map(|(other, d)| (*other, *d))
+            .collect();
+        nearest.sort_by(|a, b| a.1.total_cmp(&b.1));
+        nearest.truncate(count);
+        nearest
+    }
+
+    // Матрица в CSV: первая строка и первый столбец — названия видов
+    pub fn write_csv(&self, file_path: &str) -> Result<(), DbError> {
+        let mut wtr = csv::Writer::from_path(file_path)?;
+        wtr.write_record(std::iter::once("").chain(self.labels.iter().map(String::as_str)))?;
+        for (label, row) in self.labels.iter().zip(&self.values) {
+            let cells = row.iter().map(|d| format!("{:.4}", d));
+            wtr.write_record(std::iter::once(label.clone()).chain(cells))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    // Дендрограмма UPGMA в формате Newick
+    pub fn upgma_newick(&self) -> String {
+        if self.ids.is_empty() {
+            return ";".to_string();
+        }
+
+        // Кластер: поддерево Newick, высота узла и число листьев
+        let mut clusters: Vec<(String, f64, usize)> = self.labels.iter()
+            .map(|l| (newick_label(l), 0.0, 1))
+            .collect();
+        let mut dist = self.values.clone();
+
+        while clusters.len() > 1 {
+            let (mut a, mut b) = (0, 1);
+            for i in 0..clusters.len() {
+                for j in (i + 1)..clusters.len() {
+                    if dist[i][j] < dist[a][b] {
+                        a = i;
+                        b = j;
+                    }
+                }
+            }
+
+            let height = dist[a][b] / 2.0;
+            let (tree_b, height_b, size_b) = clusters.remove(b);
+            let (tree_a, height_a, size_a) = clusters.remove(a);
+            let merged = format!(
+                "({}:{:.4},{}:{:.4})",
+                tree_a,
+                (height - height_a).max(0.0),
+                tree_b,
+                (height - height_b).max(0.0)
+            );
+
+            // Расстояние до нового кластера — среднее, взвешенное по числу листьев
+            let row: Vec<f64> = (0..dist.len())
+                .filter(|k| *k != a && *k != b)
+                .map(|k| (dist[a][k] * size_a as f64 + dist[b][k] * size_b as f64) / (size_a + size_b) as f64)
+                .collect();
+
+            for d in dist.iter_mut() {
+                d.remove(b);
+                d.remove(a);
+            }
+            dist.remove(b);
+            dist.remove(a);
+            for (d, value) in dist.iter_mut().zip(&row) {
+                d.push(*value);
+            }
+            let mut last = row;
+            last.push(0.0);
+            dist.push(last);
+
+            clusters.push((merged, height, size_a + size_b));
+        }
+
+        format!("{};", clusters[0].0)
+    }
+}
+
+// Расстояние Гауэра: среднее по признакам, заполненным у обеих записей.
+// Числовые признаки нормируются на размах, кодированные состояния
+// сравниваются на равенство, описательный текст — косинусом TF-IDF
+pub fn distance_matrix(records: &[Eucarinogammarus]) -> DistanceMatrix {
+    let columns: Vec<&str> = COLUMNS.iter().copied().filter(|c| !EXCLUDED.contains(c)).collect();
+
+    let numeric: HashMap<&str, (Vec<Option<f64>>, f64)> = columns.iter()
+        .filter(|c| NUMERIC.contains(c))
+        .map(|c| {
+            let values: Vec<Option<f64>> = records.iter().map(|r| midpoint(r.field(c).unwrap_or(""))).collect();
+            let present: Vec<f64> = values.iter().flatten().copied().collect();
+            let min = present.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = present.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            (*c, (values, (max - min).max(0.0)))
+        })
+        .collect();
+
+    let prose: HashMap<&str, Vec<HashMap<String, f64>>> = columns.iter()
+        .filter(|c| !NUMERIC.contains(c))
+        .map(|c| (*c, tfidf(records.iter().map(|r| r.field(c).unwrap_or("")))))
+        .collect();
+
+    let n = records.len();
+    let mut values = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let mut total = 0.0;
+            let mut count = 0usize;
+
+            for column in &columns {
+                let d = if let Some((numbers, range)) = numeric.get(column) {
+                    match (numbers[i], numbers[j]) {
+                        (Some(x), Some(y)) if *range > 0.0 => Some((x - y).abs() / range),
+                        (Some(_), Some(_)) => Some(0.0),
+                        _ => None,
+                    }
+                } else {
+                    let x = normalize(records[i].field(column).unwrap_or(""));
+                    let y = normalize(records[j].field(column).unwrap_or(""));
+                    if x.is_empty() || y.is_empty() {
+                        None
+                    } else if is_coded(&x) && is_coded(&y) {
+                        Some(if x == y { 0.0 } else { 1.0 })
+                    } else {
+                        Some(1.0 - cosine(&prose[column][i], &prose[column][j]))
+                    }
+                };
+
+                if let Some(d) = d {
+                    total += d;
+                    count += 1;
+                }
+            }
+
+            // Записи без общих признаков считаются максимально далёкими
+            let d = if count == 0 { 1.0 } else { total / count as f64 };
+            values[i][j] = d;
+            values[j][i] = d;
+        }
+    }
+
+    DistanceMatrix {
+        ids: records.iter().map(|r| r.id).collect(),
+        labels: records.iter().map(|r| format!("{} {}", r.genus.trim(), r.species.trim())).collect(),
+        values,
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn is_coded(value: &str) -> bool {
+    value.split_whitespace().count() <= CODED_MAX_WORDS
+}
+
+// Середина диапазона вида "12-15" или "до 20"; None, если чисел нет
+fn midpoint(value: &str) -> Option<f64> {
+    let numbers: Vec<f64> = value
+        .replace(',', ".")
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|s| s.trim_matches('.').parse().ok())
+        .collect();
+    let min = numbers.iter().copied().reduce(f64::min)?;
+    let max = numbers.iter().copied().reduce(f64::max)?;
+    Some((min + max) / 2.0)
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1)
+        .map(String::from)
+        .collect()
+}
+
+// Векторы TF-IDF для набора текстов одного признака
+fn tfidf<'a>(texts: impl Iterator<Item = &'a str>) -> Vec<HashMap<String, f64>> {
+    let docs: Vec<Vec<String>> = texts.map(tokens).collect();
+    let mut df: HashMap<&str, usize> = HashMap::new();
+    for doc in &docs {
+        let mut seen: Vec<&str> = doc.iter().map(String::as_str).collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for term in seen {
+            *df.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    let n = docs.len() as f64;
+    docs.iter()
+        .map(|doc| {
+            let mut tf: HashMap<String, f64> = HashMap::new();
+            for term in doc {
+                *tf.entry(term.clone()).or_insert(0.0) += 1.0;
+            }
+            for (term, weight) in tf.iter_mut() {
+                *weight *= (n / df[term.as_str()] as f64).ln() + 1.0;
+            }
+            tf
+        })
+        .collect()
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(k, x)| b.get(k).map(|y| x * y)).sum();
+    let norm_a = a.values().map(|x| x * x).sum::<f64>().sqrt();
+    let norm_b = b.values().map(|x| x * x).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        (dot / (norm_a * norm_b)).min(1.0)
+    }
+}
+
+fn newick_label(label: &str) -> String {
+    let label = label.replace(' ', "_");
+    if label.chars().any(|c| "()[]':;,".contains(c)) {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        label
+    }
+}
+
+// Экспорт матрицы и дендрограммы в файлы
+pub fn export(records: &[Eucarinogammarus], matrix_path: &str, newick_path: &str) -> Result<(), DbError> {
+    let matrix = distance_matrix(records);
+    if !matrix_path.is_empty() {
+        matrix.write_csv(matrix_path)?;
+    }
+    if !newick_path.is_empty() {
+        let mut file = File::create(newick_path)?;
+        writeln!(file, "{}", matrix.upgma_newick())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::db::{record_from_row, Eucarinogammarus, COLUMNS};
+use crate::error::DbError;
+use crate::merge;
+
+// Синхронизация копий базы на разных компьютерах через файлы наборов
+// изменений (например, на флешке). Записи сопоставляются по UUID, а не по ID:
+// ID имеет смысл только внутри одного файла. Из двух версий записи остаётся
+// изменённая позже (modified_at, UTC); окончательно удалённые записи
+// передаются как надгробия (Tombstone) и больше не возвращаются. Повторный
+// импорт того же набора ничего не меняет
+
+// Версия формата файла набора изменений
+pub const FORMAT: u32 = 1;
+
+// Набор изменений в файле TOML
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub format: u32,
+    pub id: String,
+    pub created_at: String,
+    // Набор содержит только изменения после этого времени; нет — вся база
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub records: Vec<SyncRecord>,
+    #[serde(default)]
+    pub purged: Vec<Purged>,
+}
+
+// Запись со всеми полями, включая удалённые в корзину
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub uuid: String,
+    pub modified_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<String>,
+    // Столбец базы -> значение; отсутствующий столбец считается пустым
+    pub fields: BTreeMap<String, String>,
+}
+
+// Надгробие окончательно удалённой записи
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Purged {
+    pub uuid: String,
+    pub deleted_at: String,
+}
+
+// Итог импорта набора изменений
+#[derive(Debug, Clone, Default)]
+pub struct SyncSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    // Записи, которые в этой базе не старше, чем в наборе, или уже удалены
+    pub skipped: usize,
+    // Набор с этим id уже импортировался раньше
+    pub repeated: bool,
+}
+
+// Запись журнала синхронизации
+#[derive(Debug, Clone)]
+pub struct SyncEntry {
+    pub timestamp: String,
+    pub changeset: String,
+    // "export" или "import"
+    pub direction: String,
+    pub file: String,
+    pub records: usize,
+}
+
+// Выгрузка записей, изменённых после since (без since — всех), в файл path
+pub fn export(conn: &Connection, path: &Path, since: Option<&str>) -> Result<ChangeSet, DbError> {
+    let (id, created_at): (String, String) = conn.query_row(
+        "SELECT lower(hex(randomblob(16))), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let changes = ChangeSet {
+        format: FORMAT,
+        id,
+        created_at,
+        since: since.map(str::to_string),
+        records: load_sync_records(conn, since)?,
+        purged: load_purged(conn, since)?,
+    };
+
+    fs::write(path, toml::to_string(&changes)?)?;
+    log(conn, &changes, "export", path)?;
+    Ok(changes)
+}
+
+// Чтение набора изменений без применения
+pub fn read(path: &Path) -> Result<ChangeSet, DbError> {
+    let changes: ChangeSet = toml::from_str(&fs::read_to_string(path)?)?;
+    if changes.format > FORMAT {
+        return Err(DbError::Invalid(format!(
+            "Набор изменений {} создан более новой версией программы (формат {})",
+            path.display(),
+            changes.format
+        )));
+    }
+    if let Some(column) = changes.records.iter().flat_map(|r| r.fields.keys()).find(|c| !COLUMNS.contains(&c.as_str())) {
+        return Err(DbError::InvalidColumn(column.clone()));
+    }
+    Ok(changes)
+}
+
+// Применение набора изменений из файла path одной транзакцией
+pub fn import(conn: &Connection, path: &Path) -> Result<SyncSummary, DbError> {
+    let changes = read(path)?;
+    let tx = conn.unchecked_transaction()?;
+    let mut summary = SyncSummary {
+        repeated: tx.query_row(
+            "SELECT 1 FROM SyncLog WHERE changeset = ?1 AND direction = 'import'",
+            params![changes.id],
+            |_| Ok(()),
+        ).optional()?.is_some(),
+        ..Default::default()
+    };
+
+    for purged in &changes.purged {
+        let local = local_state(&tx, &purged.uuid)?;
+        match local {
+            // Запись, изменённая здесь после удаления там, остаётся
+            Some((_, modified_at)) if modified_at > purged.deleted_at => {
+                summary.skipped += 1;
+                continue;
+            }
+            Some((id, _)) => {
+                tx.execute("DELETE FROM Specimen WHERE taxon_id = ?1", params![id])?;
+                tx.execute("DELETE FROM Eucarinogammarus WHERE id = ?1", params![id])?;
+                summary.deleted += 1;
+            }
+            None => {}
+        }
+        // Время удаления берётся из набора, а не время импорта
+        tx.execute(
+            "INSERT OR REPLACE INTO Tombstone (uuid, deleted_at) VALUES (?1, ?2)",
+            params![purged.uuid, purged.deleted_at],
+        )?;
+    }
+
+    let adopted = adoptable(&tx, &changes.records)?;
+    for record in &changes.records {
+        if is_purged(&tx, &record.uuid)? {
+            summary.skipped += 1;
+            continue;
+        }
+        if let Some(id) = adopted.get(&record.uuid) {
+            tx.execute("UPDATE Eucarinogammarus SET uuid = ?1 WHERE id = ?2", params![record.uuid, id])?;
+        }
+        match local_state(&tx, &record.uuid)? {
+            Some((id, modified_at)) if record.modified_at > modified_at => {
+                overwrite(&tx, id, record)?;
+                summary.updated += 1;
+            }
+            Some(_) => summary.skipped += 1,
+            None => {
+                insert(&tx, record)?;
+                summary.inserted += 1;
+            }
+        }
+    }
+
+    log(&tx, &changes, "import", path)?;
+    tx.commit()?;
+    Ok(summary)
+}
+
+// Журнал синхронизации, последние записи сверху
+pub fn load_log(conn: &Connection) -> Result<Vec<SyncEntry>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, changeset, direction, file, records FROM SyncLog ORDER BY id DESC",
+    )?;
+    let entries = stmt.query_map([], |row| {
+        Ok(SyncEntry {
+            timestamp: row.get(0)?,
+            changeset: row.get(1)?,
+            direction: row.get(2)?,
+            file: row.get(3)?,
+            records: row.get::<_, i64>(4)? as usize,
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for entry in entries {
+        result.push(entry?);
+    }
+    Ok(result)
+}
+
+// Время последней выгрузки — начало следующего неполного набора. Записи,
+// полученные из других копий, сохраняют своё время изменения и в такой
+// набор не попадают, поэтому для передачи дальше нужна полная выгрузка
+pub fn last_export(conn: &Connection) -> Result<Option<String>, DbError> {
+    let timestamp = conn.query_row(
+        "SELECT MAX(timestamp) FROM SyncLog WHERE direction = 'export'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(timestamp)
+}
+
+pub(crate) fn load_sync_records(conn: &Connection, since: Option<&str>) -> Result<Vec<SyncRecord>, DbError> {
+    let query = format!(
+        "SELECT id, {}, uuid, modified_at, deleted_at, deleted_by FROM Eucarinogammarus
+         WHERE ?1 IS NULL OR modified_at > ?1 ORDER BY uuid",
+        COLUMNS.join(", ")
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let first = COLUMNS.len() + 1;
+    let records = stmt.query_map(params![since], |row| {
+        let record = record_from_row(row)?;
+        Ok(SyncRecord {
+            uuid: row.get(first)?,
+            modified_at: row.get(first + 1)?,
+            deleted_at: row.get(first + 2)?,
+            deleted_by: row.get(first + 3)?,
+            fields: COLUMNS.iter()
+                .map(|c| (c.to_string(), record.field(c).unwrap_or("").to_string()))
+                .collect(),
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for record in records {
+        result.push(record?);
+    }
+    Ok(result)
+}
+
+fn load_purged(conn: &Connection, since: Option<&str>) -> Result<Vec<Purged>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT uuid, deleted_at FROM Tombstone WHERE ?1 IS NULL OR deleted_at > ?1 ORDER BY uuid",
+    )?;
+    let purged = stmt.query_map(params![since], |row| Ok(Purged { uuid: row.get(0)?, deleted_at: row.get(1)? }))?;
+
+    let mut result = Vec::new();
+    for entry in purged {
+        result.push(entry?);
+    }
+    Ok(result)
+}
+
+// ID и время изменения записи с этим UUID
+fn local_state(conn: &Connection, uuid: &str) -> Result<Option<(i32, String)>, DbError> {
+    let state = conn.query_row(
+        "SELECT id, modified_at FROM Eucarinogammarus WHERE uuid = ?1",
+        params![uuid],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).optional()?;
+    Ok(state)
+}
+
+fn is_purged(conn: &Connection, uuid: &str) -> Result<bool, DbError> {
+    let found = conn.query_row("SELECT 1 FROM Tombstone WHERE uuid = ?1", params![uuid], |_| Ok(())).optional()?;
+    Ok(found.is_some())
+}
+
+// Копии одной базы, обновлённые до версии с UUID по отдельности, получили
+// разные UUID для одних и тех же записей. Новая для этой базы запись набора
+// перенимается местной записью, если название у обеих единственное в своей
+// базе, а UUID местной записи в наборе не встречается. Возвращает UUID -> ID
+fn adoptable(conn: &Connection, records: &[SyncRecord]) -> Result<HashMap<String, i32>, DbError> {
+    let incoming: HashSet<&str> = records.iter().map(|r| r.uuid.as_str()).collect();
+    let mut unknown: HashMap<String, Vec<&str>> = HashMap::new();
+    for record in records {
+        if local_state(conn, &record.uuid)?.is_none() && !is_purged(conn, &record.uuid)? {
+            unknown.entry(merge::match_key(&record.to_record())).or_default().push(&record.uuid);
+        }
+    }
+
+    let mut stmt = conn.prepare(&format!("SELECT id, {}, uuid FROM Eucarinogammarus", COLUMNS.join(", ")))?;
+    let rows = stmt.query_map([], |row| Ok((record_from_row(row)?, row.get::<_, String>(COLUMNS.len() + 1)?)))?;
+    let mut local: HashMap<String, Vec<(i32, String)>> = HashMap::new();
+    for row in rows {
+        let (record, uuid) = row?;
+        local.entry(merge::match_key(&record)).or_default().push((record.id, uuid));
+    }
+
+    let mut adopted = HashMap::new();
+    for (key, uuids) in unknown {
+        if let (&[uuid], Some([(id, own)])) = (uuids.as_slice(), local.get(&key).map(Vec::as_slice)) {
+            if !incoming.contains(own.as_str()) {
+                adopted.insert(uuid.to_string(), *id);
+            }
+        }
+    }
+    Ok(adopted)
+}
+
+// Замена всех полей записи версией из набора. modified_at меняется явно,
+// поэтому триггер record_modified не ставит время импорта
+fn overwrite(conn: &Connection, id: i32, record: &SyncRecord) -> Result<(), DbError> {
+    let assignments: Vec<String> = COLUMNS.iter().enumerate().map(|(i, c)| format!("{} = ?{}", c, i + 1)).collect();
+    let n = COLUMNS.len();
+    let query = format!(
+        "UPDATE Eucarinogammarus SET {}, modified_at = ?{}, deleted_at = ?{}, deleted_by = ?{} WHERE id = ?{}",
+        assignments.join(", "),
+        n + 1,
+        n + 2,
+        n + 3,
+        n + 4
+    );
+
+    let mut values: Vec<rusqlite::types::Value> = field_values(record);
+    values.push(record.modified_at.clone().into());
+    values.push(record.deleted_at.clone().into());
+    values.push(record.deleted_by.clone().into());
+    values.push(id.into());
+    conn.execute(&query, rusqlite::params_from_iter(values))?;
+    Ok(())
+}
+
+pub(crate) fn insert(conn: &Connection, record: &SyncRecord) -> Result<(), DbError> {
+    let placeholders: Vec<String> = (1..=COLUMNS.len() + 4).map(|i| format!("?{}", i)).collect();
+    let query = format!(
+        "INSERT INTO Eucarinogammarus ({}, uuid, modified_at, deleted_at, deleted_by) VALUES ({})",
+        COLUMNS.join(", "),
+        placeholders.join(", ")
+    );
+
+    let mut values = field_values(record);
+    values.push(record.uuid.clone().into());
+    values.push(record.modified_at.clone().into());
+    values.push(record.deleted_at.clone().into());
+    values.push(record.deleted_by.clone().into());
+    conn.execute(&query, rusqlite::params_from_iter(values))?;
+    Ok(())
+}
+
+fn field_values(record: &SyncRecord) -> Vec<rusqlite::types::Value> {
+    COLUMNS.iter()
+        .map(|c| record.fields.get(*c).cloned().unwrap_or_default().into())
+        .collect()
+}
+
+fn log(conn: &Connection, changes: &ChangeSet, direction: &str, path: &Path) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO SyncLog (changeset, direction, file, records) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            changes.id,
+            direction,
+            path.display().to_string(),
+            (changes.records.len() + changes.purged.len()) as i64
+        ],
+    )?;
+    Ok(())
+}
+
+impl SyncRecord {
+    // Поля набора в виде записи (без ID) для сопоставления по названию
+    fn to_record(&self) -> Eucarinogammarus {
+        let field = |column: &str| self.fields.get(column).cloned().unwrap_or_default();
+        Eucarinogammarus { genus: field("Род"), species: field("Вид"), ..Default::default() }
+    }
+}
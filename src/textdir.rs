@@ -0,0 +1,291 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::db::COLUMNS;
+use crate::error::DbError;
+use crate::sync::{self, SyncRecord};
+
+// Представление базы каталогом текстовых файлов для хранения в git:
+// database.toml с форматом и словарями и species/Род_вид.toml на каждую
+// запись вместе с её находками. Вывод детерминирован: ключи отсортированы,
+// пустые поля не пишутся, одна и та же база всегда даёт те же файлы.
+// Из каталога собирается новая база; журналы и надгробия не переносятся
+
+// Версия формата каталога
+pub const FORMAT: u32 = 1;
+
+const SPECIES_DIR: &str = "species";
+const DATABASE_FILE: &str = "database.toml";
+
+// Столбцы находки и её местонахождения, которые попадают в файл вида
+const SPECIMEN_COLUMNS: [&str; 8] = [
+    "occurrence_id",
+    "catalog_number",
+    "institution_code",
+    "basis_of_record",
+    "recorded_by",
+    "event_date",
+    "individual_count",
+    "source",
+];
+const LOCALITY_COLUMNS: [&str; 7] = [
+    "locality",
+    "country_code",
+    "water_body",
+    "decimal_latitude",
+    "decimal_longitude",
+    "min_depth_m",
+    "max_depth_m",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DatabaseFile {
+    format: u32,
+    // Утверждённые термины словарей: столбец -> термины
+    #[serde(default)]
+    vocabulary: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SpeciesFile {
+    uuid: String,
+    modified_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_by: Option<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    // Непустые столбцы находки и местонахождения
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    specimens: Vec<BTreeMap<String, String>>,
+}
+
+// Итог выгрузки или сборки
+#[derive(Debug, Clone, Default)]
+pub struct TextSummary {
+    pub records: usize,
+    pub specimens: usize,
+    // Файлы видов, которых больше нет в базе (только при выгрузке)
+    pub removed: usize,
+}
+
+// Выгрузка базы в каталог dir. Файлы видов, которых в базе уже нет,
+// удаляются, чтобы удаление записи было видно в git
+pub fn export(conn: &Connection, dir: &Path) -> Result<TextSummary, DbError> {
+    let species_dir = dir.join(SPECIES_DIR);
+    fs::create_dir_all(&species_dir)?;
+
+    let database = DatabaseFile { format: FORMAT, vocabulary: load_vocabulary(conn)? };
+    fs::write(dir.join(DATABASE_FILE), toml::to_string(&database)?)?;
+
+    let mut specimens = load_specimens(conn)?;
+    let mut summary = TextSummary::default();
+    let mut written = Vec::new();
+    for (name, record) in file_names(sync::load_sync_records(conn, None)?) {
+        let file = SpeciesFile {
+            specimens: specimens.remove(&record.uuid).unwrap_or_default(),
+            uuid: record.uuid,
+            modified_at: record.modified_at,
+            deleted_at: record.deleted_at,
+            deleted_by: record.deleted_by,
+            fields: record.fields.into_iter().filter(|(_, value)| !value.is_empty()).collect(),
+        };
+        summary.records += 1;
+        summary.specimens += file.specimens.len();
+        fs::write(species_dir.join(&name), toml::to_string(&file)?)?;
+        written.push(name);
+    }
+
+    for entry in fs::read_dir(&species_dir)? {
+        let path = entry?.path();
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if name.ends_with(".toml") && !written.contains(&name) {
+            fs::remove_file(&path)?;
+            summary.removed += 1;
+        }
+    }
+    Ok(summary)
+}
+
+// Заполнение пустой базы conn из каталога dir одной транзакцией
+pub fn import(conn: &Connection, dir: &Path) -> Result<TextSummary, DbError> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM Eucarinogammarus", [], |row| row.get(0))?;
+    if count > 0 {
+        return Err(DbError::Invalid("Собрать базу из каталога можно только в пустую базу".to_string()));
+    }
+
+    let database: DatabaseFile = read_toml(&dir.join(DATABASE_FILE))?;
+    if database.format > FORMAT {
+        return Err(DbError::Invalid(format!(
+            "Каталог {} выгружен более новой версией программы (формат {})",
+            dir.display(),
+            database.format
+        )));
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir.join(SPECIES_DIR))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|p| p.extension().is_some_and(|e| e == "toml"));
+    paths.sort();
+
+    let tx = conn.unchecked_transaction()?;
+    for (column, terms) in &database.vocabulary {
+        for term in terms {
+            tx.execute("INSERT OR IGNORE INTO Vocabulary (column_name, term) VALUES (?1, ?2)", params![column, term])?;
+        }
+    }
+
+    let mut summary = TextSummary::default();
+    for path in &paths {
+        let file: SpeciesFile = read_toml(path)?;
+        if let Some(column) = file.fields.keys().find(|c| !COLUMNS.contains(&c.as_str())) {
+            return Err(DbError::Parse(format!("{}: неизвестный столбец {}", path.display(), column)));
+        }
+        let record = SyncRecord {
+            uuid: file.uuid,
+            modified_at: file.modified_at,
+            deleted_at: file.deleted_at,
+            deleted_by: file.deleted_by,
+            fields: file.fields,
+        };
+        sync::insert(&tx, &record).map_err(|e| match e {
+            DbError::Constraint(_) => DbError::Parse(format!("{}: UUID {} встречается дважды", path.display(), record.uuid)),
+            e => e,
+        })?;
+        let taxon_id = tx.last_insert_rowid();
+        for specimen in &file.specimens {
+            insert_specimen(&tx, taxon_id, specimen)?;
+        }
+        summary.records += 1;
+        summary.specimens += file.specimens.len();
+    }
+
+    tx.commit()?;
+    Ok(summary)
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, DbError> {
+    let text = fs::read_to_string(path).map_err(|e| DbError::Parse(format!("{}: {}", path.display(), e)))?;
+    toml::from_str(&text).map_err(|e| DbError::Parse(format!("{}: {}", path.display(), e)))
+}
+
+// Имена файлов вида Род_вид.toml; записи с одинаковым названием
+// различаются номером в порядке UUID
+fn file_names(mut records: Vec<SyncRecord>) -> Vec<(String, SyncRecord)> {
+    let base = |record: &SyncRecord| {
+        let field = |column: &str| record.fields.get(column).map(|v| v.trim().to_string()).unwrap_or_default();
+        let name = format!("{} {}", field("Род"), field("Вид"));
+        let name: String = name.trim()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        if name.is_empty() { "без_названия".to_string() } else { name }
+    };
+    records.sort_by(|a, b| base(a).cmp(&base(b)).then_with(|| a.uuid.cmp(&b.uuid)));
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    records.into_iter()
+        .map(|record| {
+            let base = base(&record);
+            // Регистр не различается: на Windows и macOS это был бы один файл
+            let n = seen.entry(base.to_lowercase()).or_insert(0);
+            *n += 1;
+            let name = if *n == 1 { format!("{}.toml", base) } else { format!("{}-{}.toml", base, n) };
+            (name, record)
+        })
+        .collect()
+}
+
+fn load_vocabulary(conn: &Connection) -> Result<BTreeMap<String, Vec<String>>, DbError> {
+    let mut stmt = conn.prepare("SELECT column_name, term FROM Vocabulary ORDER BY column_name, term")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+    let mut vocabulary: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for row in rows {
+        let (column, term) = row?;
+        vocabulary.entry(column).or_default().push(term);
+    }
+    Ok(vocabulary)
+}
+
+// Находки по UUID вида, в каждом виде отсортированные по значениям
+fn load_specimens(conn: &Connection) -> Result<HashMap<String, Vec<BTreeMap<String, String>>>, DbError> {
+    let columns: Vec<String> = SPECIMEN_COLUMNS.iter().map(|c| format!("s.{}", c))
+        .chain(LOCALITY_COLUMNS.iter().map(|c| format!("COALESCE(l.{}, '')", c)))
+        .collect();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT e.uuid, {} FROM Specimen s
+         JOIN Eucarinogammarus e ON e.id = s.taxon_id
+         LEFT JOIN Locality l ON l.id = s.locality_id",
+        columns.join(", ")
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        let mut values = BTreeMap::new();
+        for (i, column) in SPECIMEN_COLUMNS.iter().chain(LOCALITY_COLUMNS.iter()).enumerate() {
+            let value: String = row.get(i + 1)?;
+            if !value.is_empty() {
+                values.insert(column.to_string(), value);
+            }
+        }
+        Ok((row.get::<_, String>(0)?, values))
+    })?;
+
+    let mut specimens: HashMap<String, Vec<BTreeMap<String, String>>> = HashMap::new();
+    for row in rows {
+        let (uuid, values) = row?;
+        specimens.entry(uuid).or_default().push(values);
+    }
+    for list in specimens.values_mut() {
+        list.sort();
+    }
+    Ok(specimens)
+}
+
+fn insert_specimen(conn: &Connection, taxon_id: i64, specimen: &BTreeMap<String, String>) -> Result<(), DbError> {
+    let get = |column: &str| specimen.get(column).cloned().unwrap_or_default();
+    if let Some(column) = specimen.keys().find(|c| !SPECIMEN_COLUMNS.contains(&c.as_str()) && !LOCALITY_COLUMNS.contains(&c.as_str())) {
+        return Err(DbError::InvalidColumn(column.clone()));
+    }
+
+    // Одинаковые местонахождения разных находок снова становятся одной строкой Locality
+    let locality: Vec<String> = LOCALITY_COLUMNS.iter().map(|c| get(c)).collect();
+    let locality_id = if locality.iter().all(|v| v.is_empty()) {
+        None
+    } else {
+        let conditions: Vec<String> = LOCALITY_COLUMNS.iter().enumerate().map(|(i, c)| format!("{} = ?{}", c, i + 1)).collect();
+        let existing: Option<i64> = conn.query_row(
+            &format!("SELECT id FROM Locality WHERE {}", conditions.join(" AND ")),
+            rusqlite::params_from_iter(&locality),
+            |row| row.get(0),
+        ).optional()?;
+        match existing {
+            Some(id) => Some(id),
+            None => {
+                let placeholders: Vec<String> = (1..=LOCALITY_COLUMNS.len()).map(|i| format!("?{}", i)).collect();
+                conn.execute(
+                    &format!("INSERT INTO Locality ({}) VALUES ({})", LOCALITY_COLUMNS.join(", "), placeholders.join(", ")),
+                    rusqlite::params_from_iter(&locality),
+                )?;
+                Some(conn.last_insert_rowid())
+            }
+        }
+    };
+
+    let mut values: Vec<rusqlite::types::Value> = vec![taxon_id.into(), locality_id.into()];
+    values.extend(SPECIMEN_COLUMNS.iter().map(|c| get(c).into()));
+    let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("?{}", i)).collect();
+    conn.execute(
+        &format!(
+            "INSERT INTO Specimen (taxon_id, locality_id, {}) VALUES ({})",
+            SPECIMEN_COLUMNS.join(", "),
+            placeholders.join(", ")
+        ),
+        rusqlite::params_from_iter(values),
+    )?;
+    Ok(())
+}
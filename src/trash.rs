@@ -0,0 +1,84 @@
+use rusqlite::{params, Connection};
+
+use crate::db::{Eucarinogammarus, record_from_row};
+use crate::error::DbError;
+
+// Запись в корзине вместе с временем и автором удаления
+#[derive(Debug, Clone)]
+pub struct TrashedRecord {
+    pub record: Eucarinogammarus,
+    pub deleted_at: String,
+    pub deleted_by: String,
+}
+
+// Перемещение записи в корзину; нет такой неудалённой записи — DbError::NotFound
+pub fn soft_delete(conn: &Connection, id: i32, user_name: &str) -> Result<(), DbError> {
+    let changed = conn.execute(
+        "UPDATE Eucarinogammarus SET deleted_at = datetime('now', 'localtime'), deleted_by = ?2
+         WHERE id = ?1 AND deleted_at IS NULL",
+        params![id, user_name],
+    )?;
+    found(changed, id)
+}
+
+// Восстановление записи из корзины
+pub fn restore(conn: &Connection, id: i32) -> Result<(), DbError> {
+    let changed = conn.execute(
+        "UPDATE Eucarinogammarus SET deleted_at = NULL, deleted_by = NULL
+         WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    found(changed, id)
+}
+
+// Окончательное удаление записи из корзины вместе с её находками
+pub fn purge(conn: &mut Connection, id: i32) -> Result<(), DbError> {
+    let tx = conn.transaction()?;
+    let changed = tx.execute(
+        "DELETE FROM Eucarinogammarus WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    found(changed, id)?;
+    tx.execute("DELETE FROM Specimen WHERE taxon_id = ?1", params![id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Запись из корзины по ID
+pub fn load_trashed(conn: &Connection, id: i32) -> Result<Option<TrashedRecord>, DbError> {
+    Ok(load_trash(conn)?.into_iter().find(|t| t.record.id == id))
+}
+
+// Содержимое корзины, последние удалённые сверху
+pub fn load_trash(conn: &Connection) -> Result<Vec<TrashedRecord>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, Код, Род, Вид, Размеры_мм, Тело, Окраска, Распространение,
+                Глубина_м, Вооруж_тела, Средний_ряд_I_VII, Средн_ряд_VIII_X, Сред_ряд_урозом,
+                Боковой_ряд, Краевой_ряд, Особен_воор, Эпимир_пласт, Верх_антенны,
+                Прид_жгутик, Нижн_антенны, Базип_III_V, Уроподы_III, Головн_сегм,
+                Глаза, Тельсон, deleted_at, deleted_by
+         FROM Eucarinogammarus WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+    )?;
+
+    let records = stmt.query_map([], |row| {
+        Ok(TrashedRecord {
+            record: record_from_row(row)?,
+            deleted_at: row.get(25)?,
+            deleted_by: row.get::<_, Option<String>>(26)?.unwrap_or_default(),
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for record in records {
+        result.push(record?);
+    }
+
+    Ok(result)
+}
+
+fn found(changed: usize, id: i32) -> Result<(), DbError> {
+    if changed == 0 {
+        return Err(DbError::NotFound(id));
+    }
+    Ok(())
+}
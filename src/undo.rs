@@ -0,0 +1,220 @@
+use rusqlite::Connection;
+
+use crate::db;
+use crate::trash;
+use crate::vocabulary;
+use crate::error::DbError;
+
+// Обратимая операция над таблицей Eucarinogammarus.
+// Удаление и отмена добавления работают через корзину, поэтому
+// повтор операции восстанавливает ту же запись с тем же ID
+#[derive(Debug, Clone)]
+pub enum Command {
+    Insert { id: i32, name: String },
+    Update { id: i32, column: String, old: String, new: String },
+    Delete { id: i32, name: String },
+    Restore { id: i32, name: String },
+    // Термины словаря столбца, исключённые и добавленные операцией
+    Terms { column: String, removed: Vec<String>, added: Vec<String> },
+    Bulk { description: String, commands: Vec<Command> },
+}
+
+impl Command {
+    // Описание для строки состояния
+    pub fn description(&self) -> String {
+        match self {
+            Command::Insert { name, .. } => format!("добавление {}", name),
+            Command::Update { id, column, .. } => format!("изменение {} записи {}", column, id),
+            Command::Delete { name, .. } => format!("удаление {}", name),
+            Command::Restore { name, .. } => format!("восстановление {}", name),
+            Command::Terms { column, .. } => format!("изменение словаря {}", column),
+            Command::Bulk { description, .. } => description.clone(),
+        }
+    }
+
+    // Изменения полей при отмене (reverting) или повторе операции
+    pub fn field_writes(&self, reverting: bool) -> Vec<FieldWrite> {
+        match self {
+            Command::Update { id, column, old, new } => {
+                let (expected, value) = if reverting { (new, old) } else { (old, new) };
+                vec![FieldWrite::new(*id, column, expected, value)]
+            }
+            Command::Bulk { commands, .. } => commands.iter().flat_map(|c| c.field_writes(reverting)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Затрагивает ли операция запись id
+    fn involves(&self, id: i32) -> bool {
+        match self {
+            Command::Insert { id: own, .. }
+            | Command::Update { id: own, .. }
+            | Command::Delete { id: own, .. }
+            | Command::Restore { id: own, .. } => *own == id,
+            Command::Terms { .. } => false,
+            Command::Bulk { commands, .. } => commands.iter().any(|c| c.involves(id)),
+        }
+    }
+
+    fn apply(&self, conn: &Connection, user_name: &str) -> Result<(), DbError> {
+        match self {
+            Command::Insert { id, .. } | Command::Restore { id, .. } => {
+                trash::restore(conn, *id)?;
+            }
+            Command::Update { id, column, old, new } => set_field(conn, *id, column, old, new)?,
+            Command::Delete { id, .. } => {
+                trash::soft_delete(conn, *id, user_name)?;
+            }
+            Command::Terms { column, removed, added } => set_terms(conn, column, removed, added)?,
+            Command::Bulk { commands, .. } => {
+                for command in commands {
+                    command.apply(conn, user_name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn revert(&self, conn: &Connection, user_name: &str) -> Result<(), DbError> {
+        match self {
+            Command::Insert { id, .. } | Command::Restore { id, .. } => {
+                trash::soft_delete(conn, *id, user_name)?;
+            }
+            Command::Update { id, column, old, new } => set_field(conn, *id, column, new, old)?,
+            Command::Delete { id, .. } => {
+                trash::restore(conn, *id)?;
+            }
+            Command::Terms { column, removed, added } => set_terms(conn, column, added, removed)?,
+            Command::Bulk { commands, .. } => {
+                for command in commands.iter().rev() {
+                    command.revert(conn, user_name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Изменение поля в составе операции: в поле должно быть значение expected,
+// вместо него записывается value
+#[derive(Debug, Clone)]
+pub struct FieldWrite {
+    pub id: i32,
+    pub column: String,
+    pub expected: String,
+    pub value: String,
+}
+
+impl FieldWrite {
+    pub fn new(id: i32, column: &str, expected: &str, value: &str) -> Self {
+        FieldWrite {
+            id,
+            column: column.to_string(),
+            expected: expected.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+// Стеки отмены и повтора; живут, пока открыто приложение
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+}
+
+impl UndoStack {
+    // Новая операция делает невозможным повтор отменённых
+    pub fn push(&mut self, command: Command) {
+        self.undo.push(command);
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_description(&self) -> Option<String> {
+        self.undo.last().map(Command::description)
+    }
+
+    pub fn redo_description(&self) -> Option<String> {
+        self.redo.last().map(Command::description)
+    }
+
+    // Операции, которые будут отменены и повторены следующими
+    pub fn next_undo(&self) -> Option<&Command> {
+        self.undo.last()
+    }
+
+    pub fn next_redo(&self) -> Option<&Command> {
+        self.redo.last()
+    }
+
+    // Отмена последней операции; возвращает её описание. Операция, которую
+    // отменить не удалось (например, запись уже удалена), убирается из стека,
+    // иначе она закрыла бы все операции под ней
+    pub fn undo(&mut self, conn: &Connection, user_name: &str) -> Result<Option<String>, DbError> {
+        let command = match self.undo.pop() {
+            Some(command) => command,
+            None => return Ok(None),
+        };
+
+        in_transaction(conn, |tx| command.revert(tx, user_name))?;
+
+        let description = command.description();
+        self.redo.push(command);
+        Ok(Some(description))
+    }
+
+    // Повтор последней отменённой операции; неудавшийся повтор, как и
+    // неудавшаяся отмена, убирается из стека
+    pub fn redo(&mut self, conn: &Connection, user_name: &str) -> Result<Option<String>, DbError> {
+        let command = match self.redo.pop() {
+            Some(command) => command,
+            None => return Ok(None),
+        };
+
+        in_transaction(conn, |tx| command.apply(tx, user_name))?;
+
+        let description = command.description();
+        self.undo.push(command);
+        Ok(Some(description))
+    }
+
+    // Удаление из обоих стеков операций над записью id, удалённой навсегда
+    pub fn forget(&mut self, id: i32) {
+        self.undo.retain(|c| !c.involves(id));
+        self.redo.retain(|c| !c.involves(id));
+    }
+}
+
+fn in_transaction<F>(conn: &Connection, f: F) -> Result<(), DbError>
+where
+    F: FnOnce(&Connection) -> Result<(), DbError>,
+{
+    let tx = conn.unchecked_transaction()?;
+    f(&tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Поле меняется, только если в нём то значение, которое оставила операция;
+// если его успел изменить другой пользователь — DbError::Conflict
+fn set_terms(conn: &Connection, column: &str, remove: &[String], add: &[String]) -> Result<(), DbError> {
+    for term in remove {
+        vocabulary::remove_term(conn, column, term)?;
+    }
+    for term in add {
+        vocabulary::add_term(conn, column, term)?;
+    }
+    Ok(())
+}
+
+fn set_field(conn: &Connection, id: i32, column: &str, expected: &str, value: &str) -> Result<(), DbError> {
+    db::update_field_expecting(conn, id, column, expected, value)
+}
@@ -1,39 +1,37 @@
 use eframe::egui;
 use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::{COLUMNS, COLUMN_LABELS};
+use crate::views::{autocomplete, field_errors};
+use eucarinogammarus_db::vocabulary::is_vocabulary_column;
 
 pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
     egui::ScrollArea::vertical().show(ui, |ui| {
         ui.heading("Добавление новой записи");
         
-        ui.horizontal(|ui| {
-            ui.label("Код:");
-            ui.text_edit_singleline(&mut app.new_record.code);
-        });
-        
-        ui.horizontal(|ui| {
-            ui.label("Род:");
-            ui.text_edit_singleline(&mut app.new_record.genus);
-        });
-        
-        ui.horizontal(|ui| {
-            ui.label("Вид:");
-            ui.text_edit_singleline(&mut app.new_record.species);
-        });
-        
-        ui.horizontal(|ui| {
-            ui.label("Размеры мм:");
-            ui.text_edit_singleline(&mut app.new_record.size_mm);
-        });
-        
-        ui.horizontal(|ui| {
-            ui.label("Тело:");
-            ui.text_edit_singleline(&mut app.new_record.body);
-        });
+        egui::Grid::new("add_grid")
+            .num_columns(2)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                for (i, (column, label)) in COLUMNS.iter().zip(COLUMN_LABELS).enumerate() {
+                    ui.label(format!("{}:", label));
+                    ui.vertical(|ui| {
+                        // Описательные признаки заполняются с подсказками из словаря
+                        if is_vocabulary_column(column) {
+                            app.load_vocabulary(column);
+                            autocomplete(ui, &mut app.new_record.values[i], &app.vocabularies[*column]);
+                        } else {
+                            ui.text_edit_singleline(&mut app.new_record.values[i]);
+                        }
+                        field_errors(ui, &app.form_errors, column);
+                    });
+                    ui.end_row();
+                }
+            });
         
         if ui.button("Добавить запись").clicked() {
             if let Err(e) = app.add_record() {
-                app.status_message = format!("Ошибка: {}", e);
+                app.report_error(e);
             }
         }
     });
-}
\ No newline at end of file
+}
\ No newline at end of file
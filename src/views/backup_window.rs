@@ -0,0 +1,103 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+
+// Окно снимков базы: настройки, создание снимка вручную и восстановление
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    if !app.backup_form.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut settings_changed = false;
+    let mut create = false;
+    let mut select = None;
+    let mut restore = false;
+
+    egui::Window::new("Резервные копии")
+        .id(egui::Id::new("backup_window"))
+        .open(&mut open)
+        .default_width(550.0)
+        .show(ctx, |ui| {
+            let settings = &mut app.config.backup;
+            ui.collapsing("Автоматические снимки", |ui| {
+                settings_changed |= ui.checkbox(&mut settings.on_startup, "При запуске").changed();
+                settings_changed |= ui.checkbox(&mut settings.before_import, "Перед импортом").changed();
+                settings_changed |= ui.checkbox(&mut settings.before_bulk_edit, "Перед массовой правкой").changed();
+                ui.horizontal(|ui| {
+                    ui.label("Хранить снимков каждого вида:");
+                    settings_changed |= ui.add(egui::DragValue::new(&mut settings.keep).clamp_range(1..=100)).changed();
+                });
+                ui.label(format!("Каталог: {}", settings.directory(&app.database_path).display()));
+                ui.weak("Снимки, сделанные вручную, не удаляются");
+            });
+
+            if ui.button("Создать снимок").clicked() {
+                create = true;
+            }
+            ui.separator();
+
+            if app.backup_form.snapshots.is_empty() {
+                ui.label("Снимков пока нет");
+            }
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("snapshots_grid").striped(true).num_columns(3).show(ui, |ui| {
+                    for (i, snapshot) in app.backup_form.snapshots.iter().enumerate() {
+                        let selected = app.backup_form.selected == Some(i);
+                        if ui.selectable_label(selected, &snapshot.created).clicked() {
+                            select = Some(i);
+                        }
+                        ui.label(snapshot.reason.label());
+                        ui.label(format!("{:.1} КБ", snapshot.size as f64 / 1024.0));
+                        ui.end_row();
+                    }
+                });
+            });
+
+            // Предпросмотр: сколько записей окажется в базе после восстановления
+            if let Some(info) = &app.backup_form.preview {
+                ui.separator();
+                ui.label(format!("В снимке: записей {}, в корзине {}", info.records, info.trashed));
+                ui.label(format!("Сейчас: записей {}, в корзине {}", app.records.len(), app.trash.len()));
+
+                if app.backup_form.confirm_restore {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Текущая база будет заменена снимком. Её состояние сохранится в отдельный снимок, а история отмены очистится.",
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Восстановить").clicked() {
+                            restore = true;
+                        }
+                        if ui.button("Отмена").clicked() {
+                            app.backup_form.confirm_restore = false;
+                        }
+                    });
+                } else if ui.button("Восстановить…").clicked() {
+                    app.backup_form.confirm_restore = true;
+                }
+            }
+        });
+
+    if settings_changed {
+        if let Err(e) = app.config.save() {
+            app.report_error(e);
+        }
+    }
+
+    let result = if create {
+        app.create_snapshot()
+    } else if let Some(i) = select {
+        app.preview_snapshot(i)
+    } else if restore {
+        app.restore_snapshot()
+    } else {
+        Ok(())
+    };
+    if let Err(e) = result {
+        app.report_error(e);
+    }
+
+    if !open {
+        app.backup_form.open = false;
+    }
+}
@@ -0,0 +1,96 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::bulk::BulkMode;
+use eucarinogammarus_db::db::{COLUMNS, COLUMN_LABELS};
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Массовая правка");
+    
+    ui.horizontal(|ui| {
+        ui.label("Отбор записей (поиск):");
+        if ui.text_edit_singleline(&mut app.search_term).changed() {
+            app.bulk_form.preview.clear();
+        }
+    });
+    ui.label(format!("Записей в отборе: {}", app.filtered_records().len()));
+    
+    ui.horizontal(|ui| {
+        ui.label("Столбец:");
+        egui::ComboBox::from_id_source("bulk_column")
+            .selected_text(&app.bulk_form.column)
+            .show_ui(ui, |ui| {
+                for (column, label) in COLUMNS.iter().zip(COLUMN_LABELS) {
+                    ui.selectable_value(&mut app.bulk_form.column, column.to_string(), label);
+                }
+            });
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Действие:");
+        egui::ComboBox::from_id_source("bulk_mode")
+            .selected_text(app.bulk_form.mode.label())
+            .show_ui(ui, |ui| {
+                for mode in [BulkMode::Set, BulkMode::Replace, BulkMode::RegexReplace, BulkMode::Append] {
+                    ui.selectable_value(&mut app.bulk_form.mode, mode, mode.label());
+                }
+            });
+    });
+    
+    if matches!(app.bulk_form.mode, BulkMode::Replace | BulkMode::RegexReplace) {
+        ui.horizontal(|ui| {
+            ui.label("Найти:");
+            ui.text_edit_singleline(&mut app.bulk_form.find);
+        });
+    }
+    
+    ui.horizontal(|ui| {
+        let label = match app.bulk_form.mode {
+            BulkMode::Set => "Новое значение:",
+            BulkMode::Append => "Дописать:",
+            _ => "Заменить на:",
+        };
+        ui.label(label);
+        ui.text_edit_singleline(&mut app.bulk_form.value);
+    });
+    
+    ui.horizontal(|ui| {
+        if ui.button("Предпросмотр").clicked() {
+            if let Err(e) = app.preview_bulk_edit() {
+                app.report_error(e);
+            }
+        }
+        let can_apply = !app.bulk_form.preview.is_empty();
+        if ui.add_enabled(can_apply, egui::Button::new("Применить")).clicked() {
+            if let Err(e) = app.apply_bulk_edit() {
+                app.report_error(e);
+            }
+        }
+    });
+    
+    if app.bulk_form.preview.is_empty() {
+        return;
+    }
+    
+    ui.separator();
+    ui.label(format!("Будет изменено записей: {}", app.bulk_form.preview.len()));
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("bulk_preview")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.strong("ID");
+                ui.strong("Вид");
+                ui.strong("Было");
+                ui.strong("Станет");
+                ui.end_row();
+                
+                for change in &app.bulk_form.preview {
+                    ui.label(change.id.to_string());
+                    ui.label(&change.name);
+                    ui.add(egui::Label::new(&change.old).wrap(true));
+                    ui.add(egui::Label::new(&change.new).wrap(true));
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,121 @@
+use eframe::egui;
+use eframe::egui::text::LayoutJob;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::{Eucarinogammarus, COLUMNS, COLUMN_LABELS};
+use eucarinogammarus_db::diff::{diff_words, DiffOp};
+
+// Тексты длиннее этого числа символов сравниваются по словам
+const LONG_TEXT: usize = 40;
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Сравнение видов");
+
+    egui::SidePanel::left("compare_picker")
+        .resizable(true)
+        .default_width(250.0)
+        .show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Поиск:");
+                ui.text_edit_singleline(&mut app.search_term);
+            });
+            if ui.button("Снять выбор").clicked() {
+                app.compare_ids.clear();
+            }
+
+            let ids: Vec<(i32, String)> = app.filtered_records().iter()
+                .map(|r| (r.id, format!("{}: {} {}", r.id, r.genus, r.species)))
+                .collect();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (id, text) in ids {
+                    let mut checked = app.compare_ids.contains(&id);
+                    if ui.checkbox(&mut checked, text).changed() {
+                        if checked {
+                            app.compare_ids.push(id);
+                        } else {
+                            app.compare_ids.retain(|x| *x != id);
+                        }
+                    }
+                }
+            });
+        });
+
+    // Записи в порядке выбора; первая служит основой для сравнения текста
+    let selected: Vec<&Eucarinogammarus> = app.compare_ids.iter()
+        .filter_map(|id| app.records.iter().find(|r| r.id == *id))
+        .collect();
+
+    if selected.len() < 2 {
+        ui.label("Выберите две или более записи слева");
+        return;
+    }
+
+    egui::ScrollArea::both().show(ui, |ui| {
+        egui::Grid::new("compare_grid")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("");
+                for record in &selected {
+                    ui.strong(format!("{} {}", record.genus, record.species));
+                }
+                ui.end_row();
+
+                for (column, label) in COLUMNS.iter().zip(COLUMN_LABELS) {
+                    let values: Vec<&str> = selected.iter()
+                        .map(|r| r.field(column).unwrap_or("").trim())
+                        .collect();
+                    let differs = values.iter().any(|v| *v != values[0]);
+
+                    // Различающиеся признаки выделяются цветом
+                    if differs {
+                        ui.colored_label(ui.visuals().warn_fg_color, label);
+                    } else {
+                        ui.label(label);
+                    }
+
+                    for (i, value) in values.iter().enumerate() {
+                        let long = values[0].chars().count() > LONG_TEXT || value.chars().count() > LONG_TEXT;
+                        if i > 0 && differs && long {
+                            ui.label(diff_layout(ui, values[0], value));
+                        } else {
+                            ui.add(egui::Label::new(*value).wrap(true));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+// Текст с выделением добавленных и удалённых относительно первой записи слов
+fn diff_layout(ui: &egui::Ui, old: &str, new: &str) -> LayoutJob {
+    let font = egui::TextStyle::Body.resolve(ui.style());
+    let color = ui.visuals().text_color();
+    let mut job = LayoutJob::default();
+    job.wrap.max_width = 300.0;
+
+    for op in diff_words(old, new) {
+        let (text, format) = match op {
+            DiffOp::Same(text) => (text, egui::TextFormat::simple(font.clone(), color)),
+            DiffOp::Added(text) => (text, egui::TextFormat {
+                font_id: font.clone(),
+                color,
+                background: egui::Color32::from_rgba_unmultiplied(0, 160, 0, 90),
+                ..Default::default()
+            }),
+            DiffOp::Removed(text) => (text, egui::TextFormat {
+                font_id: font.clone(),
+                color: egui::Color32::from_rgb(200, 60, 60),
+                strikethrough: egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 60, 60)),
+                ..Default::default()
+            }),
+        };
+        if !job.text.is_empty() {
+            job.append(" ", 0.0, egui::TextFormat::simple(font.clone(), color));
+        }
+        job.append(&text, 0.0, format);
+    }
+
+    job
+}
@@ -0,0 +1,68 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+
+// Окно конфликта: правку или операцию не удалось выполнить, потому что
+// запись тем временем изменил другой пользователь
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    let conflict = match &app.conflict {
+        Some(conflict) => conflict,
+        None => return,
+    };
+
+    let mut open = true;
+    let mut choice = None;
+
+    egui::Window::new("Конфликт изменений")
+        .id(egui::Id::new("conflict_window"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Запись {} изменена после того, как вы начали её править.", conflict.name));
+            if let Some(operation) = &conflict.operation {
+                ui.label(format!("Операция «{}» не выполнена, база не изменена; данные перечитаны.", operation));
+            }
+            if let Some(entry) = &conflict.changed_by {
+                ui.weak(format!("Последнее изменение: {}, {}", entry.user_name, entry.timestamp));
+            }
+            ui.separator();
+
+            egui::Grid::new("conflict_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Столбец:");
+                ui.label(&conflict.column);
+                ui.end_row();
+                ui.label("Ваше значение:");
+                ui.label(&conflict.mine);
+                ui.end_row();
+                ui.label("Сейчас в базе:");
+                ui.label(&conflict.theirs);
+                ui.end_row();
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                // Операцию целиком можно только повторить по новым данным
+                if conflict.operation.is_some() {
+                    if ui.button("Закрыть").clicked() {
+                        choice = Some(false);
+                    }
+                    return;
+                }
+                if ui.button("Сохранить моё").clicked() {
+                    choice = Some(true);
+                }
+                if ui.button("Оставить как в базе").clicked() {
+                    choice = Some(false);
+                }
+            });
+        });
+
+    // Закрытие окна — то же, что отказ от своей правки
+    if !open {
+        choice = Some(false);
+    }
+    if let Some(keep_mine) = choice {
+        if let Err(e) = app.resolve_conflict(keep_mine) {
+            app.report_error(e);
+        }
+    }
+}
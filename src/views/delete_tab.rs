@@ -10,8 +10,35 @@ pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
     });
     
     if ui.button("Удалить запись").clicked() {
-        if let Err(e) = app.delete_record() {
-            app.status_message = format!("Ошибка: {}", e);
+        app.request_delete();
+    }
+    
+    ui.label("Удалённые записи попадают в корзину, откуда их можно восстановить.");
+    
+    // Окно подтверждения с родом и видом удаляемой записи
+    if let Some(id) = app.delete_confirm {
+        let name = app.record_name(id);
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new("Подтверждение удаления")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("Переместить запись {} в корзину?", name));
+                ui.horizontal(|ui| {
+                    confirmed = ui.button("Удалить").clicked();
+                    cancelled = ui.button("Отмена").clicked();
+                });
+            });
+        
+        if confirmed {
+            if let Err(e) = app.delete_record() {
+                app.report_error(e);
+            }
+        } else if cancelled {
+            app.delete_confirm = None;
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::{COLUMNS, COLUMN_LABELS};
+
+// Число видов в списке наиболее похожих
+const SIMILAR_COUNT: usize = 5;
+
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    let id = match app.detail_id {
+        Some(id) => id,
+        None => return,
+    };
+    
+    let record = match app.records.iter().find(|r| r.id == id) {
+        Some(record) => record.clone(),
+        None => {
+            app.detail_id = None;
+            return;
+        }
+    };
+    
+    let similar = app.similarity().most_similar(id, SIMILAR_COUNT);
+    let history = app.history(id);
+    let mut open = true;
+    let mut selected = None;
+    let mut revert = None;
+    
+    egui::Window::new(format!("{} {}", record.genus, record.species))
+        .id(egui::Id::new("detail_window"))
+        .open(&mut open)
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("detail_grid")
+                    .striped(true)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("ID");
+                        ui.label(record.id.to_string());
+                        ui.end_row();
+                        
+                        for (column, label) in COLUMNS.iter().zip(COLUMN_LABELS) {
+                            ui.label(label);
+                            ui.add(egui::Label::new(record.field(column).unwrap_or("")).wrap(true));
+                            ui.end_row();
+                        }
+                    });
+            });
+            
+            ui.separator();
+            ui.strong("Наиболее похожие виды");
+            for (other, distance) in &similar {
+                if let Some(r) = app.records.iter().find(|r| r.id == *other) {
+                    let text = format!("{} {} — сходство {:.0}%", r.genus, r.species, (1.0 - distance) * 100.0);
+                    if ui.link(text).clicked() {
+                        selected = Some(*other);
+                    }
+                }
+            }
+            
+            ui.separator();
+            egui::CollapsingHeader::new(format!("История изменений ({})", history.len()))
+                .id_source("detail_history")
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                        egui::Grid::new("history_grid")
+                            .striped(true)
+                            .spacing([10.0, 5.0])
+                            .show(ui, |ui| {
+                                for entry in &history {
+                                    ui.label(&entry.timestamp);
+                                    ui.label(&entry.user_name);
+                                    let action = match entry.action.as_str() {
+                                        "INSERT" => "создание",
+                                        "UPDATE" => "изменение",
+                                        "DELETE" => "удаление",
+                                        "TRASH" => "в корзину",
+                                        "RESTORE" => "восстановление",
+                                        "MERGE" => "объединение",
+                                        other => other,
+                                    };
+                                    ui.label(action);
+                                    ui.label(entry.column_name.as_deref().unwrap_or(""));
+                                    ui.add(egui::Label::new(format!(
+                                        "{} → {}",
+                                        entry.old_value.as_deref().unwrap_or("∅"),
+                                        entry.new_value.as_deref().unwrap_or("∅")
+                                    )).wrap(true));
+                                    if entry.action == "UPDATE" && ui.small_button("Откатить").clicked() {
+                                        revert = Some(entry.id);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                });
+        });
+    
+    if let Some(audit_id) = revert {
+        if let Err(e) = app.revert_change(audit_id) {
+            app.report_error(e);
+        }
+    }
+    
+    if !open {
+        app.detail_id = None;
+    } else if selected.is_some() {
+        app.detail_id = selected;
+    }
+}
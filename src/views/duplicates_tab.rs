@@ -0,0 +1,99 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::{Eucarinogammarus, COLUMNS, COLUMN_LABELS};
+use eucarinogammarus_db::duplicates::DuplicateKind;
+
+// Длина значения в ячейке выбора; полный текст виден во всплывающей подсказке
+const PREVIEW_CHARS: usize = 40;
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Поиск дубликатов");
+    
+    ui.horizontal(|ui| {
+        ui.label("Порог сходства:");
+        ui.add(egui::Slider::new(&mut app.duplicate_form.threshold, 0.5..=1.0));
+        if ui.button("Найти дубликаты").clicked() {
+            app.find_duplicates();
+        }
+    });
+    
+    ui.separator();
+    
+    let mut select = None;
+    egui::SidePanel::left("duplicate_groups")
+        .resizable(true)
+        .default_width(280.0)
+        .show_inside(ui, |ui| {
+            if app.duplicate_form.groups.is_empty() {
+                ui.label("Дубликаты не найдены");
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (i, group) in app.duplicate_form.groups.iter().enumerate() {
+                    let kind = match group.kind {
+                        DuplicateKind::Exact => "точный".to_string(),
+                        DuplicateKind::Near => format!("похожие, {:.0}%", group.score * 100.0),
+                    };
+                    let names: Vec<String> = group.ids.iter().map(|id| app.record_name(*id)).collect();
+                    let text = format!("[{}] {}", kind, names.join(", "));
+                    if ui.selectable_label(app.duplicate_form.selected == Some(i), text).clicked() {
+                        select = Some(i);
+                    }
+                }
+            });
+        });
+    
+    if let Some(i) = select {
+        app.select_duplicate_group(i);
+    }
+    
+    let group = match app.duplicate_form.selected.and_then(|i| app.duplicate_form.groups.get(i)) {
+        Some(group) => group.clone(),
+        None => return,
+    };
+    
+    let records: Vec<Eucarinogammarus> = group.ids.iter()
+        .filter_map(|id| app.records.iter().find(|r| r.id == *id).cloned())
+        .collect();
+    
+    ui.label("Выберите оставляемую запись и значение каждого поля:");
+    
+    let mut merge = false;
+    egui::ScrollArea::both().show(ui, |ui| {
+        egui::Grid::new("merge_grid")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Оставить");
+                for record in &records {
+                    ui.radio_value(&mut app.duplicate_form.keep_id, record.id, format!("ID {}", record.id));
+                }
+                ui.end_row();
+                
+                for (c, (column, label)) in COLUMNS.iter().zip(COLUMN_LABELS).enumerate() {
+                    let values: Vec<&str> = records.iter().map(|r| r.field(column).unwrap_or("")).collect();
+                    if values.iter().any(|v| *v != values[0]) {
+                        ui.colored_label(ui.visuals().warn_fg_color, label);
+                    } else {
+                        ui.label(label);
+                    }
+                    
+                    for (record, value) in records.iter().zip(&values) {
+                        let short: String = value.chars().take(PREVIEW_CHARS).collect();
+                        let text = if short.len() < value.len() { format!("{}…", short) } else { short };
+                        ui.radio_value(&mut app.duplicate_form.choices[c], record.id, text)
+                            .on_hover_text(*value);
+                    }
+                    ui.end_row();
+                }
+            });
+        
+        ui.add_space(10.0);
+        merge = ui.button("Объединить").clicked();
+    });
+    
+    if merge {
+        if let Err(e) = app.merge_duplicates() {
+            app.report_error(e);
+        }
+    }
+}
@@ -1,12 +1,27 @@
 use eframe::egui;
 use crate::app::EucarinogammarusApp;
+use crate::views::{autocomplete, field_errors};
+use eucarinogammarus_db::vocabulary::is_vocabulary_column;
 
 pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
     ui.heading("Редактирование записи");
     
     ui.horizontal(|ui| {
         ui.label("ID записи:");
-        ui.text_edit_singleline(&mut app.edit_id);
+        // Версия запоминается при выборе записи: если до сохранения её
+        // изменит другой пользователь, откроется окно конфликта
+        if ui.text_edit_singleline(&mut app.edit_id).changed() {
+            app.edit_version = app.edit_id.trim().parse::<i32>().ok()
+                .and_then(|id| app.versions.get(&id).map(|version| (id, *version)));
+        }
+        
+        // Какая запись будет изменена
+        if let Ok(id) = app.edit_id.trim().parse::<i32>() {
+            match app.records.iter().find(|r| r.id == id) {
+                Some(record) => ui.label(format!("{} {}", record.genus, record.species)),
+                None => ui.colored_label(ui.visuals().error_fg_color, "запись не найдена"),
+            };
+        }
     });
     
     ui.horizontal(|ui| {
@@ -43,12 +58,19 @@ pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
     
     ui.horizontal(|ui| {
         ui.label("Новое значение:");
-        ui.text_edit_singleline(&mut app.edit_value);
+        if is_vocabulary_column(&app.edit_column) {
+            let column = app.edit_column.clone();
+            app.load_vocabulary(&column);
+            autocomplete(ui, &mut app.edit_value, &app.vocabularies[&column]);
+        } else {
+            ui.text_edit_singleline(&mut app.edit_value);
+        }
     });
+    field_errors(ui, &app.form_errors, &app.edit_column);
     
     if ui.button("Обновить запись").clicked() {
         if let Err(e) = app.edit_record() {
-            app.status_message = format!("Ошибка: {}", e);
+            app.report_error(e);
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::crypto;
+
+// Окно шифрования текущей базы: включение, смена пароля и снятие
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    if !app.encryption_form.open {
+        return;
+    }
+
+    let encrypted = app.db.lock().map(|db| db.is_encrypted()).unwrap_or(false);
+    let mut open = true;
+    let mut set: Option<Option<String>> = None;
+
+    egui::Window::new("Шифрование")
+        .id(egui::Id::new("encryption_window"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            if !crypto::ENABLED {
+                ui.label("Программа собрана без поддержки шифрования (функция encryption).");
+                return;
+            }
+
+            ui.label(if encrypted { "База зашифрована." } else { "База не зашифрована." });
+            ui.weak("Пароль нигде не сохраняется: без него базу открыть нельзя.");
+            ui.weak("Выгрузки (CSV, Darwin Core, текстовый каталог, наборы изменений) не шифруются.");
+            ui.separator();
+
+            let form = &mut app.encryption_form;
+            egui::Grid::new("encryption_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Новый пароль:");
+                ui.add(egui::TextEdit::singleline(&mut form.password).password(true));
+                ui.end_row();
+                ui.label("Повторите пароль:");
+                ui.add(egui::TextEdit::singleline(&mut form.confirm).password(true));
+                ui.end_row();
+            });
+
+            let matching = !form.password.is_empty() && form.password == form.confirm;
+            if !form.confirm.is_empty() && form.password != form.confirm {
+                ui.colored_label(ui.visuals().error_fg_color, "Пароли не совпадают");
+            }
+            ui.horizontal(|ui| {
+                let label = if encrypted { "Сменить пароль" } else { "Зашифровать" };
+                if ui.add_enabled(matching, egui::Button::new(label)).clicked() {
+                    set = Some(Some(form.password.clone()));
+                }
+                if encrypted && ui.button("Снять шифрование").clicked() {
+                    set = Some(None);
+                }
+            });
+        });
+
+    if let Some(password) = set {
+        if let Err(e) = app.set_password(password.as_deref()) {
+            app.report_error(e);
+        }
+    }
+    if !open {
+        app.encryption_form.open = false;
+    }
+}
@@ -0,0 +1,52 @@
+use eframe::egui;
+use std::time::Duration;
+use crate::app::EucarinogammarusApp;
+
+// Окно журнала ошибок за сеанс; новые сверху
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    if !app.show_error_log {
+        return;
+    }
+
+    let mut open = true;
+    let mut clear = false;
+
+    egui::Window::new("Журнал ошибок")
+        .id(egui::Id::new("error_log"))
+        .open(&mut open)
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            if ui.button("Очистить").clicked() {
+                clear = true;
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for entry in app.error_log.iter().rev() {
+                    ui.horizontal(|ui| {
+                        ui.weak(age(entry.time.elapsed()));
+                        ui.strong(entry.title);
+                    });
+                    ui.colored_label(ui.visuals().error_fg_color, &entry.message);
+                    ui.separator();
+                }
+            });
+        });
+
+    if clear {
+        app.error_log.clear();
+        app.status_message.clear();
+    }
+    if !open || clear {
+        app.show_error_log = false;
+    }
+}
+
+// Сколько времени прошло с ошибки
+fn age(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0..=59 => "только что".to_string(),
+        s @ 60..=3599 => format!("{} мин назад", s / 60),
+        s => format!("{} ч назад", s / 3600),
+    }
+}
@@ -0,0 +1,73 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.heading("Экспорт в Darwin Core Archive");
+        
+        ui.horizontal(|ui| {
+            ui.label("Файл архива:");
+            ui.text_edit_singleline(&mut app.export_form.file_path);
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Файл сопоставления (CSV, необязательно):");
+            ui.text_edit_singleline(&mut app.export_form.mapping_path);
+        });
+        
+        ui.separator();
+        ui.label("Метаданные (eml.xml)");
+        
+        ui.horizontal(|ui| {
+            ui.label("Название набора:");
+            ui.text_edit_singleline(&mut app.export_form.options.title);
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Автор:");
+            ui.text_edit_singleline(&mut app.export_form.options.creator);
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Язык:");
+            ui.text_edit_singleline(&mut app.export_form.options.language);
+        });
+        
+        ui.label("Описание:");
+        ui.text_edit_multiline(&mut app.export_form.options.description);
+        
+        if ui.button("Экспортировать").clicked() {
+            if let Err(e) = app.export_dwca() {
+                app.report_error(e);
+            }
+        }
+        
+        // Нарушения, найденные при проверке
+        if !app.export_form.issues.is_empty() {
+            ui.separator();
+            ui.label("Исправьте записи перед экспортом:");
+            for issue in &app.export_form.issues {
+                ui.colored_label(egui::Color32::RED, issue);
+            }
+        }
+        
+        ui.separator();
+        ui.heading("Морфологическое сходство");
+        
+        ui.horizontal(|ui| {
+            ui.label("Матрица расстояний (CSV):");
+            ui.text_edit_singleline(&mut app.export_form.matrix_path);
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Дендрограмма UPGMA (Newick):");
+            ui.text_edit_singleline(&mut app.export_form.newick_path);
+        });
+        
+        if ui.button("Сохранить матрицу и дендрограмму").clicked() {
+            if let Err(e) = app.export_similarity() {
+                app.report_error(e);
+            }
+        }
+    });
+}
@@ -0,0 +1,140 @@
+use eframe::egui;
+use std::path::PathBuf;
+use crate::app::{EucarinogammarusApp, FileAction, FileForm};
+
+// Меню «Файл»: открытие, создание и копирование базы, недавние базы
+pub fn menu(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    let mut open_recent: Option<PathBuf> = None;
+    let mut open_backups = false;
+    let mut open_sync = false;
+
+    ui.menu_button("Файл", |ui| {
+        for (action, label) in [
+            (FileAction::Open, "Открыть…"),
+            (FileAction::New, "Создать…"),
+            (FileAction::SaveAs, "Сохранить как…"),
+            (FileAction::ExportText, "Выгрузить в каталог…"),
+            (FileAction::FromText, "Собрать из каталога…"),
+        ] {
+            if ui.button(label).clicked() {
+                app.file_form = FileForm {
+                    action: Some(action),
+                    path: match action {
+                        FileAction::Open => app.database_path.display().to_string(),
+                        _ => String::new(),
+                    },
+                    directory: String::new(),
+                };
+                ui.close_menu();
+            }
+        }
+
+        if ui.button("Сравнить с базой…").clicked() {
+            app.merge_form.open = true;
+            ui.close_menu();
+        }
+        if ui.button("Резервные копии…").clicked() {
+            open_backups = true;
+            ui.close_menu();
+        }
+        if ui.button("Синхронизация…").clicked() {
+            open_sync = true;
+            ui.close_menu();
+        }
+        if ui.button("Шифрование…").clicked() {
+            app.encryption_form.open = true;
+            ui.close_menu();
+        }
+
+        ui.separator();
+        ui.menu_button("Недавние", |ui| {
+            if app.config.recent_databases.is_empty() {
+                ui.label("Список пуст");
+            }
+            for path in &app.config.recent_databases {
+                if ui.button(path.display().to_string()).clicked() {
+                    open_recent = Some(path.clone());
+                    ui.close_menu();
+                }
+            }
+        });
+    });
+
+    if open_backups {
+        app.backup_form.open = true;
+        if let Err(e) = app.load_snapshots() {
+            app.report_error(e);
+        }
+    }
+
+    if open_sync {
+        app.sync_form.open = true;
+        if let Err(e) = app.load_sync_log() {
+            app.report_error(e);
+        }
+    }
+
+    if let Some(path) = open_recent {
+        if let Err(e) = app.open_database(&path) {
+            app.report_error(e);
+        }
+    }
+}
+
+// Окно ввода пути для выбранного действия меню
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    let action = match app.file_form.action {
+        Some(action) => action,
+        None => return,
+    };
+
+    let (title, button, hint) = match action {
+        FileAction::Open => ("Открыть базу", "Открыть", "Путь к существующему файлу базы"),
+        FileAction::New => ("Создать базу", "Создать", "Путь к новому файлу; база создаётся пустой"),
+        FileAction::SaveAs => ("Сохранить базу как", "Сохранить", "Путь к новому файлу; дальше работа идёт с копией"),
+        FileAction::ExportText => (
+            "Выгрузить в каталог",
+            "Выгрузить",
+            "Каталог для текстовых файлов (по файлу на вид); удобно хранить в git",
+        ),
+        FileAction::FromText => ("Собрать из каталога", "Собрать", "Путь к новому файлу базы"),
+    };
+
+    let mut open = true;
+    let mut run = false;
+    let mut cancel = false;
+
+    egui::Window::new(title)
+        .id(egui::Id::new("file_dialog"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            if action == FileAction::FromText {
+                ui.label("Каталог, выгруженный командой «Выгрузить в каталог»");
+                ui.add(egui::TextEdit::singleline(&mut app.file_form.directory).desired_width(400.0));
+            }
+            ui.label(hint);
+            let response = ui.add(egui::TextEdit::singleline(&mut app.file_form.path).desired_width(400.0));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                run = true;
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(button).clicked() {
+                    run = true;
+                }
+                if ui.button("Отмена").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if run {
+        if let Err(e) = app.run_file_action() {
+            app.report_error(e);
+        }
+    }
+    if !open || cancel {
+        app.file_form = FileForm::default();
+    }
+}
@@ -0,0 +1,106 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::dwca::import::{CoreKind, Decision};
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Импорт Darwin Core");
+    
+    ui.horizontal(|ui| {
+        ui.label("Файл (txt, csv или zip):");
+        ui.text_edit_singleline(&mut app.import_form.file_path);
+        if ui.button("Прочитать").clicked() {
+            if let Err(e) = app.read_import() {
+                app.report_error(e);
+            }
+        }
+    });
+    
+    let records = &app.records;
+    let pending = match &mut app.import_form.pending {
+        Some(pending) => pending,
+        None => return,
+    };
+    
+    ui.separator();
+    let kind = match pending.table.kind {
+        CoreKind::Occurrence => "находки",
+        CoreKind::Taxon => "чеклист",
+    };
+    ui.label(format!("Источник: {} ({}), строк: {}", pending.table.source, kind, pending.table.rows.len()));
+    if pending.unnamed > 0 {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("Строк без рода (не импортируются): {}", pending.unnamed));
+    }
+    
+    let mut apply = false;
+    let mut cancel = false;
+    ui.horizontal(|ui| {
+        apply = ui.button("Применить импорт").clicked();
+        cancel = ui.button("Отмена").clicked();
+    });
+    
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("import_grid")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Род");
+                ui.label("Вид");
+                ui.label("Строк");
+                ui.label("Состояние");
+                ui.label("Действие");
+                ui.end_row();
+                
+                for (i, group) in pending.groups.iter_mut().enumerate() {
+                    ui.label(&group.genus);
+                    ui.label(&group.species);
+                    ui.label(group.rows.len().to_string());
+                    
+                    // Неоднозначные и новые названия выделяются для проверки
+                    if group.is_ambiguous() {
+                        ui.colored_label(egui::Color32::RED, format!("неоднозначно ({})", group.candidates.len()));
+                    } else if group.is_new() {
+                        ui.colored_label(egui::Color32::YELLOW, "новое название");
+                    } else {
+                        ui.label("совпадение");
+                    }
+                    
+                    let selected = match group.decision {
+                        Decision::Assign(id) => records.iter()
+                            .find(|r| r.id == id)
+                            .map(|r| format!("{}: {} {}", r.id, r.genus, r.species))
+                            .unwrap_or_else(|| id.to_string()),
+                        Decision::CreateTaxon => "Создать новый вид".to_string(),
+                        Decision::Skip => "Пропустить".to_string(),
+                    };
+                    
+                    egui::ComboBox::from_id_source(("import_decision", i))
+                        .selected_text(selected)
+                        .width(300.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut group.decision, Decision::Skip, "Пропустить");
+                            ui.selectable_value(&mut group.decision, Decision::CreateTaxon, "Создать новый вид");
+                            ui.separator();
+                            // Сначала найденные кандидаты, затем все остальные записи
+                            for record in records.iter().filter(|r| group.candidates.contains(&r.id)) {
+                                let text = format!("{}: {} {}", record.id, record.genus, record.species);
+                                ui.selectable_value(&mut group.decision, Decision::Assign(record.id), text);
+                            }
+                            ui.separator();
+                            for record in records.iter().filter(|r| !group.candidates.contains(&r.id)) {
+                                let text = format!("{}: {} {}", record.id, record.genus, record.species);
+                                ui.selectable_value(&mut group.decision, Decision::Assign(record.id), text);
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+    });
+    
+    if cancel {
+        app.import_form.pending = None;
+    } else if apply {
+        if let Err(e) = app.apply_import() {
+            app.report_error(e);
+        }
+    }
+}
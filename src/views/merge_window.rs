@@ -0,0 +1,113 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::merge::ChangeKind;
+
+// Окно сравнения с другим файлом базы и выбора переносимых отличий
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    if !app.merge_form.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut compare = false;
+    let mut apply = false;
+
+    egui::Window::new("Сравнение с другой базой")
+        .id(egui::Id::new("merge_window"))
+        .open(&mut open)
+        .default_width(650.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Файл базы:");
+                ui.add(egui::TextEdit::singleline(&mut app.merge_form.path).desired_width(400.0));
+                if ui.button("Сравнить").clicked() {
+                    compare = true;
+                }
+            });
+
+            let pending = match &mut app.merge_form.pending {
+                Some(pending) => pending,
+                None => {
+                    ui.label("Записи сопоставляются по роду и виду. Файл, с которым идёт сравнение, не изменяется.");
+                    return;
+                }
+            };
+
+            ui.separator();
+            ui.label(format!(
+                "Только в файле: {}, только в текущей базе: {}, изменено: {}, без изменений: {}",
+                pending.count(ChangeKind::Added),
+                pending.count(ChangeKind::Removed),
+                pending.count(ChangeKind::Changed),
+                pending.unchanged
+            ));
+            ui.horizontal(|ui| {
+                ui.label("Конфликты:");
+                if ui.button("оставить значения текущей базы").clicked() {
+                    pending.prefer_source(false);
+                }
+                if ui.button("взять значения из файла").clicked() {
+                    pending.prefer_source(true);
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(450.0).show(ui, |ui| {
+                for (i, change) in pending.changes.iter_mut().enumerate() {
+                    let name = change.name();
+                    match change.kind {
+                        ChangeKind::Added => {
+                            ui.checkbox(&mut change.apply, format!("+ {} — добавить", name));
+                        }
+                        ChangeKind::Removed => {
+                            ui.checkbox(&mut change.apply, format!("− {} — переместить в корзину", name));
+                        }
+                        ChangeKind::Changed => {
+                            let title = match change.conflicts() {
+                                0 => format!("~ {}", name),
+                                n => format!("~ {} (конфликтов: {})", name, n),
+                            };
+                            egui::CollapsingHeader::new(title).id_source(("merge_change", i)).show(ui, |ui| {
+                                egui::Grid::new(("merge_fields", i)).num_columns(3).striped(true).show(ui, |ui| {
+                                    ui.strong("Поле");
+                                    ui.strong("Текущая база");
+                                    ui.strong("Файл");
+                                    ui.end_row();
+                                    for field in &mut change.fields {
+                                        if field.is_conflict() {
+                                            ui.colored_label(ui.visuals().warn_fg_color, field.column);
+                                        } else {
+                                            ui.label(field.column);
+                                        }
+                                        ui.radio_value(&mut field.take_source, false, &field.target);
+                                        ui.radio_value(&mut field.take_source, true, &field.source);
+                                        ui.end_row();
+                                    }
+                                });
+                            });
+                        }
+                    }
+                }
+            });
+
+            ui.separator();
+            if ui.button("Применить выбранное").clicked() {
+                apply = true;
+            }
+        });
+
+    let result = if compare {
+        app.compare_database()
+    } else if apply {
+        app.apply_merge()
+    } else {
+        Ok(())
+    };
+    if let Err(e) = result {
+        app.report_error(e);
+    }
+
+    if !open {
+        app.merge_form.open = false;
+    }
+}
@@ -1,4 +1,66 @@
 pub mod view_tab;
 pub mod add_tab;
 pub mod edit_tab;
-pub mod delete_tab;
\ No newline at end of file
+pub mod delete_tab;
+pub mod export_tab;
+pub mod import_tab;
+pub mod compare_tab;
+pub mod detail_window;
+pub mod trash_tab;
+pub mod bulk_tab;
+pub mod duplicates_tab;
+pub mod quality_tab;
+pub mod vocabulary_tab;
+pub mod file_dialog;
+pub mod startup_screen;
+pub mod error_log;
+pub mod backup_window;
+pub mod merge_window;
+pub mod sync_window;
+pub mod password_window;
+pub mod encryption_window;
+pub mod conflict_window;
+
+use eframe::egui;
+use eucarinogammarus_db::db::FieldError;
+use eucarinogammarus_db::vocabulary::{self, Term};
+
+// Сообщения о нарушениях правил под полем формы
+pub fn field_errors(ui: &mut egui::Ui, errors: &[FieldError], column: &str) {
+    for error in errors.iter().filter(|e| e.column == column) {
+        ui.colored_label(ui.visuals().error_fg_color, &error.message);
+    }
+}
+
+// Однострочное поле с подсказками из словаря столбца
+pub fn autocomplete(ui: &mut egui::Ui, value: &mut String, terms: &[Term]) -> egui::Response {
+    let response = ui.text_edit_singleline(value);
+    let popup_id = response.id.with("autocomplete");
+    if response.has_focus() {
+        ui.memory_mut(|m| m.open_popup(popup_id));
+    }
+    if !ui.memory(|m| m.is_popup_open(popup_id)) {
+        return response;
+    }
+
+    let suggestions = vocabulary::suggest(terms, value, 8);
+    if suggestions.is_empty() {
+        return response;
+    }
+
+    let mut chosen = None;
+    egui::popup_below_widget(ui, popup_id, &response, |ui| {
+        ui.set_min_width(200.0);
+        for term in suggestions {
+            if ui.selectable_label(false, term).clicked() {
+                chosen = Some(term.to_string());
+            }
+        }
+    });
+
+    if let Some(term) = chosen {
+        *value = term;
+        ui.memory_mut(|m| m.close_popup());
+    }
+    response
+}
\ No newline at end of file
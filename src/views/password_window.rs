@@ -0,0 +1,45 @@
+use eframe::egui;
+use crate::app::{EucarinogammarusApp, PasswordForm};
+
+// Запрос пароля при открытии зашифрованной базы
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    let path = match &app.password_form.path {
+        Some(path) => path.clone(),
+        None => return,
+    };
+
+    let mut open = true;
+    let mut unlock = false;
+    let mut cancel = false;
+
+    egui::Window::new("Пароль базы")
+        .id(egui::Id::new("password_window"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(format!("База {} зашифрована", path.display()));
+            let response = ui.add(egui::TextEdit::singleline(&mut app.password_form.password).password(true));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                unlock = true;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Открыть").clicked() {
+                    unlock = true;
+                }
+                if ui.button("Отмена").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if unlock {
+        let password = std::mem::take(&mut app.password_form.password);
+        match app.open_encrypted(&path, &password) {
+            Ok(()) => app.password_form = PasswordForm::default(),
+            Err(e) => app.report_error(e),
+        }
+    }
+    if !open || cancel {
+        app.password_form = PasswordForm::default();
+    }
+}
@@ -0,0 +1,63 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::{COLUMNS, COLUMN_LABELS};
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Качество данных");
+
+    // Отчёт строится при первом открытии вкладки и после каждого изменения данных
+    if app.quality_report.is_none() || ui.button("Проверить заново").clicked() {
+        if let Err(e) = app.check_quality() {
+            app.report_error(e);
+            return;
+        }
+    }
+
+    let report = match &app.quality_report {
+        Some(report) => report,
+        None => return,
+    };
+
+    if report.is_empty() {
+        ui.label("Все записи соответствуют правилам проверки");
+        return;
+    }
+
+    ui.label(format!("Нарушений: {}", report.len()));
+
+    let mut open = None;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("quality_grid")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.strong("ID");
+                ui.strong("Запись");
+                ui.strong("Поле");
+                ui.strong("Значение");
+                ui.strong("Нарушение");
+                ui.end_row();
+
+                for error in report {
+                    let id = error.record_id.unwrap_or(0);
+                    if ui.link(id.to_string()).clicked() {
+                        open = Some(id);
+                    }
+                    ui.label(app.record_name(id));
+                    let label = COLUMNS.iter()
+                        .position(|c| *c == error.column)
+                        .map(|i| COLUMN_LABELS[i])
+                        .unwrap_or(error.column.as_str());
+                    ui.label(label);
+                    ui.label(&error.value);
+                    ui.colored_label(ui.visuals().error_fg_color, &error.message);
+                    ui.end_row();
+                }
+            });
+    });
+
+    if open.is_some() {
+        app.detail_id = open;
+    }
+}
@@ -0,0 +1,106 @@
+use eframe::egui;
+use std::path::PathBuf;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::crypto;
+use eucarinogammarus_db::DbError;
+
+// Экран вместо вкладок, если базу не удалось открыть при запуске
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    let startup = match &mut app.startup {
+        Some(startup) => startup,
+        None => return,
+    };
+
+    ui.heading("Не удалось открыть базу данных");
+    ui.add_space(10.0);
+    ui.colored_label(ui.visuals().error_fg_color, startup.error.to_string());
+    ui.label(hint(&startup.error));
+    ui.add_space(10.0);
+
+    // Действие выполняется после отрисовки: ему нужен весь app
+    enum Action {
+        Retry,
+        Unlock(PathBuf, String),
+        Open(PathBuf),
+        New(PathBuf),
+        Seed(PathBuf, PathBuf),
+    }
+    let mut action = None;
+
+    if matches!(startup.error, DbError::Encrypted(_)) && crypto::ENABLED {
+        ui.horizontal(|ui| {
+            ui.label("Пароль:");
+            let response = ui.add(egui::TextEdit::singleline(&mut startup.password).password(true));
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Открыть").clicked() || enter {
+                action = Some(Action::Unlock(startup.path.clone(), startup.password.clone()));
+            }
+        });
+    } else if ui.button("Повторить").clicked() {
+        action = Some(Action::Retry);
+    }
+    ui.separator();
+
+    egui::Grid::new("startup_grid")
+        .num_columns(3)
+        .spacing([10.0, 10.0])
+        .show(ui, |ui| {
+            ui.label("Открыть другую базу:");
+            ui.add(egui::TextEdit::singleline(&mut startup.open_path).desired_width(350.0));
+            if ui.button("Открыть").clicked() {
+                action = Some(Action::Open(PathBuf::from(startup.open_path.trim())));
+            }
+            ui.end_row();
+
+            ui.label("Создать пустую базу:");
+            ui.add(egui::TextEdit::singleline(&mut startup.new_path).desired_width(350.0));
+            if ui.button("Создать").clicked() {
+                action = Some(Action::New(PathBuf::from(startup.new_path.trim())));
+            }
+            ui.end_row();
+
+            // Заполнение из CSV возможно только для ещё не созданного файла
+            if !startup.path.exists() {
+                ui.label(format!("Заполнить {} из CSV:", startup.path.display()));
+                ui.add(egui::TextEdit::singleline(&mut startup.csv_path).desired_width(350.0));
+                if ui.button("Импортировать").clicked() {
+                    action = Some(Action::Seed(startup.path.clone(), PathBuf::from(startup.csv_path.trim())));
+                }
+                ui.end_row();
+            }
+        });
+
+    if !app.status_message.is_empty() {
+        ui.separator();
+        ui.label(&app.status_message);
+    }
+
+    let result = match action {
+        Some(Action::Retry) => {
+            let path = startup.path.clone();
+            let csv_path = PathBuf::from(startup.csv_path.trim());
+            app.seed_database(&path, &csv_path)
+        }
+        Some(Action::Unlock(path, password)) => app.open_encrypted(&path, &password),
+        Some(Action::Open(path)) => app.open_database(&path),
+        Some(Action::New(path)) => app.new_database(&path),
+        Some(Action::Seed(path, csv_path)) => app.seed_database(&path, &csv_path),
+        None => Ok(()),
+    };
+    if let Err(e) = result {
+        app.report_error(e);
+    }
+}
+
+// Что можно сделать при данной ошибке
+fn hint(error: &DbError) -> &'static str {
+    match error {
+        DbError::Locked(_) => "Закройте другую программу, работающую с этим файлом, и нажмите «Повторить».",
+        DbError::NotADatabase(_) => "Выберите другой файл базы или создайте новую.",
+        DbError::Encrypted(_) if crypto::ENABLED => "Введите пароль базы.",
+        DbError::Encrypted(_) => "Откройте базу программой, собранной с функцией encryption, или выберите другую базу.",
+        DbError::CannotOpen(_) => "Проверьте, что каталог существует и доступен для записи, или выберите другой путь.",
+        DbError::SeedMissing(_) => "Укажите CSV с исходными данными или создайте пустую базу.",
+        _ => "Выберите другую базу или создайте новую.",
+    }
+}
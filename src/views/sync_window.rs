@@ -0,0 +1,77 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+
+// Окно синхронизации: выгрузка и применение наборов изменений, журнал
+pub fn show(ctx: &egui::Context, app: &mut EucarinogammarusApp) {
+    if !app.sync_form.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut export = false;
+    let mut import = false;
+
+    egui::Window::new("Синхронизация")
+        .id(egui::Id::new("sync_window"))
+        .open(&mut open)
+        .default_width(550.0)
+        .show(ctx, |ui| {
+            ui.label("Наборы изменений переносят записи между копиями базы на разных компьютерах. \
+                      Из двух версий записи остаётся изменённая позже.");
+            ui.separator();
+
+            ui.heading("Выгрузка");
+            ui.horizontal(|ui| {
+                ui.label("Файл:");
+                ui.add(egui::TextEdit::singleline(&mut app.sync_form.export_path).desired_width(400.0));
+            });
+            ui.checkbox(&mut app.sync_form.incremental, "Только изменения после последней выгрузки");
+            let ready = !app.sync_form.export_path.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new("Выгрузить")).clicked() {
+                export = true;
+            }
+            ui.separator();
+
+            ui.heading("Применение");
+            ui.horizontal(|ui| {
+                ui.label("Файл:");
+                ui.add(egui::TextEdit::singleline(&mut app.sync_form.import_path).desired_width(400.0));
+            });
+            let ready = !app.sync_form.import_path.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new("Применить")).clicked() {
+                import = true;
+            }
+            ui.separator();
+
+            ui.heading("Журнал");
+            if app.sync_form.log.is_empty() {
+                ui.label("Синхронизаций пока не было");
+            }
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                egui::Grid::new("sync_log_grid").striped(true).num_columns(4).show(ui, |ui| {
+                    for entry in &app.sync_form.log {
+                        ui.label(&entry.timestamp);
+                        ui.label(if entry.direction == "export" { "выгрузка" } else { "применение" });
+                        ui.label(format!("записей {}", entry.records));
+                        ui.label(&entry.file);
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+    let result = if export {
+        app.export_changes()
+    } else if import {
+        app.import_changes()
+    } else {
+        Ok(())
+    };
+    if let Err(e) = result {
+        app.report_error(e);
+    }
+
+    if !open {
+        app.sync_form.open = false;
+    }
+}
@@ -0,0 +1,83 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Корзина");
+    
+    if app.trash.is_empty() {
+        ui.label("Корзина пуста");
+        return;
+    }
+    
+    let mut restore = None;
+    let mut purge = None;
+    
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("trash_grid")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.strong("ID");
+                ui.strong("Код");
+                ui.strong("Род");
+                ui.strong("Вид");
+                ui.strong("Удалено");
+                ui.strong("Кем");
+                ui.label("");
+                ui.label("");
+                ui.end_row();
+                
+                for item in &app.trash {
+                    ui.label(item.record.id.to_string());
+                    ui.label(&item.record.code);
+                    ui.label(&item.record.genus);
+                    ui.label(&item.record.species);
+                    ui.label(&item.deleted_at);
+                    ui.label(&item.deleted_by);
+                    if ui.button("Восстановить").clicked() {
+                        restore = Some(item.record.id);
+                    }
+                    if ui.button("Удалить навсегда").clicked() {
+                        purge = Some(item.record.id);
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+    
+    if let Some(id) = restore {
+        if let Err(e) = app.restore_record(id) {
+            app.report_error(e);
+        }
+    }
+    if purge.is_some() {
+        app.purge_confirm = purge;
+    }
+    
+    // Окончательное удаление необратимо, поэтому требует подтверждения
+    if let Some(id) = app.purge_confirm {
+        let name = app.record_name(id);
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new("Удалить навсегда")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("Запись {} будет удалена без возможности восстановления.", name));
+                ui.horizontal(|ui| {
+                    confirmed = ui.button("Удалить навсегда").clicked();
+                    cancelled = ui.button("Отмена").clicked();
+                });
+            });
+        
+        if confirmed {
+            if let Err(e) = app.purge_record() {
+                app.report_error(e);
+            }
+        } else if cancelled {
+            app.purge_confirm = None;
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use eframe::egui;
-use crate::app::{EucarinogammarusApp, SortDirection};
-use crate::db::load_records_sorted;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::SortDirection;
 
 pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
     ui.horizontal(|ui| {
@@ -39,8 +39,8 @@ pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
                                 } else {
                                      app.sort_column = db_column.to_string();
                                 }
-                                if let Ok(conn) = app.conn.lock() {
-                                    if let Ok(records) = load_records_sorted(&conn, &app.sort_column, app.sort_direction) {
+                                if let Ok(db) = app.db.lock() {
+                                    if let Ok(records) = db.records_sorted(&app.sort_column, app.sort_direction) {
                                         app.records = records;
                                     }
                                 }
@@ -76,9 +76,12 @@ pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
                     ui.end_row();
                 
     
-                    // Отображение записей
+                    // Отображение записей; щелчок по ID открывает карточку записи
+                    let mut opened = None;
                     for record in app.filtered_records() {
-                        ui.label(record.id.to_string());
+                        if ui.link(record.id.to_string()).clicked() {
+                            opened = Some(record.id);
+                        }
                         ui.label(&record.code);
                         ui.label(&record.genus);
                         ui.label(&record.species);
@@ -105,6 +108,9 @@ pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
                         ui.label(&record.telson);
                         ui.end_row();
                     }
+                    if opened.is_some() {
+                        app.detail_id = opened;
+                    }
                 });
         });
     });
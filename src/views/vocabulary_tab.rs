@@ -0,0 +1,125 @@
+use eframe::egui;
+use crate::app::EucarinogammarusApp;
+use eucarinogammarus_db::db::{COLUMNS, COLUMN_LABELS};
+use eucarinogammarus_db::vocabulary::{is_vocabulary_column, variant_key};
+
+pub fn render(ui: &mut egui::Ui, app: &mut EucarinogammarusApp) {
+    ui.heading("Словари признаков");
+
+    ui.horizontal(|ui| {
+        ui.label("Столбец:");
+        let before = app.vocabulary_form.column.clone();
+        egui::ComboBox::from_id_source("vocabulary_column")
+            .selected_text(&app.vocabulary_form.column)
+            .show_ui(ui, |ui| {
+                for (column, label) in COLUMNS.iter().zip(COLUMN_LABELS) {
+                    if is_vocabulary_column(column) {
+                        ui.selectable_value(&mut app.vocabulary_form.column, column.to_string(), label);
+                    }
+                }
+            });
+        if app.vocabulary_form.column != before {
+            app.vocabulary_form.selected.clear();
+            app.vocabulary_form.target.clear();
+        }
+    });
+
+    let column = app.vocabulary_form.column.clone();
+    if !is_vocabulary_column(&column) {
+        ui.label("Выберите столбец");
+        return;
+    }
+    app.load_vocabulary(&column);
+
+    ui.horizontal(|ui| {
+        ui.label("Новый термин:");
+        ui.text_edit_singleline(&mut app.vocabulary_form.new_term);
+        if ui.button("Добавить в словарь").clicked() {
+            if let Err(e) = app.add_vocabulary_term() {
+                app.report_error(e);
+            }
+        }
+    });
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label("Заменить выбранные варианты на:");
+        ui.text_edit_singleline(&mut app.vocabulary_form.target);
+        let can_merge = !app.vocabulary_form.selected.is_empty() && !app.vocabulary_form.target.trim().is_empty();
+        if ui.add_enabled(can_merge, egui::Button::new("Объединить")).clicked() {
+            if let Err(e) = app.merge_vocabulary_variants() {
+                app.report_error(e);
+            }
+        }
+        if ui.button("Снять выбор").clicked() {
+            app.vocabulary_form.selected.clear();
+        }
+    });
+
+    let terms = app.vocabularies[&column].clone();
+    let keys: Vec<String> = terms.iter().map(|t| variant_key(&t.term)).collect();
+
+    let mut remove = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("vocabulary_grid")
+            .striped(true)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("Термин");
+                ui.strong("Записей");
+                ui.strong("Утверждён");
+                ui.label("");
+                ui.label("");
+                ui.end_row();
+
+                for (term, key) in terms.iter().zip(&keys) {
+                    let form = &mut app.vocabulary_form;
+                    let mut checked = form.selected.contains(&term.term);
+                    if ui.checkbox(&mut checked, "").changed() {
+                        if checked {
+                            form.selected.insert(term.term.clone());
+                        } else {
+                            form.selected.remove(&term.term);
+                        }
+                    }
+
+                    if ui.link(&term.term).on_hover_text("Сделать итоговым термином").clicked() {
+                        form.target = term.term.clone();
+                    }
+                    ui.label(term.count.to_string());
+                    ui.label(if term.curated { "да" } else { "" });
+
+                    // Варианты, отличающиеся только регистром, пробелами или знаками препинания
+                    let variants = keys.iter().filter(|k| *k == key).count();
+                    if variants > 1 {
+                        if ui.button(format!("Варианты ({})", variants)).clicked() {
+                            form.selected = terms.iter()
+                                .zip(&keys)
+                                .filter(|(_, k)| *k == key)
+                                .map(|(t, _)| t.term.clone())
+                                .collect();
+                            form.target = term.term.clone();
+                        }
+                    } else {
+                        ui.label("");
+                    }
+
+                    if term.curated {
+                        if ui.button("Исключить").clicked() {
+                            remove = Some(term.term.clone());
+                        }
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    if let Some(term) = remove {
+        if let Err(e) = app.remove_vocabulary_term(&term) {
+            app.report_error(e);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+
+use crate::bulk::{self, BulkChange};
+use crate::db::{Eucarinogammarus, COLUMNS};
+use crate::undo::Command;
+use crate::error::DbError;
+
+// Столбцы со свободным или числовым значением словаря не имеют
+const FREE_COLUMNS: [&str; 4] = ["Код", "Вид", "Размеры_мм", "Глубина_м"];
+
+// Термин словаря: утверждённый вручную и/или встречающийся в записях
+#[derive(Debug, Clone)]
+pub struct Term {
+    pub term: String,
+    pub count: usize,
+    pub curated: bool,
+}
+
+pub fn is_vocabulary_column(column: &str) -> bool {
+    COLUMNS.contains(&column) && !FREE_COLUMNS.contains(&column)
+}
+
+// Словарь столбца: утверждённые термины и различающиеся значения записей,
+// сначала самые частые
+pub fn load_terms(conn: &Connection, column: &str) -> Result<Vec<Term>, DbError> {
+    if !is_vocabulary_column(column) {
+        return Err(DbError::Invalid(format!("Для столбца {} словарь не ведётся", column)));
+    }
+
+    let mut terms: BTreeMap<String, Term> = BTreeMap::new();
+
+    let query = format!(
+        "SELECT TRIM({0}), COUNT(*) FROM Eucarinogammarus
+        WHERE deleted_at IS NULL AND TRIM(COALESCE({0}, '')) <> ''
+        GROUP BY TRIM({0})",
+        column
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+    for row in rows {
+        let (term, count) = row?;
+        terms.insert(term.clone(), Term { term, count: count as usize, curated: false });
+    }
+
+    let mut stmt = conn.prepare("SELECT term FROM Vocabulary WHERE column_name = ?1")?;
+    let rows = stmt.query_map(params![column], |row| row.get::<_, String>(0))?;
+    for row in rows {
+        let term = row?;
+        terms.entry(term.clone())
+            .or_insert(Term { term, count: 0, curated: false })
+            .curated = true;
+    }
+
+    let mut terms: Vec<Term> = terms.into_values().collect();
+    terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+    Ok(terms)
+}
+
+pub fn add_term(conn: &Connection, column: &str, term: &str) -> Result<(), DbError> {
+    let term = term.trim();
+    if !is_vocabulary_column(column) || term.is_empty() {
+        return Err(DbError::Invalid("Неверный столбец или пустой термин".to_string()));
+    }
+    conn.execute(
+        "INSERT OR IGNORE INTO Vocabulary (column_name, term) VALUES (?1, ?2)",
+        params![column, term],
+    )?;
+    Ok(())
+}
+
+pub fn remove_term(conn: &Connection, column: &str, term: &str) -> Result<(), DbError> {
+    conn.execute(
+        "DELETE FROM Vocabulary WHERE column_name = ?1 AND term = ?2",
+        params![column, term],
+    )?;
+    Ok(())
+}
+
+// Подсказки для введённого текста: сначала термины, начинающиеся с него,
+// затем содержащие его; регистр не учитывается
+pub fn suggest<'a>(terms: &'a [Term], input: &str, limit: usize) -> Vec<&'a str> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return terms.iter().take(limit).map(|t| t.term.as_str()).collect();
+    }
+
+    let lower: Vec<String> = terms.iter().map(|t| t.term.to_lowercase()).collect();
+    let prefix = terms.iter().zip(&lower).filter(|(_, l)| l.starts_with(&input));
+    let inner = terms.iter().zip(&lower).filter(|(_, l)| !l.starts_with(&input) && l.contains(&input));
+    prefix.chain(inner)
+        .map(|(t, _)| t.term.as_str())
+        .filter(|t| t.to_lowercase() != input)
+        .take(limit)
+        .collect()
+}
+
+// Ключ для поиска вариантов написания: регистр, пробелы и знаки препинания не учитываются
+pub fn variant_key(term: &str) -> String {
+    term.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Замена вариантов написания одним термином во всех записях.
+// Варианты исключаются из утверждённых терминов, итоговый термин добавляется.
+// Записи и словарь меняются одной транзакцией; отмена возвращает и то и другое
+pub fn merge_variants(
+    conn: &Connection,
+    records: &[Eucarinogammarus],
+    column: &str,
+    variants: &[String],
+    target: &str,
+) -> Result<Command, DbError> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err(DbError::Invalid("Не задан итоговый термин".to_string()));
+    }
+
+    let changes: Vec<BulkChange> = records.iter()
+        .filter_map(|r| {
+            let old = r.field(column)?;
+            let matches = variants.iter().any(|v| v == old.trim());
+            (matches && old != target).then(|| BulkChange {
+                id: r.id,
+                name: format!("{} {}", r.genus, r.species),
+                old: old.to_string(),
+                new: target.to_string(),
+            })
+        })
+        .collect();
+
+    let tx = conn.unchecked_transaction()?;
+    let mut commands = bulk::write_changes(&tx, column, &changes)?;
+
+    // В команду отмены попадают только действительно исключённые и добавленные термины
+    let mut removed = Vec::new();
+    for variant in variants.iter().filter(|v| v.as_str() != target) {
+        if has_term(&tx, column, variant)? {
+            remove_term(&tx, column, variant)?;
+            removed.push(variant.clone());
+        }
+    }
+    let mut added = Vec::new();
+    if !has_term(&tx, column, target)? {
+        add_term(&tx, column, target)?;
+        added.push(target.to_string());
+    }
+    tx.commit()?;
+
+    commands.push(Command::Terms { column: column.to_string(), removed, added });
+    Ok(Command::Bulk {
+        description: format!("объединение вариантов {} ({} записей)", column, changes.len()),
+        commands,
+    })
+}
+
+fn has_term(conn: &Connection, column: &str, term: &str) -> Result<bool, DbError> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM Vocabulary WHERE column_name = ?1 AND term = ?2",
+        params![column, term],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
@@ -0,0 +1,78 @@
+// Проверка настроек пользователя: список недавних баз и чтение/запись config.toml
+use std::path::PathBuf;
+
+use eucarinogammarus_db::config::{BackupSettings, Config};
+
+// Каталог с уникальным для теста именем
+fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("eucarinogammarus-config-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn recent_databases_are_deduplicated_and_capped() {
+    let mut config = Config::default();
+    // Несуществующие пути запоминаются как есть
+    config.remember(&PathBuf::from("/нет/a.db"));
+    config.remember(&PathBuf::from("/нет/b.db"));
+    config.remember(&PathBuf::from("/нет/a.db"));
+    assert_eq!(config.recent_databases, [PathBuf::from("/нет/a.db"), PathBuf::from("/нет/b.db")]);
+    assert_eq!(config.last_database, Some(PathBuf::from("/нет/a.db")));
+
+    for i in 0..15 {
+        config.remember(&PathBuf::from(format!("/нет/{}.db", i)));
+    }
+    assert_eq!(config.recent_databases.len(), 10);
+    assert_eq!(config.recent_databases[0], PathBuf::from("/нет/14.db"));
+    assert_eq!(config.recent_databases[9], PathBuf::from("/нет/5.db"));
+    assert_eq!(config.database_path(None), PathBuf::from("/нет/14.db"));
+}
+
+#[test]
+fn existing_paths_are_remembered_canonically() {
+    let dir = temp_dir("canonical");
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = dir.join("base.db");
+    std::fs::write(&db, "").unwrap();
+
+    let mut config = Config::default();
+    config.remember(&db);
+    config.remember(&dir.join(".").join("base.db"));
+    assert_eq!(config.recent_databases, [std::fs::canonicalize(&db).unwrap()]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn config_round_trips_through_toml() {
+    let dir = temp_dir("toml");
+    let path = dir.join("nested").join("config.toml");
+
+    // Отсутствующий файл даёт настройки по умолчанию
+    let empty = Config::load_from(&path).unwrap();
+    assert!(empty.recent_databases.is_empty());
+    assert_eq!(empty.backup, BackupSettings::default());
+
+    let mut config = Config::default();
+    config.remember(&PathBuf::from("/нет/Байкал.db"));
+    config.remember(&PathBuf::from("/нет/b.db"));
+    config.backup.keep = 3;
+    config.backup.on_startup = false;
+    config.backup.directory = Some(PathBuf::from("/нет/снимки"));
+    config.save_to(&path).unwrap();
+
+    let loaded = Config::load_from(&path).unwrap();
+    assert_eq!(loaded.last_database, config.last_database);
+    assert_eq!(loaded.recent_databases, config.recent_databases);
+    assert_eq!(loaded.backup, config.backup);
+
+    // Недостающие поля берутся по умолчанию
+    std::fs::write(&path, "last_database = \"/нет/c.db\"\n").unwrap();
+    let partial = Config::load_from(&path).unwrap();
+    assert_eq!(partial.last_database, Some(PathBuf::from("/нет/c.db")));
+    assert_eq!(partial.backup, BackupSettings::default());
+
+    std::fs::write(&path, "recent_databases = 5\n").unwrap();
+    assert!(Config::load_from(&path).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,866 @@
+// Проверка публичного API библиотеки на базе в памяти
+use std::path::PathBuf;
+
+use eucarinogammarus_db::audit;
+use eucarinogammarus_db::backup::{self, SnapshotReason};
+use eucarinogammarus_db::config::BackupSettings;
+use eucarinogammarus_db::dwca::export::{DwcMapping, ExportOptions};
+use eucarinogammarus_db::dwca::import;
+use eucarinogammarus_db::merge::ChangeKind;
+use eucarinogammarus_db::undo::{Command, UndoStack};
+use eucarinogammarus_db::vocabulary;
+use eucarinogammarus_db::{db, Database, DbError, Eucarinogammarus, SortDirection};
+
+fn record(code: &str, species: &str, size_mm: &str) -> Eucarinogammarus {
+    Eucarinogammarus {
+        code: code.to_string(),
+        genus: "Eucarinogammarus".to_string(),
+        species: species.to_string(),
+        size_mm: size_mm.to_string(),
+        ..Default::default()
+    }
+}
+
+fn seeded() -> Database {
+    let db = Database::open_in_memory().unwrap();
+    db.insert(&record("E1", "wagii", "20-25")).unwrap();
+    db.insert(&record("E2", "cyaneus", "12")).unwrap();
+    db.insert(&record("E3", "ruber", "30")).unwrap();
+    db
+}
+
+// Временный файл с уникальным для теста именем
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("eucarinogammarus-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn migrations_are_applied_once() {
+    let db = Database::open_in_memory().unwrap();
+    assert_eq!(db.schema_version().unwrap(), db::SCHEMA_VERSION);
+
+    db.migrate().unwrap();
+    assert_eq!(db.schema_version().unwrap(), db::SCHEMA_VERSION);
+    assert_eq!(db.count().unwrap(), 0);
+}
+
+#[test]
+fn insert_and_read_back() {
+    let db = Database::open_in_memory().unwrap();
+    let mut new = record("E1", "wagii", "20-25");
+    new.coloration = "красная".to_string();
+
+    let id = db.insert(&new).unwrap();
+    let stored = db.record(id).unwrap().unwrap();
+
+    new.id = id;
+    assert_eq!(stored, new);
+    assert!(db.record(id + 1).unwrap().is_none());
+}
+
+#[test]
+fn insert_values_checks_length() {
+    let db = Database::open_in_memory().unwrap();
+    assert!(db.insert_values(&["E1", "Eucarinogammarus"]).is_err());
+}
+
+#[test]
+fn update_single_field_and_whole_record() {
+    let mut db = seeded();
+
+    let updated = db.update(1, "Окраска", "синяя").unwrap();
+    assert_eq!(updated.coloration, "синяя");
+    assert_eq!(updated.name(), "Eucarinogammarus wagii (ID 1)");
+    assert_eq!(db.record(1).unwrap().unwrap(), updated);
+    assert!(matches!(db.update(99, "Окраска", "синяя"), Err(DbError::NotFound(99))));
+    assert!(db.update(1, "id; DROP TABLE Eucarinogammarus", "x").is_err());
+
+    let mut changed = db.record(2).unwrap().unwrap();
+    changed.depth_m = "100-200".to_string();
+    changed.eyes = "чёрные".to_string();
+    let version = db.versions().unwrap()[&2];
+    assert_eq!(db.update_record(&changed, version).unwrap(), changed);
+    assert_eq!(db.record(2).unwrap().unwrap(), changed);
+
+    changed.id = 99;
+    assert!(matches!(db.update_record(&changed, 0), Err(DbError::NotFound(99))));
+}
+
+#[test]
+fn search_and_sort() {
+    let db = seeded();
+
+    let found = db.search("CYAN").unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].code, "E2");
+    assert_eq!(db.search("").unwrap().len(), 3);
+
+    let sorted = db.records_sorted("Вид", SortDirection::Descending).unwrap();
+    let species: Vec<&str> = sorted.iter().map(|r| r.species.as_str()).collect();
+    assert_eq!(species, ["wagii", "ruber", "cyaneus"]);
+
+    assert!(db.records_sorted("Вид DESC; --", SortDirection::Ascending).is_err());
+}
+
+#[test]
+fn delete_restore_and_purge() {
+    let mut db = seeded();
+
+    assert_eq!(db.delete(2).unwrap().species, "cyaneus");
+    assert_eq!(db.count().unwrap(), 2);
+    assert!(db.record(2).unwrap().is_none());
+    assert_eq!(db.trash().unwrap().len(), 1);
+    assert_eq!(db.trash().unwrap()[0].deleted_by, db.user_name());
+
+    // Запись в корзине нельзя ни изменить, ни удалить повторно
+    assert!(matches!(db.update(2, "Окраска", "синяя"), Err(DbError::NotFound(2))));
+    assert!(matches!(db.delete(2), Err(DbError::NotFound(2))));
+
+    assert_eq!(db.restore(2).unwrap().species, "cyaneus");
+    assert_eq!(db.count().unwrap(), 3);
+    assert!(matches!(db.restore(2), Err(DbError::NotFound(2))));
+    assert!(matches!(db.delete(99), Err(DbError::NotFound(99))));
+
+    // Навсегда удаляются только записи из корзины
+    assert!(matches!(db.purge(3), Err(DbError::NotFound(3))));
+    db.delete(3).unwrap();
+    assert_eq!(db.purge(3).unwrap().species, "ruber");
+    assert!(db.trash().unwrap().is_empty());
+    assert_eq!(db.count().unwrap(), 2);
+}
+
+#[test]
+fn changes_are_recorded_in_history() {
+    let db = seeded();
+    db.update(1, "Тело", "гладкое").unwrap();
+    db.delete(1).unwrap();
+
+    let history = db.history(1).unwrap();
+    let change = history.iter()
+        .find(|e| e.column_name.as_deref() == Some("Тело"))
+        .expect("изменение поля попадает в журнал");
+    assert_eq!(change.new_value.as_deref(), Some("гладкое"));
+    assert_eq!(change.user_name, db.user_name());
+
+    // Откат не трогает запись в корзине
+    let error = audit::revert_change(db.conn(), change.id).err().unwrap();
+    assert!(matches!(error, DbError::NotFound(1)));
+    db.restore(1).unwrap();
+    assert_eq!(db.record(1).unwrap().unwrap().body, "гладкое");
+    audit::revert_change(db.conn(), change.id).unwrap();
+    assert_eq!(db.record(1).unwrap().unwrap().body, "");
+}
+
+#[test]
+fn validation_rules() {
+    let db = seeded();
+
+    let errors = db.validate(None, &[("Код", "E1"), ("Вид", "Wagii"), ("Размеры_мм", "около 20")]).unwrap();
+    let columns: Vec<&str> = errors.iter().map(|e| e.column.as_str()).collect();
+    assert_eq!(columns, ["Код", "Вид", "Размеры_мм"]);
+
+    // Запись не конфликтует сама с собой
+    assert!(db.validate(Some(1), &[("Код", "E1")]).unwrap().is_empty());
+    assert!(db.validate_all().unwrap().is_empty());
+
+    // Род проверяется по словарю, только когда словарь родов заполнен
+    assert!(db.validate(None, &[("Род", "Gammarus")]).unwrap().is_empty());
+    vocabulary::add_term(db.conn(), "Род", "Eucarinogammarus").unwrap();
+    assert_eq!(db.validate(None, &[("Род", "Gammarus")]).unwrap().len(), 1);
+    assert!(db.validate(None, &[("Род", "Eucarinogammarus")]).unwrap().is_empty());
+}
+
+#[test]
+fn csv_round_trip() {
+    let source = seeded();
+    let path = temp_path("round-trip.csv");
+    let path = path.to_str().unwrap();
+
+    assert_eq!(source.export_csv(path).unwrap(), 3);
+
+    let target = Database::open_in_memory().unwrap();
+    assert_eq!(target.import_csv(path).unwrap(), 3);
+    std::fs::remove_file(path).unwrap();
+
+    let strip_id = |records: Vec<Eucarinogammarus>| -> Vec<Eucarinogammarus> {
+        records.into_iter().map(|r| Eucarinogammarus { id: 0, ..r }).collect()
+    };
+    assert_eq!(strip_id(target.records().unwrap()), strip_id(source.records().unwrap()));
+}
+
+#[test]
+fn failed_csv_import_adds_nothing() {
+    let path = temp_path("broken.csv");
+    let full_row = vec!["x"; 24].join(",");
+    std::fs::write(&path, format!("{0}\n{0}\n{0}\nE9,Eucarinogammarus\n", full_row)).unwrap();
+
+    let db = Database::open_in_memory().unwrap();
+    assert!(db.import_csv(path.to_str().unwrap()).is_err());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(db.count().unwrap(), 0);
+}
+
+#[test]
+fn open_seeded_imports_only_into_new_database() {
+    let csv = temp_path("seed.csv");
+    let file = temp_path("seed.db");
+    seeded().export_csv(csv.to_str().unwrap()).unwrap();
+
+    let db = Database::open_seeded(&file, &csv).unwrap();
+    assert_eq!(db.count().unwrap(), 3);
+    db.delete(1).unwrap();
+    db.delete(2).unwrap();
+    db.delete(3).unwrap();
+    drop(db);
+
+    // Существующая база открывается как есть, даже если в ней не осталось записей
+    let db = Database::open_seeded(&file, &csv).unwrap();
+    assert_eq!(db.count().unwrap(), 0);
+    drop(db);
+
+    std::fs::remove_file(csv).unwrap();
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn open_errors_are_typed() {
+    let file = temp_path("unseeded.db");
+    let csv = temp_path("missing.csv");
+    let error = Database::open_seeded(&file, &csv).err().unwrap();
+    assert!(matches!(error, DbError::SeedMissing(path) if path == csv));
+    assert!(!file.exists(), "без CSV файл базы не создаётся");
+
+    let garbage = temp_path("garbage.db");
+    std::fs::write(&garbage, "это не база данных, а просто текст достаточной длины".repeat(20)).unwrap();
+    let error = Database::open(&garbage).err().unwrap();
+    assert!(matches!(error, DbError::NotADatabase(_)));
+    std::fs::remove_file(garbage).unwrap();
+
+    let error = Database::open(temp_path("no-such-dir").join("db.sqlite")).err().unwrap();
+    assert!(matches!(error, DbError::CannotOpen(_)));
+}
+
+#[test]
+fn dwca_export() {
+    let db = seeded();
+    let path = temp_path("dwca.zip");
+    let path = path.to_str().unwrap();
+
+    let summary = db.export_dwca(path, &DwcMapping::default(), &ExportOptions::default()).unwrap();
+    assert_eq!((summary.taxa, summary.descriptions), (3, 3));
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+    let mut read = |name: &str| -> String {
+        let mut text = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut text).unwrap();
+        text
+    };
+    let meta = read("meta.xml");
+    assert!(meta.contains("metadata=\"eml.xml\""));
+    for term in ["taxonID", "scientificName", "genus", "specificEpithet", "kingdom", "locality"] {
+        assert!(meta.contains(&format!("term=\"http://rs.tdwg.org/dwc/terms/{}\"", term)), "нет термина {}", term);
+    }
+    assert!(meta.contains("<location>taxon.txt</location>"));
+
+    let taxon = read("taxon.txt");
+    let lines: Vec<&str> = taxon.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("id\ttaxonID\tscientificName\tgenus\tspecificEpithet"));
+    assert!(lines[1].starts_with("1\tE1\tEucarinogammarus wagii\tEucarinogammarus\twagii\t"));
+    assert!(lines[3].starts_with("3\tE3\t"));
+
+    let description = read("description.txt");
+    assert!(description.lines().any(|l| l == "2\t12\tРазмеры_мм\tru"));
+    assert!(read("eml.xml").contains("<eml:eml"));
+    std::fs::remove_file(path).unwrap();
+
+    // Запись без кода не проходит проверку обязательного taxonID
+    db.insert(&record("", "ruber", "")).unwrap();
+    let error = db.export_dwca(path, &DwcMapping::default(), &ExportOptions::default()).unwrap_err();
+    match error {
+        DbError::ExportRejected(validation) => assert!(validation.issues.iter().any(|i| i.term == "taxonID")),
+        other => panic!("ожидалась ошибка проверки, получено: {}", other),
+    }
+}
+
+#[test]
+fn save_as_copies_database() {
+    let db = seeded();
+    let path = temp_path("copy.db");
+
+    db.save_as(&path).unwrap();
+    assert!(db.save_as(&path).is_err(), "существующий файл не перезаписывается");
+
+    let copy = Database::open(&path).unwrap();
+    assert_eq!(copy.path(), Some(path.as_path()));
+    assert_eq!(copy.records().unwrap(), db.records().unwrap());
+    drop(copy);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn sqlite_errors_are_classified() {
+    let db = seeded();
+
+    let error: DbError = db.conn()
+        .execute("INSERT INTO Specimen (taxon_id) VALUES (NULL)", [])
+        .unwrap_err()
+        .into();
+    assert!(matches!(error, DbError::Constraint(_)), "{:?}", error);
+    assert_eq!(error.title(), "Нарушено ограничение");
+
+    let error = db.update(1, "Нет такого столбца", "x").unwrap_err();
+    assert!(matches!(error, DbError::InvalidColumn(_)));
+}
+
+#[test]
+fn snapshots_rotate_and_restore() {
+    let dir = temp_path("backups");
+    let file = temp_path("snapshots.db");
+    let settings = BackupSettings { keep: 2, directory: Some(dir.clone()), ..Default::default() };
+
+    let mut db = Database::open(&file).unwrap();
+    db.insert(&record("E1", "wagii", "20")).unwrap();
+    db.insert(&record("E2", "cyaneus", "12")).unwrap();
+    let manual = db.snapshot(&settings, SnapshotReason::Manual).unwrap();
+
+    // Автоматических снимков каждого вида остаётся не больше keep, снимки вручную не удаляются
+    for _ in 0..3 {
+        db.snapshot(&settings, SnapshotReason::Startup).unwrap();
+    }
+    let snapshots = db.snapshots(&settings).unwrap();
+    assert_eq!(snapshots.iter().filter(|s| s.reason == SnapshotReason::Startup).count(), 2);
+    assert!(snapshots.iter().any(|s| s.path == manual.path));
+
+    db.delete(1).unwrap();
+    db.insert(&record("E3", "ruber", "30")).unwrap();
+    let info = backup::inspect(&manual.path, None).unwrap();
+    assert_eq!((info.records, info.trashed), (2, 0));
+
+    let (before, _) = db.restore_snapshot(&settings, &manual.path).unwrap();
+    let species: Vec<String> = db.records().unwrap().into_iter().map(|r| r.species).collect();
+    assert_eq!(species, ["wagii", "cyaneus"]);
+    assert_eq!(backup::inspect(&before.path, None).unwrap().records, 2);
+    assert_eq!(before.reason, SnapshotReason::Restore);
+
+    // Журнал изменений продолжает работать после восстановления
+    db.update(1, "Окраска", "синяя").unwrap();
+    assert!(db.history(1).unwrap().iter().any(|e| e.new_value.as_deref() == Some("синяя")));
+
+    assert!(Database::open_in_memory().unwrap().snapshot(&settings, SnapshotReason::Manual).is_err());
+
+    drop(db);
+    std::fs::remove_dir_all(dir).unwrap();
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn compare_and_merge_databases() {
+    let source_path = temp_path("merge-source.db");
+    let target_path = temp_path("merge-target.db");
+
+    let source = Database::open(&source_path).unwrap();
+    let mut wagii = record("E1", "wagii", "20-25");
+    wagii.coloration = "синяя".to_string();
+    wagii.eyes = "чёрные".to_string();
+    source.insert(&wagii).unwrap();
+    source.insert(&record("E2", "cyaneus", "12")).unwrap();
+    source.insert(&record("E4", "flavus", "8")).unwrap();
+    drop(source);
+
+    let target = Database::open(&target_path).unwrap();
+    let mut wagii = record("E1", "Wagii ", "20-25");
+    wagii.coloration = "красная".to_string();
+    target.insert(&wagii).unwrap();
+    target.insert(&record("E2", "cyaneus", "12")).unwrap();
+    target.insert(&record("E3", "ruber", "30")).unwrap();
+
+    assert!(target.compare_with(&target_path).is_err(), "база не сравнивается сама с собой");
+    let pending = target.compare_with(&source_path).unwrap();
+    assert_eq!(pending.count(ChangeKind::Added), 1);
+    assert_eq!(pending.count(ChangeKind::Removed), 1);
+    assert_eq!(pending.count(ChangeKind::Changed), 1);
+    assert_eq!(pending.unchanged, 1);
+
+    // Пустое поле дополняется, в конфликте по умолчанию остаётся значение целевой базы
+    let changed = pending.changes.iter().find(|c| c.kind == ChangeKind::Changed).unwrap();
+    let take: Vec<(&str, bool)> = changed.fields.iter().map(|f| (f.column, f.take_source)).collect();
+    assert_eq!(take, [("Вид", false), ("Окраска", false), ("Глаза", true)]);
+    assert_eq!(changed.conflicts(), 2);
+
+    let (summary, command) = target.apply_merge(&pending).unwrap();
+    assert_eq!((summary.added, summary.updated, summary.removed), (1, 1, 0));
+    let merged = target.record(1).unwrap().unwrap();
+    assert_eq!((merged.coloration.as_str(), merged.eyes.as_str()), ("красная", "чёрные"));
+    assert_eq!(target.count().unwrap(), 4);
+
+    // Слияние отменяется целиком
+    let mut undo = UndoStack::default();
+    undo.push(command);
+    undo.undo(target.conn(), target.user_name()).unwrap();
+    assert_eq!(target.count().unwrap(), 3);
+    assert_eq!(target.record(1).unwrap().unwrap().eyes, "");
+
+    drop(target);
+    std::fs::remove_file(&source_path).unwrap();
+    std::fs::remove_file(target_path).unwrap();
+    // Соединение только для чтения оставляет файлы журнала WAL источника
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", source_path.display(), suffix));
+    }
+}
+
+#[test]
+fn existing_records_get_distinct_uuids() {
+    let db = seeded();
+    // Схема до UUID: столбцы, индекс и триггеры версий 6 и 7 убираются
+    db.conn().execute_batch(
+        "DROP TRIGGER record_version;
+        ALTER TABLE Eucarinogammarus DROP COLUMN version;
+        DROP TRIGGER record_created;
+        DROP TRIGGER record_modified;
+        DROP TRIGGER record_purged;
+        DROP INDEX record_uuid;
+        DROP TABLE Tombstone;
+        DROP TABLE SyncLog;
+        ALTER TABLE Eucarinogammarus DROP COLUMN uuid;
+        ALTER TABLE Eucarinogammarus DROP COLUMN modified_at;
+        PRAGMA user_version = 5;",
+    ).unwrap();
+
+    db.migrate().unwrap();
+    let (distinct, dated): (i64, i64) = db.conn().query_row(
+        "SELECT COUNT(DISTINCT uuid), COUNT(modified_at) FROM Eucarinogammarus",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert_eq!((distinct, dated), (3, 3));
+}
+
+#[test]
+fn changesets_sync_copies() {
+    let (first, second) = (temp_path("changes-1.toml"), temp_path("changes-2.toml"));
+    let a = seeded();
+    let b = Database::open_in_memory().unwrap();
+
+    a.export_changes(&first, None).unwrap();
+    let summary = b.import_changes(&first).unwrap();
+    assert_eq!((summary.inserted, summary.updated, summary.skipped, summary.repeated), (3, 0, 0, false));
+
+    // Повторный импорт ничего не меняет
+    let summary = b.import_changes(&first).unwrap();
+    assert_eq!((summary.inserted, summary.updated, summary.skipped, summary.repeated), (0, 0, 3, true));
+    assert_eq!(b.count().unwrap(), 3);
+
+    // Из двух правок одной записи остаётся более поздняя
+    let wagii = |db: &Database| db.records().unwrap().into_iter().find(|r| r.species == "wagii").unwrap();
+    a.update(wagii(&a).id, "Окраска", "красная").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    b.update(wagii(&b).id, "Окраска", "синяя").unwrap();
+
+    a.export_changes(&first, None).unwrap();
+    b.export_changes(&second, None).unwrap();
+    assert_eq!(a.import_changes(&second).unwrap().updated, 1);
+    assert_eq!(b.import_changes(&first).unwrap().updated, 0);
+    assert_eq!(wagii(&a).coloration, "синяя");
+    assert_eq!(wagii(&b).coloration, "синяя");
+
+    // Окончательное удаление передаётся надгробием; старый набор запись не возвращает
+    let mut a = a;
+    let id = wagii(&a).id;
+    a.delete(id).unwrap();
+    a.purge(id).unwrap();
+    let since = a.last_export().unwrap();
+    let changes = a.export_changes(&first, since.as_deref()).unwrap();
+    assert_eq!((changes.records.len(), changes.purged.len()), (0, 1));
+    assert_eq!(b.import_changes(&first).unwrap().deleted, 1);
+    assert_eq!(b.import_changes(&second).unwrap().inserted, 0);
+    assert_eq!(b.count().unwrap(), 2);
+    assert_eq!(a.sync_log().unwrap().len(), 4);
+
+    std::fs::remove_file(first).unwrap();
+    std::fs::remove_file(second).unwrap();
+}
+
+#[test]
+fn changesets_match_copies_upgraded_separately() {
+    // Две копии получили UUID независимо; записи сопоставляются по названию
+    let path = temp_path("changes-copies.toml");
+    let (a, b) = (seeded(), seeded());
+    a.export_changes(&path, None).unwrap();
+    let summary = b.import_changes(&path).unwrap();
+    assert_eq!(summary.inserted, 0);
+    assert_eq!(b.count().unwrap(), 3);
+
+    // Теперь записи связаны: повторная выгрузка ничего не добавляет
+    b.export_changes(&path, None).unwrap();
+    assert_eq!(a.import_changes(&path).unwrap().inserted, 0);
+    assert_eq!(a.count().unwrap(), 3);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn vocabulary_suggestions() {
+    let terms: Vec<vocabulary::Term> = ["Синяя", "тёмно-синяя", "синеватая", "красная"].iter()
+        .map(|t| vocabulary::Term { term: t.to_string(), count: 1, curated: false })
+        .collect();
+
+    // Сначала начинающиеся с введённого, затем содержащие его; без учёта регистра
+    assert_eq!(vocabulary::suggest(&terms, "син", 10), ["Синяя", "синеватая", "тёмно-синяя"]);
+    assert_eq!(vocabulary::suggest(&terms, "син", 1), ["Синяя"]);
+    // Уже введённый термин целиком не подсказывается
+    assert_eq!(vocabulary::suggest(&terms, "синяя", 10), ["тёмно-синяя"]);
+    assert_eq!(vocabulary::suggest(&terms, "  ", 2), ["Синяя", "тёмно-синяя"]);
+    assert!(vocabulary::suggest(&terms, "зелёная", 10).is_empty());
+
+    assert_eq!(vocabulary::variant_key(" Тёмно-Синяя,  с пятнами "), "тёмно синяя с пятнами");
+    assert_eq!(vocabulary::variant_key("тёмно синяя с пятнами"), vocabulary::variant_key("Тёмно-синяя (с пятнами)"));
+}
+
+#[test]
+fn vocabulary_variants_merge_and_undo() {
+    let db = seeded();
+    for (id, value) in [(1, "синяя"), (2, "Синяя "), (3, "красная")] {
+        db.update(id, "Окраска", value).unwrap();
+    }
+    vocabulary::add_term(db.conn(), "Окраска", "Синяя").unwrap();
+    vocabulary::add_term(db.conn(), "Окраска", "красная").unwrap();
+    let curated = |db: &Database| -> Vec<String> {
+        let mut terms: Vec<String> = vocabulary::load_terms(db.conn(), "Окраска").unwrap().into_iter()
+            .filter(|t| t.curated)
+            .map(|t| t.term)
+            .collect();
+        terms.sort();
+        terms
+    };
+
+    let records = db.records().unwrap();
+    let variants = ["синяя".to_string(), "Синяя".to_string()];
+    let command = vocabulary::merge_variants(db.conn(), &records, "Окраска", &variants, "синяя").unwrap();
+    let colors: Vec<String> = db.records().unwrap().into_iter().map(|r| r.coloration).collect();
+    assert_eq!(colors, ["синяя", "синяя", "красная"]);
+    assert_eq!(curated(&db), ["красная", "синяя"]);
+
+    // Отмена возвращает и значения записей, и исключённые варианты словаря
+    let mut undo = UndoStack::default();
+    undo.push(command);
+    undo.undo(db.conn(), db.user_name()).unwrap();
+    let colors: Vec<String> = db.records().unwrap().into_iter().map(|r| r.coloration).collect();
+    assert_eq!(colors, ["синяя", "Синяя ", "красная"]);
+    assert_eq!(curated(&db), ["Синяя", "красная"]);
+
+    undo.redo(db.conn(), db.user_name()).unwrap();
+    assert_eq!(curated(&db), ["красная", "синяя"]);
+
+    // Пустой итоговый термин не меняет ничего
+    assert!(vocabulary::merge_variants(db.conn(), &records, "Окраска", &variants, " ").is_err());
+}
+
+#[test]
+fn text_directory_round_trip() {
+    let (dir, copy, rebuilt) = (temp_path("text"), temp_path("text-copy"), temp_path("text-rebuilt.db"));
+    let db = seeded();
+    db.update(1, "Тело", "первая строка\nвторая строка").unwrap();
+    db.delete(3).unwrap();
+    eucarinogammarus_db::vocabulary::add_term(db.conn(), "Окраска", "красная").unwrap();
+    db.conn().execute_batch(
+        "INSERT INTO Locality (locality, water_body) VALUES ('Листвянка', 'Байкал');
+        INSERT INTO Specimen (taxon_id, locality_id, occurrence_id, event_date) VALUES (1, 1, 'occ-1', '1990-07-01');",
+    ).unwrap();
+
+    let summary = db.export_text(&dir).unwrap();
+    assert_eq!((summary.records, summary.specimens), (3, 1));
+    let wagii = std::fs::read_to_string(dir.join("species").join("Eucarinogammarus_wagii.toml")).unwrap();
+    assert!(wagii.contains("water_body = \"Байкал\""));
+    assert!(!wagii.contains("Глаза"), "пустые поля не пишутся");
+
+    let (copy_db, summary) = Database::create_from_text(&rebuilt, &dir).unwrap();
+    assert_eq!((summary.records, summary.specimens), (3, 1));
+    assert_eq!(copy_db.count().unwrap(), 2);
+    assert_eq!(copy_db.trash().unwrap().len(), 1);
+    assert!(Database::create_from_text(&rebuilt, &dir).is_err(), "существующий файл не перезаписывается");
+
+    // Собранная база выгружается в те же файлы
+    copy_db.export_text(&copy).unwrap();
+    for name in ["database.toml", "species/Eucarinogammarus_wagii.toml", "species/Eucarinogammarus_ruber.toml"] {
+        assert_eq!(std::fs::read_to_string(dir.join(name)).unwrap(), std::fs::read_to_string(copy.join(name)).unwrap());
+    }
+
+    // Файл окончательно удалённой записи убирается при следующей выгрузке
+    let mut db = db;
+    db.purge(3).unwrap();
+    assert_eq!(db.export_text(&dir).unwrap().removed, 1);
+
+    drop(copy_db);
+    std::fs::remove_dir_all(dir).unwrap();
+    std::fs::remove_dir_all(copy).unwrap();
+    std::fs::remove_file(rebuilt).unwrap();
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encrypt_rekey_and_decrypt() {
+    let (path, dir) = (temp_path("encrypted.db"), temp_path("encrypted-backups"));
+    let settings = BackupSettings { directory: Some(dir.clone()), ..Default::default() };
+    let mut db = Database::open(&path).unwrap();
+    db.insert(&record("E1", "wagii", "20")).unwrap();
+
+    db.set_password(Some("секрет")).unwrap();
+    assert!(db.is_encrypted());
+    let snapshot = db.snapshot(&settings, SnapshotReason::Manual).unwrap();
+    assert_eq!(db.inspect_snapshot(&snapshot.path).unwrap().records, 1);
+    assert!(matches!(backup::inspect(&snapshot.path, None), Err(DbError::Encrypted(_))));
+    drop(db);
+
+    assert!(matches!(Database::open(&path), Err(DbError::Encrypted(_))));
+    assert!(matches!(Database::open_with_password(&path, "не тот"), Err(DbError::Encrypted(_))));
+    let mut db = Database::open_with_password(&path, "секрет").unwrap();
+    assert_eq!(db.count().unwrap(), 1);
+
+    // Копия и восстановленный снимок зашифрованы тем же паролем
+    let copy = temp_path("encrypted-copy.db");
+    db.save_as(&copy).unwrap();
+    assert!(matches!(Database::open(&copy), Err(DbError::Encrypted(_))));
+    assert_eq!(db.open_copy(&copy).unwrap().count().unwrap(), 1);
+    std::fs::remove_file(copy).unwrap();
+    db.delete(1).unwrap();
+    db.restore_snapshot(&settings, &snapshot.path).unwrap();
+    assert_eq!(db.count().unwrap(), 1);
+
+    db.set_password(Some("другой")).unwrap();
+    drop(db);
+    let mut db = Database::open_with_password(&path, "другой").unwrap();
+
+    db.set_password(None).unwrap();
+    drop(db);
+    let db = Database::open(&path).unwrap();
+    assert_eq!(db.count().unwrap(), 1);
+    assert_eq!(db.schema_version().unwrap(), db::SCHEMA_VERSION);
+
+    drop(db);
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn concurrent_edits_are_detected() {
+    let path = temp_path("shared.db");
+    let first = Database::open(&path).unwrap();
+    first.insert(&record("E1", "wagii", "20")).unwrap();
+    let second = Database::open(&path).unwrap();
+    let mode: String = first.conn().query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+    assert_eq!(mode, "wal");
+
+    // Оба прочитали запись; второй сохраняет первым
+    let seen = first.versions().unwrap()[&1];
+    let changed = first.data_version().unwrap();
+    assert_eq!(second.versions().unwrap()[&1], seen);
+    second.update_checked(1, "Окраска", "синяя", seen).unwrap();
+    assert_ne!(first.data_version().unwrap(), changed);
+
+    // Правка первого по устаревшей версии не затирает чужую
+    let error = first.update_checked(1, "Окраска", "красная", seen).err().unwrap();
+    assert!(matches!(error, DbError::Conflict(1)));
+    assert_eq!(first.record(1).unwrap().unwrap().coloration, "синяя");
+
+    let current = first.versions().unwrap()[&1];
+    assert!(current > seen);
+    first.update_checked(1, "Окраска", "красная", current).unwrap();
+    assert_eq!(second.record(1).unwrap().unwrap().coloration, "красная");
+    assert!(matches!(second.update_checked(2, "Окраска", "", 0), Err(DbError::NotFound(2))));
+
+    // Запись целиком по устаревшей версии тоже не сохраняется, ни одно поле
+    let mut second = second;
+    let mut whole = second.record(1).unwrap().unwrap();
+    whole.code = "E9".to_string();
+    whole.coloration = "зелёная".to_string();
+    let error = second.update_record(&whole, seen).err().unwrap();
+    assert!(matches!(error, DbError::Conflict(1)));
+    assert_eq!(first.record(1).unwrap().unwrap().code, "E1");
+    let current = second.versions().unwrap()[&1];
+    assert_eq!(second.update_record(&whole, current).unwrap(), whole);
+    assert_eq!(first.record(1).unwrap().unwrap().coloration, "зелёная");
+
+    drop((first, second));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn dwca_import_defaults_to_quoted_fields_and_counts_unnamed_rows() {
+    use std::io::Write;
+
+    // meta.xml без fieldsEnclosedBy: по стандарту поля в кавычках
+    let path = temp_path("quoted.zip");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    zip.start_file("meta.xml", Default::default()).unwrap();
+    zip.write_all(br#"<archive><core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="," ignoreHeaderLines="1">
+        <files><location>occurrence.txt</location></files>
+        <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+        <field index="1" term="http://rs.tdwg.org/dwc/terms/genus"/>
+        <field index="2" term="http://rs.tdwg.org/dwc/terms/specificEpithet"/>
+        <field index="3" term="http://rs.tdwg.org/dwc/terms/locality"/>
+        </core></archive>"#).unwrap();
+    zip.start_file("occurrence.txt", Default::default()).unwrap();
+    zip.write_all("id,genus,epithet,locality\no1,\"Eucarinogammarus\",wagii,\"Байкал, Листвянка\"\no2,,,Ольхон\n".as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    let mut db = seeded();
+    let table = import::read_dwc(&path.to_string_lossy()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(table.rows[0]["genus"], "Eucarinogammarus");
+    assert_eq!(table.rows[0]["locality"], "Байкал, Листвянка");
+
+    let pending = import::match_taxa(table, &db.records().unwrap());
+    assert_eq!((pending.groups.len(), pending.unnamed), (1, 1));
+    let summary = db.apply_import(&pending).unwrap();
+    assert_eq!((summary.specimens_created, summary.rows_unnamed), (1, 1));
+}
+
+#[test]
+fn failed_undo_does_not_block_the_stack() {
+    let mut db = seeded();
+    let mut undo = UndoStack::default();
+    for (id, value) in [(2, "синяя"), (1, "красная")] {
+        db.update(id, "Окраска", value).unwrap();
+        undo.push(Command::Update { id, column: "Окраска".to_string(), old: String::new(), new: value.to_string() });
+    }
+
+    // Запись 1 тем временем ушла в корзину: её правку отменить нельзя,
+    // но отмена правки записи 2 под ней остаётся доступна
+    db.delete(1).unwrap();
+    assert!(matches!(undo.undo(db.conn(), db.user_name()), Err(DbError::NotFound(1))));
+    assert!(undo.undo(db.conn(), db.user_name()).unwrap().is_some());
+    assert_eq!(db.record(2).unwrap().unwrap().coloration, "");
+
+    // После удаления навсегда операции над записью забываются
+    assert!(undo.can_redo());
+    db.delete(2).unwrap();
+    db.purge(2).unwrap();
+    undo.forget(2);
+    assert!(!undo.can_undo() && !undo.can_redo());
+}
+
+#[test]
+fn checked_writes_treat_null_as_empty() {
+    use eucarinogammarus_db::bulk::{self, BulkMode};
+
+    // NULL, записанный другой программой, читается и проверяется как пустая строка
+    let db = seeded();
+    db.conn().execute("UPDATE Eucarinogammarus SET Окраска = NULL WHERE id = 3", []).unwrap();
+    db::update_field_expecting(db.conn(), 3, "Окраска", "", "серая").unwrap();
+    db.conn().execute("UPDATE Eucarinogammarus SET Окраска = NULL WHERE id = 3", []).unwrap();
+    let records = db.records().unwrap();
+    let changes = bulk::preview(&records.iter().collect::<Vec<_>>(), "Окраска", BulkMode::Set, "", "серая").unwrap();
+    bulk::apply(db.conn(), "Окраска", &changes).unwrap();
+    let mut undo = UndoStack::default();
+    undo.push(Command::Update { id: 3, column: "Окраска".to_string(), old: String::new(), new: "серая".to_string() });
+    undo.undo(db.conn(), db.user_name()).unwrap();
+    assert_eq!(db.record(3).unwrap().unwrap().coloration, "");
+}
+
+#[test]
+fn stale_operations_are_rejected() {
+    use eucarinogammarus_db::bulk::{self, BulkMode};
+
+    let db = seeded();
+    let records = db.records().unwrap();
+    let changes = bulk::preview(&records.iter().collect::<Vec<_>>(), "Окраска", BulkMode::Set, "", "серая").unwrap();
+    assert_eq!(changes.len(), 3);
+
+    // Другой пользователь изменил поле после предпросмотра: не меняется ничего
+    db.update(2, "Окраска", "синяя").unwrap();
+    assert!(matches!(bulk::apply(db.conn(), "Окраска", &changes), Err(DbError::Conflict(2))));
+    let colors: Vec<String> = db.records().unwrap().into_iter().map(|r| r.coloration).collect();
+    assert_eq!(colors, ["", "синяя", ""]);
+
+    // Отмена не затирает поле, изменённое после операции
+    let mut undo = UndoStack::default();
+    db.update(1, "Окраска", "красная").unwrap();
+    undo.push(Command::Update { id: 1, column: "Окраска".to_string(), old: String::new(), new: "красная".to_string() });
+    db.update(1, "Окраска", "бурая").unwrap();
+    assert!(matches!(undo.undo(db.conn(), db.user_name()), Err(DbError::Conflict(1))));
+    assert_eq!(db.record(1).unwrap().unwrap().coloration, "бурая");
+
+    // Откат изменения, поверх которого уже есть другое
+    let history = db.history(1).unwrap();
+    let first = history.iter().find(|e| e.new_value.as_deref() == Some("красная")).unwrap();
+    assert!(matches!(audit::revert_change(db.conn(), first.id), Err(DbError::Conflict(1))));
+    assert_eq!(db.record(1).unwrap().unwrap().coloration, "бурая");
+}
+
+#[test]
+fn duplicate_merge_records_the_given_author() {
+    use eucarinogammarus_db::duplicates;
+
+    let db = seeded();
+    db.update(2, "Глаза", "чёрные").unwrap();
+    let records = db.records().unwrap();
+    let group: Vec<&Eucarinogammarus> = records.iter().filter(|r| r.id != 3).collect();
+    let values = vec![("Глаза".to_string(), "чёрные".to_string())];
+
+    let command = duplicates::merge(db.conn(), 1, &values, &group, "куратор").unwrap();
+    assert_eq!(db.record(1).unwrap().unwrap().eyes, "чёрные");
+    let trash = db.trash().unwrap();
+    assert_eq!((trash[0].record.id, trash[0].deleted_by.as_str()), (2, "куратор"));
+    assert!(db.history(2).unwrap().iter().any(|e| e.action == "MERGE" && e.user_name == "куратор"));
+
+    let mut undo = UndoStack::default();
+    undo.push(command);
+    undo.undo(db.conn(), db.user_name()).unwrap();
+    assert_eq!(db.record(1).unwrap().unwrap().eyes, "");
+    assert_eq!(db.count().unwrap(), 3);
+}
+
+#[test]
+fn distinct_bare_species_are_not_duplicates() {
+    use eucarinogammarus_db::duplicates::{find_duplicates, DuplicateKind};
+
+    // Только род и вид, как у видов, созданных импортом DwC
+    let mut records = vec![
+        Eucarinogammarus { id: 1, genus: "Eucarinogammarus".to_string(), species: "wagii".to_string(), ..Default::default() },
+        Eucarinogammarus { id: 2, genus: "Eucarinogammarus".to_string(), species: "ruber".to_string(), ..Default::default() },
+        Eucarinogammarus { id: 3, genus: "Eucarinogammarus".to_string(), species: "cyaneus".to_string(), ..Default::default() },
+    ];
+    assert!(find_duplicates(&records, 0.85).is_empty());
+
+    // Опечатка в эпитете при одинаковом описании — вероятный дубликат
+    records[0].body = "тело с высокими килями".to_string();
+    records.push(Eucarinogammarus { id: 4, species: "wagi".to_string(), ..records[0].clone() });
+    let groups = find_duplicates(&records, 0.85);
+    assert_eq!(groups.len(), 1);
+    assert_eq!((groups[0].kind, groups[0].ids.as_slice()), (DuplicateKind::Near, [1, 4].as_slice()));
+}
+
+#[test]
+fn bulk_regex_replace_and_append() {
+    use eucarinogammarus_db::bulk::{self, BulkMode};
+
+    let db = seeded();
+    let records = db.records().unwrap();
+    let records: Vec<&Eucarinogammarus> = records.iter().collect();
+
+    // Группы захвата подставляются в замену; записи без совпадения пропускаются
+    let changes = bulk::preview(&records, "Размеры_мм", BulkMode::RegexReplace, r"(\d+)-(\d+)", "$2-$1").unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!((changes[0].id, changes[0].old.as_str(), changes[0].new.as_str()), (1, "20-25", "25-20"));
+    let changes = bulk::preview(&records, "Размеры_мм", BulkMode::RegexReplace, r"^(\d+)$", "${1} мм").unwrap();
+    let sizes: Vec<&str> = changes.iter().map(|c| c.new.as_str()).collect();
+    assert_eq!(sizes, ["12 мм", "30 мм"]);
+    assert!(bulk::preview(&records, "Размеры_мм", BulkMode::RegexReplace, "(", "").is_err());
+
+    // Дописывание к пустому и непустому полю
+    db.update(2, "Окраска", "серая").unwrap();
+    let records = db.records().unwrap();
+    let changes = bulk::preview(&records.iter().collect::<Vec<_>>(), "Окраска", BulkMode::Append, "", ", пятнистая").unwrap();
+    assert_eq!(changes.len(), 3);
+    let mut undo = UndoStack::default();
+    undo.push(bulk::apply(db.conn(), "Окраска", &changes).unwrap());
+    let colors: Vec<String> = db.records().unwrap().into_iter().map(|r| r.coloration).collect();
+    assert_eq!(colors, [", пятнистая", "серая, пятнистая", ", пятнистая"]);
+
+    // Пустое дополнение ничего не меняет, а отмена возвращает все поля
+    assert!(bulk::preview(&db.records().unwrap().iter().collect::<Vec<_>>(), "Окраска", BulkMode::Append, "", "").unwrap().is_empty());
+    undo.undo(db.conn(), db.user_name()).unwrap();
+    let colors: Vec<String> = db.records().unwrap().into_iter().map(|r| r.coloration).collect();
+    assert_eq!(colors, ["", "серая", ""]);
+}
@@ -0,0 +1,48 @@
+// Проверка пословного сравнения описаний
+use eucarinogammarus_db::diff::{diff_words, DiffOp};
+
+fn same(text: &str) -> DiffOp {
+    DiffOp::Same(text.to_string())
+}
+
+fn removed(text: &str) -> DiffOp {
+    DiffOp::Removed(text.to_string())
+}
+
+fn added(text: &str) -> DiffOp {
+    DiffOp::Added(text.to_string())
+}
+
+#[test]
+fn equal_texts_are_one_part() {
+    // Пробелы между словами не различаются
+    assert_eq!(diff_words("тело с килями", "тело  с\nкилями"), [same("тело с килями")]);
+    assert!(diff_words("", "  ").is_empty());
+}
+
+#[test]
+fn pure_insertion_and_deletion() {
+    assert_eq!(diff_words("", "тело гладкое"), [added("тело гладкое")]);
+    assert_eq!(diff_words("тело гладкое", ""), [removed("тело гладкое")]);
+    assert_eq!(
+        diff_words("тело гладкое", "тело очень гладкое"),
+        [same("тело"), added("очень"), same("гладкое")]
+    );
+    assert_eq!(
+        diff_words("глаза чёрные крупные овальные", "глаза овальные"),
+        [same("глаза"), removed("чёрные крупные"), same("овальные")]
+    );
+}
+
+#[test]
+fn changed_word_in_the_middle() {
+    // Удалённое слово идёт перед добавленным
+    assert_eq!(
+        diff_words("глаза чёрные крупные", "глаза красные крупные"),
+        [same("глаза"), removed("чёрные"), added("красные"), same("крупные")]
+    );
+    assert_eq!(
+        diff_words("шипы на I-VII сегментах", "шипы на I-V сегментах, кили высокие"),
+        [same("шипы на"), removed("I-VII сегментах"), added("I-V сегментах, кили высокие")]
+    );
+}
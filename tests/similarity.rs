@@ -0,0 +1,89 @@
+// Проверка расстояний Гауэра и кластеризации UPGMA на небольших наборах
+use eucarinogammarus_db::similarity::{distance_matrix, DistanceMatrix};
+use eucarinogammarus_db::Eucarinogammarus;
+
+fn record(id: i32, species: &str, size_mm: &str, eyes: &str) -> Eucarinogammarus {
+    Eucarinogammarus {
+        id,
+        genus: "Eucarinogammarus".to_string(),
+        species: species.to_string(),
+        size_mm: size_mm.to_string(),
+        eyes: eyes.to_string(),
+        ..Default::default()
+    }
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn equal_records_are_at_zero_distance() {
+    let records = [
+        record(1, "wagii", "10-12", "чёрные"),
+        record(2, "cyaneus", "11", "Чёрные "),
+    ];
+    let matrix = distance_matrix(&records);
+    assert!(close(matrix.values[0][1], 0.0));
+    assert!(close(matrix.values[1][0], 0.0));
+    assert!(close(matrix.values[0][0], 0.0));
+}
+
+#[test]
+fn gower_averages_shared_features() {
+    let records = [
+        record(1, "wagii", "10", "чёрные"),
+        record(2, "cyaneus", "20", "чёрные"),
+        record(3, "ruber", "15", "красные"),
+        record(4, "flavus", "", ""),
+    ];
+    let matrix = distance_matrix(&records);
+
+    // Размер нормируется на размах 10..20, глаза сравниваются на равенство
+    assert!(close(matrix.values[0][1], (1.0 + 0.0) / 2.0));
+    assert!(close(matrix.values[0][2], (0.5 + 1.0) / 2.0));
+    assert!(close(matrix.values[1][2], (0.5 + 1.0) / 2.0));
+    // Без общих заполненных признаков — максимальное расстояние
+    assert!(close(matrix.values[0][3], 1.0));
+
+    let nearest: Vec<i32> = matrix.most_similar(1, 2).into_iter().map(|(id, _)| id).collect();
+    assert_eq!(nearest, [2, 3]);
+}
+
+#[test]
+fn descriptive_text_is_compared_by_tfidf() {
+    let mut records = [
+        record(1, "wagii", "", ""),
+        record(2, "cyaneus", "", ""),
+        record(3, "ruber", "", ""),
+    ];
+    records[0].body = "тело с высокими килями и шипами на спине".to_string();
+    records[1].body = "тело с высокими килями и шипами на спине".to_string();
+    records[2].body = "гладкое округлое без выростов".to_string();
+
+    let matrix = distance_matrix(&records);
+    assert!(close(matrix.values[0][1], 0.0));
+    assert!(close(matrix.values[0][2], 1.0));
+}
+
+#[test]
+fn upgma_merges_closest_pair_first() {
+    let matrix = DistanceMatrix {
+        ids: vec![1, 2, 3],
+        labels: vec!["E a".to_string(), "E b".to_string(), "E c".to_string()],
+        values: vec![
+            vec![0.0, 0.2, 0.6],
+            vec![0.2, 0.0, 0.8],
+            vec![0.6, 0.8, 0.0],
+        ],
+    };
+
+    // a и b сливаются на высоте 0.1; до c от них в среднем 0.7, высота 0.35
+    assert_eq!(matrix.upgma_newick(), "(E_c:0.3500,(E_a:0.1000,E_b:0.1000):0.2500);");
+}
+
+#[test]
+fn upgma_of_empty_and_single_matrix() {
+    assert_eq!(distance_matrix(&[]).upgma_newick(), ";");
+    assert_eq!(distance_matrix(&[record(1, "wagii", "", "")]).upgma_newick(), "Eucarinogammarus_wagii;");
+}